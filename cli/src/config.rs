@@ -0,0 +1,156 @@
+//! JSON file formats read by the CLI.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use smart_wallet::{EnrollmentFee, GidRange, RewardCurve, StakeData, TXAccountMeta, TXInstruction};
+use solana_sdk::pubkey::Pubkey;
+use std::{fs, path::Path, str::FromStr};
+
+/// An instruction in an instruction file.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionConfig {
+    pub program_id: String,
+    pub keys: Vec<AccountMetaConfig>,
+    /// Base64 encoded instruction data.
+    pub data: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMetaConfig {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Reads a JSON array of instructions.
+pub fn read_instructions(path: &Path) -> Result<Vec<TXInstruction>> {
+    let configs: Vec<InstructionConfig> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    configs
+        .into_iter()
+        .map(|ix| {
+            Ok(TXInstruction {
+                program_id: parse_pubkey(&ix.program_id)?,
+                keys: ix
+                    .keys
+                    .into_iter()
+                    .map(|meta| {
+                        Ok(TXAccountMeta {
+                            pubkey: parse_pubkey(&meta.pubkey)?,
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+                data: base64::decode(&ix.data)?,
+            })
+        })
+        .collect()
+}
+
+/// Renders instructions as the JSON array read by [read_instructions].
+pub fn write_instructions(ixs: &[TXInstruction]) -> Result<String> {
+    let configs: Vec<InstructionConfig> = ixs
+        .iter()
+        .map(|ix| InstructionConfig {
+            program_id: ix.program_id.to_string(),
+            keys: ix
+                .keys
+                .iter()
+                .map(|meta| AccountMetaConfig {
+                    pubkey: meta.pubkey.to_string(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: base64::encode(&ix.data),
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&configs)?)
+}
+
+/// The configuration of a new stake.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeConfig {
+    pub duration: i32,
+    pub genesis_epoch: i64,
+    pub name: String,
+    pub reward_pot: i64,
+    #[serde(default)]
+    pub protected_gids: Vec<u16>,
+    /// Inclusive `[start, end]` ranges of protected gids.
+    #[serde(default)]
+    pub protected_gid_ranges: Vec<(u16, u16)>,
+    pub uuid: String,
+    /// Fixed rate per ticket per second. Ignored if `maxRate` is set.
+    #[serde(default)]
+    pub rate: u64,
+    /// If set, spreads the reward pot across all mints, capped at this rate.
+    pub max_rate: Option<u64>,
+    #[serde(default)]
+    pub distribution_interval: i64,
+    #[serde(default)]
+    pub max_rewards_per_owner: u64,
+    #[serde(default)]
+    pub mint_allowlist: bool,
+    #[serde(default)]
+    pub min_hold_seconds: i64,
+    pub enrollment_fee: Option<EnrollmentFeeConfig>,
+    #[serde(default)]
+    pub require_master_edition: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollmentFeeConfig {
+    /// Mint of the fee token. Lamports are charged if unset.
+    pub mint: Option<String>,
+    pub amount: u64,
+    pub destination: String,
+}
+
+impl StakeConfig {
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn into_stake_data(self) -> Result<StakeData> {
+        let enrollment_fee = match self.enrollment_fee {
+            Some(fee) => Some(EnrollmentFee {
+                mint: fee.mint.as_deref().map(parse_pubkey).transpose()?,
+                amount: fee.amount,
+                destination: parse_pubkey(&fee.destination)?,
+            }),
+            None => None,
+        };
+        Ok(StakeData {
+            duration: self.duration,
+            genesis_epoch: self.genesis_epoch.to_le_bytes().to_vec(),
+            name: self.name.into_bytes(),
+            reward_pot: self.reward_pot,
+            protected_gids: self.protected_gids,
+            uuid: self.uuid.into_bytes(),
+            reward_curve: match self.max_rate {
+                Some(max_rate) => RewardCurve::InverseSupply { max_rate },
+                None => RewardCurve::Fixed { rate: self.rate },
+            },
+            distribution_interval: self.distribution_interval,
+            max_rewards_per_owner: self.max_rewards_per_owner,
+            mint_allowlist: self.mint_allowlist,
+            min_hold_seconds: self.min_hold_seconds,
+            enrollment_fee,
+            require_master_edition: self.require_master_edition,
+            protected_gid_ranges: self
+                .protected_gid_ranges
+                .into_iter()
+                .map(|(start, end)| GidRange { start, end })
+                .collect(),
+        })
+    }
+}
+
+pub fn parse_pubkey(s: &str) -> Result<Pubkey> {
+    Pubkey::from_str(s).map_err(|_| anyhow!("invalid pubkey: {}", s))
+}
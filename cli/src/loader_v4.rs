@@ -0,0 +1,61 @@
+//! Instructions managing loader-v4 programs whose authority is a wallet PDA.
+//!
+//! Upgradeable loader programs are governed by setting the wallet as their
+//! upgrade authority; loader-v4 programs instead carry the authority in the
+//! program account itself, and are managed with these instructions.
+
+use smart_wallet::{TXAccountMeta, TXInstruction};
+use solana_sdk::pubkey::Pubkey;
+
+/// The loader-v4 program.
+pub mod loader_v4_program {
+    solana_sdk::declare_id!("LoaderV411111111111111111111111111111111111");
+}
+
+/// Deploys a program whose data has already been written, signed by its authority.
+pub fn deploy(program: Pubkey, authority: Pubkey) -> TXInstruction {
+    instruction(
+        3,
+        vec![meta(program, false, true), meta(authority, true, false)],
+    )
+}
+
+/// Retracts a deployed program, so its data can be rewritten, signed by its authority.
+pub fn retract(program: Pubkey, authority: Pubkey) -> TXInstruction {
+    instruction(
+        4,
+        vec![meta(program, false, true), meta(authority, true, false)],
+    )
+}
+
+/// Hands the authority of a program to `new_authority`, which must sign too.
+pub fn transfer_authority(
+    program: Pubkey,
+    authority: Pubkey,
+    new_authority: Pubkey,
+) -> TXInstruction {
+    instruction(
+        5,
+        vec![
+            meta(program, false, true),
+            meta(authority, true, false),
+            meta(new_authority, true, false),
+        ],
+    )
+}
+
+fn instruction(tag: u32, keys: Vec<TXAccountMeta>) -> TXInstruction {
+    TXInstruction {
+        program_id: loader_v4_program::ID,
+        keys,
+        data: tag.to_le_bytes().to_vec(),
+    }
+}
+
+fn meta(pubkey: Pubkey, is_signer: bool, is_writable: bool) -> TXAccountMeta {
+    TXAccountMeta {
+        pubkey,
+        is_signer,
+        is_writable,
+    }
+}
@@ -0,0 +1,963 @@
+//! Command line interface for the Goki smart wallet.
+//!
+//! Builds, signs and sends smart wallet instructions, so operators can manage
+//! wallets and stakes without writing TypeScript.
+
+mod config;
+mod rpc;
+mod loader_v4;
+mod sns;
+mod token;
+mod vote;
+
+use anchor_lang::{
+    AccountDeserialize, AnchorDeserialize, Discriminator, InstructionData, ToAccountMetas,
+};
+use anyhow::{anyhow, Result};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use config::{parse_pubkey, read_instructions, write_instructions, StakeConfig};
+use rpc::Rpc;
+use smart_wallet::{
+    LegacySmartWallet, ProtocolConfig, ProtocolParams, SmartWallet, Stake, SubaccountInfo,
+    SubaccountType, TXAccountMeta, TXInstruction,
+};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use std::path::Path;
+
+/// Default RPC endpoint.
+const DEFAULT_URL: &str = "http://localhost:8899";
+
+struct Context {
+    rpc: Rpc,
+    payer: Keypair,
+    cosigners: Vec<Keypair>,
+}
+
+impl Context {
+    /// Signs and sends a transaction of one instruction paid by the payer.
+    fn send(&self, ix: Instruction, extra_signers: &[&Keypair]) -> Result<()> {
+        let mut signers: Vec<&dyn Signer> = vec![&self.payer];
+        signers.extend(self.cosigners.iter().map(|k| k as &dyn Signer));
+        signers.extend(extra_signers.iter().map(|k| *k as &dyn Signer));
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.payer.pubkey()),
+            &signers,
+            self.rpc.latest_blockhash()?,
+        );
+        let signature = self.rpc.send_and_confirm(&tx)?;
+        println!("signature: {}", signature);
+        Ok(())
+    }
+
+    /// The payer and cosigners, as signer metas for governed instructions.
+    fn owner_signers(&self) -> Vec<AccountMeta> {
+        std::iter::once(&self.payer)
+            .chain(self.cosigners.iter())
+            .map(|k| AccountMeta::new_readonly(k.pubkey(), true))
+            .collect()
+    }
+
+    fn cosigner_metas(&self) -> Vec<AccountMeta> {
+        self.cosigners
+            .iter()
+            .map(|k| AccountMeta::new_readonly(k.pubkey(), true))
+            .collect()
+    }
+
+    fn fetch<T: AccountDeserialize>(&self, key: &Pubkey) -> Result<T> {
+        let data = self
+            .rpc
+            .account_data(key)?
+            .ok_or_else(|| anyhow!("account {} not found", key))?;
+        Ok(T::try_deserialize(&mut data.as_slice())?)
+    }
+}
+
+fn instruction(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+    Instruction {
+        program_id: smart_wallet::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/// An instruction signed by a wallet, to be proposed as one of its transactions.
+fn wallet_instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> TXInstruction {
+    TXInstruction {
+        program_id: smart_wallet::ID,
+        keys: accounts
+            .to_account_metas(None)
+            .into_iter()
+            .map(|meta| TXAccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: data.data(),
+    }
+}
+
+/// The [ProtocolConfig], which handlers consulting it look up in their remaining accounts.
+fn protocol_config_meta() -> AccountMeta {
+    AccountMeta::new_readonly(ProtocolConfig::address().0, false)
+}
+
+fn pubkey_arg(matches: &ArgMatches, name: &str) -> Result<Pubkey> {
+    parse_pubkey(matches.value_of(name).unwrap())
+}
+
+fn parse_arg<T: std::str::FromStr>(matches: &ArgMatches, name: &str) -> Result<T> {
+    matches
+        .value_of(name)
+        .unwrap()
+        .parse()
+        .map_err(|_| anyhow!("invalid value for --{}", name))
+}
+
+fn create_wallet(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let owners = matches
+        .values_of("owners")
+        .unwrap()
+        .map(parse_pubkey)
+        .collect::<Result<Vec<_>>>()?;
+    let max_owners: u8 = match matches.value_of("max-owners") {
+        Some(_) => parse_arg(matches, "max-owners")?,
+        None => owners.len() as u8,
+    };
+    let base = Keypair::new();
+    let (smart_wallet, bump) = SmartWallet::address(base.pubkey());
+    let (treasury, _) = SmartWallet::subaccount_address(smart_wallet, SubaccountType::Derived, 0);
+    let ix = instruction(
+        smart_wallet::accounts::CreateSmartWallet {
+            base: base.pubkey(),
+            smart_wallet,
+            payer: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_wallet::instruction::CreateSmartWallet {
+            bump,
+            max_owners,
+            owners,
+            threshold: parse_arg(matches, "threshold")?,
+            minimum_delay: parse_arg(matches, "minimum-delay")?,
+        },
+        vec![
+            protocol_config_meta(),
+            AccountMeta::new(SubaccountInfo::address(treasury).0, false),
+        ],
+    );
+    ctx.send(ix, &[&base])?;
+    println!("smart wallet: {}", smart_wallet);
+    println!("treasury: {}", treasury);
+    Ok(())
+}
+
+fn import_wallet(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let legacy_smart_wallet = pubkey_arg(matches, "legacy-wallet")?;
+    let data = ctx
+        .rpc
+        .account_data(&legacy_smart_wallet)?
+        .ok_or_else(|| anyhow!("legacy wallet {} not found", legacy_smart_wallet))?;
+    let legacy = LegacySmartWallet::deserialize(&mut data.get(8..).unwrap_or_default())?;
+    let (smart_wallet, bump) = SmartWallet::address(legacy.base);
+    let mut remaining_accounts = ctx.owner_signers();
+    remaining_accounts.push(protocol_config_meta());
+    let ix = instruction(
+        smart_wallet::accounts::ImportSmartWallet {
+            legacy_smart_wallet,
+            smart_wallet,
+            payer: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_wallet::instruction::ImportSmartWallet {
+            bump,
+            max_owners: match matches.value_of("max-owners") {
+                Some(_) => parse_arg(matches, "max-owners")?,
+                None => legacy.owners.len() as u8,
+            },
+        },
+        remaining_accounts,
+    );
+    ctx.send(ix, &[])?;
+    println!("smart wallet: {}", smart_wallet);
+    Ok(())
+}
+
+fn import_transaction(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let smart_wallet = pubkey_arg(matches, "wallet")?;
+    let legacy_transaction = pubkey_arg(matches, "legacy-transaction")?;
+    let (transaction, bump) = Pubkey::find_program_address(
+        &[
+            smart_wallet::TRANSACTION_SEED,
+            &smart_wallet.to_bytes(),
+            &legacy_transaction.to_bytes(),
+        ],
+        &smart_wallet::ID,
+    );
+    let ix = instruction(
+        smart_wallet::accounts::ImportTransaction {
+            smart_wallet,
+            legacy_smart_wallet: pubkey_arg(matches, "legacy-wallet")?,
+            legacy_transaction,
+            transaction,
+            payer: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_wallet::instruction::ImportTransaction { bump },
+        ctx.owner_signers(),
+    );
+    ctx.send(ix, &[])?;
+    println!("transaction: {}", transaction);
+    Ok(())
+}
+
+fn execute(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let smart_wallet = pubkey_arg(matches, "wallet")?;
+    let index: u64 = parse_arg(matches, "index")?;
+    let ixs = read_instructions(Path::new(matches.value_of("instructions").unwrap()))?;
+    let authority_b = ctx
+        .cosigners
+        .first()
+        .ok_or_else(|| anyhow!("execute requires a --cosigner"))?;
+    let (derived, bump) =
+        SmartWallet::subaccount_address(smart_wallet, SubaccountType::Derived, index);
+
+    let mut remaining_accounts = vec![];
+    for ix in ixs.iter() {
+        remaining_accounts.push(AccountMeta::new_readonly(ix.program_id, false));
+        for key in ix.keys.iter() {
+            // The derived wallet is signed for by the program.
+            let is_signer = key.is_signer && key.pubkey != derived;
+            remaining_accounts.push(AccountMeta {
+                pubkey: key.pubkey,
+                is_signer,
+                is_writable: key.is_writable,
+            });
+        }
+    }
+    remaining_accounts.push(protocol_config_meta());
+    let wallet: SmartWallet = ctx.fetch(&smart_wallet)?;
+    if let Some(reimbursement) = wallet.executor_reimbursement {
+        let vault = Pubkey::create_program_address(
+            &[
+                smart_wallet::DERIVED_WALLET_SEED,
+                &smart_wallet.to_bytes(),
+                &reimbursement.vault_index.to_le_bytes(),
+                &[reimbursement.vault_bump],
+            ],
+            &smart_wallet::ID,
+        )?;
+        remaining_accounts.push(AccountMeta::new(vault, false));
+        remaining_accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    }
+
+    let ix = instruction(
+        smart_wallet::accounts::ExecuteInstructions {
+            smart_wallet,
+            authority_a: ctx.payer.pubkey(),
+            authority_b: authority_b.pubkey(),
+        },
+        smart_wallet::instruction::ExecuteIxs { index, bump, ixs },
+        remaining_accounts,
+    );
+    ctx.send(ix, &[])
+}
+
+fn list_queue(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let smart_wallet = pubkey_arg(matches, "wallet")?;
+    let include_executed = matches.is_present("all");
+    let mut transactions = ctx
+        .rpc
+        .program_accounts(
+            &smart_wallet::ID,
+            &[
+                (0, smart_wallet::Transaction::discriminator().to_vec()),
+                (8, smart_wallet.to_bytes().to_vec()),
+            ],
+        )?
+        .into_iter()
+        .map(|(key, data)| {
+            let tx = smart_wallet::Transaction::try_deserialize(&mut data.as_slice())?;
+            Ok((key, tx))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    transactions.sort_by_key(|(_, tx)| tx.index);
+
+    for (key, tx) in transactions {
+        if tx.executed_at != -1 && !include_executed {
+            continue;
+        }
+        let num_signers = tx.signers.iter().filter(|&did_sign| *did_sign).count();
+        let dependencies: Vec<String> = tx.dependencies.iter().map(|i| format!("#{}", i)).collect();
+        println!(
+            "#{} {} proposer={} signers={} eta={} instructions={} executed_at={} expired={} \
+             depends_on={}",
+            tx.index,
+            key,
+            tx.proposer,
+            num_signers,
+            tx.eta,
+            tx.instructions.len(),
+            tx.executed_at,
+            tx.expired,
+            dependencies.join(","),
+        );
+    }
+    Ok(())
+}
+
+fn show_transaction(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let key = pubkey_arg(matches, "transaction")?;
+    let data = ctx
+        .rpc
+        .account_data(&key)?
+        .ok_or_else(|| anyhow!("account {} not found", key))?;
+    let tx = goki_decoder::decode_transaction_account(&data)?;
+    print!("{}", goki_decoder::render_transaction(&tx));
+    Ok(())
+}
+
+fn create_stake(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let smart_wallet = pubkey_arg(matches, "wallet")?;
+    let abs_index: u64 = parse_arg(matches, "index")?;
+    let stake_data =
+        StakeConfig::read(Path::new(matches.value_of("config").unwrap()))?.into_stake_data()?;
+    let (stake, bump) = Stake::address(smart_wallet, abs_index);
+    let ix = instruction(
+        smart_wallet::accounts::CreateStake {
+            smart_wallet,
+            stake,
+            reward_mint: pubkey_arg(matches, "reward-mint")?,
+            reward_vault: pubkey_arg(matches, "reward-vault")?,
+            payer: ctx.payer.pubkey(),
+            owner: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_wallet::instruction::CreateStake {
+            bump,
+            abs_index,
+            stake_data,
+        },
+        ctx.cosigner_metas(),
+    );
+    ctx.send(ix, &[])?;
+    println!("stake: {}", stake);
+    Ok(())
+}
+
+fn extend_stake(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let stake = pubkey_arg(matches, "stake")?;
+    let stake_account: Stake = ctx.fetch(&stake)?;
+    let ix = instruction(
+        smart_wallet::accounts::ExtendStake {
+            smart_wallet: stake_account.smart_wallet,
+            stake,
+        },
+        smart_wallet::instruction::ExtendStake {
+            additional_duration: parse_arg(matches, "duration")?,
+            additional_reward_pot: parse_arg(matches, "reward-pot")?,
+        },
+        ctx.owner_signers(),
+    );
+    ctx.send(ix, &[])
+}
+
+fn set_stake_metadata(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let stake = pubkey_arg(matches, "stake")?;
+    let stake_account: Stake = ctx.fetch(&stake)?;
+    let ix = instruction(
+        smart_wallet::accounts::SetStakeMetadata {
+            smart_wallet: stake_account.smart_wallet,
+            stake,
+        },
+        smart_wallet::instruction::SetStakeMetadata {
+            uri: matches.value_of("uri").unwrap().to_string(),
+        },
+        ctx.owner_signers(),
+    );
+    ctx.send(ix, &[])
+}
+
+fn remap_gid(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let stake = pubkey_arg(matches, "stake")?;
+    let stake_account: Stake = ctx.fetch(&stake)?;
+    let ix = instruction(
+        smart_wallet::accounts::RemapGid {
+            smart_wallet: stake_account.smart_wallet,
+            stake,
+        },
+        smart_wallet::instruction::RemapGid {
+            from: parse_arg(matches, "from")?,
+            to: parse_arg(matches, "to")?,
+        },
+        ctx.owner_signers(),
+    );
+    ctx.send(ix, &[])
+}
+
+fn create_rent_pool(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let smart_wallet = pubkey_arg(matches, "wallet")?;
+    let (rent_pool, bump) = smart_wallet::RentPool::address(smart_wallet);
+    let ix = instruction(
+        smart_wallet::accounts::CreateRentPool {
+            smart_wallet,
+            rent_pool,
+            payer: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_wallet::instruction::CreateRentPool { bump },
+        ctx.owner_signers(),
+    );
+    ctx.send(ix, &[])?;
+    println!("rent pool: {}", rent_pool);
+    Ok(())
+}
+
+fn protocol_params(matches: &ArgMatches) -> Result<ProtocolParams> {
+    Ok(ProtocolParams {
+        max_owners: parse_arg(matches, "max-owners")?,
+        max_instructions: parse_arg(matches, "max-instructions")?,
+        default_grace_period: parse_arg(matches, "grace-period")?,
+        protocol_fee_bps: parse_arg(matches, "fee-bps")?,
+        fee_recipient: pubkey_arg(matches, "fee-recipient")?,
+    })
+}
+
+fn set_protocol_config(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let (protocol_config, bump) = ProtocolConfig::address();
+    let params = protocol_params(matches)?;
+    let ix = if ctx.rpc.account_data(&protocol_config)?.is_none() {
+        let (program_data, _) = Pubkey::find_program_address(
+            &[smart_wallet::ID.as_ref()],
+            &solana_sdk::bpf_loader_upgradeable::ID,
+        );
+        instruction(
+            smart_wallet::accounts::InitProtocolConfig {
+                protocol_config,
+                program_data,
+                authority: ctx.payer.pubkey(),
+                system_program: system_program::ID,
+            },
+            smart_wallet::instruction::InitProtocolConfig { bump, params },
+            vec![],
+        )
+    } else {
+        instruction(
+            smart_wallet::accounts::SetProtocolConfig {
+                protocol_config,
+                authority: ctx.payer.pubkey(),
+            },
+            smart_wallet::instruction::SetProtocolConfig { params },
+            vec![],
+        )
+    };
+    ctx.send(ix, &[])?;
+    println!("protocol config: {}", protocol_config);
+    Ok(())
+}
+
+fn set_security_council(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let ix = instruction(
+        smart_wallet::accounts::SetProtocolConfig {
+            protocol_config: ProtocolConfig::address().0,
+            authority: ctx.payer.pubkey(),
+        },
+        smart_wallet::instruction::SetSecurityCouncil {
+            security_council: pubkey_arg(matches, "council")?,
+        },
+        vec![],
+    );
+    ctx.send(ix, &[])
+}
+
+fn set_paused(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let ix = instruction(
+        smart_wallet::accounts::SetPaused {
+            protocol_config: ProtocolConfig::address().0,
+            security_council: ctx.payer.pubkey(),
+        },
+        smart_wallet::instruction::SetPaused {
+            paused: parse_arg(matches, "paused")?,
+        },
+        vec![],
+    );
+    ctx.send(ix, &[])
+}
+
+fn set_audited_programs_only(matches: &ArgMatches) -> Result<()> {
+    let ix = wallet_instruction(
+        smart_wallet::accounts::SetAuditedProgramsOnly {
+            smart_wallet: pubkey_arg(matches, "wallet")?,
+        },
+        smart_wallet::instruction::SetAuditedProgramsOnly {
+            enabled: parse_arg(matches, "enabled")?,
+        },
+    );
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn set_grace_period(matches: &ArgMatches) -> Result<()> {
+    let grace_period = match matches.value_of("grace-period").unwrap() {
+        "never" => smart_wallet::NO_GRACE_PERIOD,
+        _ => parse_arg(matches, "grace-period")?,
+    };
+    let ix = wallet_instruction(
+        smart_wallet::accounts::SetGracePeriod {
+            smart_wallet: pubkey_arg(matches, "wallet")?,
+        },
+        smart_wallet::instruction::SetGracePeriod { grace_period },
+    );
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn resize_wallet(ctx: &Context, matches: &ArgMatches) -> Result<()> {
+    let mut remaining_accounts = ctx.owner_signers();
+    remaining_accounts.push(protocol_config_meta());
+    let ix = instruction(
+        smart_wallet::accounts::ResizeSmartWallet {
+            smart_wallet: pubkey_arg(matches, "wallet")?,
+            payer: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        smart_wallet::instruction::ResizeSmartWallet {
+            max_owners: parse_arg(matches, "max-owners")?,
+        },
+        remaining_accounts,
+    );
+    ctx.send(ix, &[])
+}
+
+fn update_audited_programs(ctx: &Context, matches: &ArgMatches, add: bool) -> Result<()> {
+    let program = pubkey_arg(matches, "program")?;
+    let accounts = smart_wallet::accounts::SetProtocolConfig {
+        protocol_config: ProtocolConfig::address().0,
+        authority: ctx.payer.pubkey(),
+    };
+    let ix = if add {
+        instruction(
+            accounts,
+            smart_wallet::instruction::AddAuditedProgram { program },
+            vec![],
+        )
+    } else {
+        instruction(
+            accounts,
+            smart_wallet::instruction::RemoveAuditedProgram { program },
+            vec![],
+        )
+    };
+    ctx.send(ix, &[])
+}
+
+/// The domain account, and the wallet PDA owning it.
+fn sns_domain(matches: &ArgMatches) -> Result<(Pubkey, Pubkey)> {
+    Ok((
+        sns::domain_key(matches.value_of("domain").unwrap()),
+        pubkey_arg(matches, "owner")?,
+    ))
+}
+
+fn sns_transfer(matches: &ArgMatches) -> Result<()> {
+    let (domain, owner) = sns_domain(matches)?;
+    let ix = sns::transfer(domain, owner, pubkey_arg(matches, "new-owner")?);
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn sns_update(matches: &ArgMatches) -> Result<()> {
+    let (domain, owner) = sns_domain(matches)?;
+    let record = base64::decode(matches.value_of("data").unwrap())?;
+    let ix = sns::update(domain, owner, parse_arg(matches, "offset")?, &record);
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn loader_v4_deploy(matches: &ArgMatches) -> Result<()> {
+    let ix = loader_v4::deploy(
+        pubkey_arg(matches, "program")?,
+        pubkey_arg(matches, "authority")?,
+    );
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn loader_v4_retract(matches: &ArgMatches) -> Result<()> {
+    let ix = loader_v4::retract(
+        pubkey_arg(matches, "program")?,
+        pubkey_arg(matches, "authority")?,
+    );
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn loader_v4_transfer_authority(matches: &ArgMatches) -> Result<()> {
+    let ix = loader_v4::transfer_authority(
+        pubkey_arg(matches, "program")?,
+        pubkey_arg(matches, "authority")?,
+        pubkey_arg(matches, "new-authority")?,
+    );
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn vote_authorize(matches: &ArgMatches) -> Result<()> {
+    let kind = match matches.value_of("kind").unwrap() {
+        "voter" => vote::VoteAuthorize::Voter,
+        _ => vote::VoteAuthorize::Withdrawer,
+    };
+    let ix = vote::authorize(
+        pubkey_arg(matches, "vote-account")?,
+        pubkey_arg(matches, "authority")?,
+        pubkey_arg(matches, "new-authority")?,
+        kind,
+    );
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn vote_withdraw(matches: &ArgMatches) -> Result<()> {
+    let ix = vote::withdraw(
+        pubkey_arg(matches, "vote-account")?,
+        pubkey_arg(matches, "authority")?,
+        pubkey_arg(matches, "recipient")?,
+        parse_arg(matches, "lamports")?,
+    );
+    println!("{}", write_instructions(&[ix])?);
+    Ok(())
+}
+
+fn close_token_accounts(matches: &ArgMatches) -> Result<()> {
+    let accounts = matches
+        .values_of("account")
+        .unwrap()
+        .map(parse_pubkey)
+        .collect::<Result<Vec<_>>>()?;
+    let authority = pubkey_arg(matches, "authority")?;
+    let destination = match matches.value_of("destination") {
+        Some(destination) => parse_pubkey(destination)?,
+        None => authority,
+    };
+    let ixs = token::close_accounts(&accounts, destination, authority);
+    println!("{}", write_instructions(&ixs)?);
+    Ok(())
+}
+
+fn pubkey(name: &'static str) -> Arg<'static, 'static> {
+    Arg::with_name(name)
+        .long(name)
+        .value_name("PUBKEY")
+        .takes_value(true)
+        .required(true)
+}
+
+fn value(name: &'static str, value_name: &'static str) -> Arg<'static, 'static> {
+    Arg::with_name(name)
+        .long(name)
+        .value_name(value_name)
+        .takes_value(true)
+        .required(true)
+}
+
+fn app() -> App<'static, 'static> {
+    App::new("goki-cli")
+        .about("Manage Goki smart wallets and stakes")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("url")
+                .long("url")
+                .global(true)
+                .takes_value(true)
+                .default_value(DEFAULT_URL)
+                .help("RPC endpoint"),
+        )
+        .arg(
+            Arg::with_name("keypair")
+                .long("keypair")
+                .global(true)
+                .takes_value(true)
+                .help("Keypair of the payer and first owner [default: ~/.config/solana/id.json]"),
+        )
+        .arg(
+            Arg::with_name("cosigner")
+                .long("cosigner")
+                .global(true)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Keypair of an additional owner signing the instruction"),
+        )
+        .subcommand(
+            SubCommand::with_name("create-wallet")
+                .about("Create a smart wallet")
+                .arg(
+                    pubkey("owners")
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .help("Comma separated owners"),
+                )
+                .arg(value("threshold", "COUNT"))
+                .arg(value("minimum-delay", "SECONDS").default_value("0"))
+                .arg(value("max-owners", "COUNT").required(false)),
+        )
+        .subcommand(
+            SubCommand::with_name("import-wallet")
+                .about("Create a wallet from an upstream Goki wallet, signed by its owners")
+                .arg(pubkey("legacy-wallet"))
+                .arg(value("max-owners", "COUNT").required(false)),
+        )
+        .subcommand(
+            SubCommand::with_name("import-transaction")
+                .about("Copy a pending upstream Goki transaction into an imported wallet")
+                .arg(pubkey("wallet"))
+                .arg(pubkey("legacy-wallet"))
+                .arg(pubkey("legacy-transaction")),
+        )
+        .subcommand(
+            SubCommand::with_name("execute")
+                .about("Execute instructions from a JSON file as a derived wallet, signed by two owners")
+                .arg(pubkey("wallet"))
+                .arg(value("index", "INDEX"))
+                .arg(value("instructions", "FILE")),
+        )
+        .subcommand(
+            SubCommand::with_name("list-queue")
+                .about("List the transactions of a smart wallet that are not yet executed")
+                .arg(pubkey("wallet"))
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .help("Include executed transactions"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-transaction")
+                .about("Decode the instructions of a transaction for review")
+                .arg(pubkey("transaction")),
+        )
+        .subcommand(
+            SubCommand::with_name("create-stake")
+                .about("Create a stake from a JSON config")
+                .arg(pubkey("wallet"))
+                .arg(value("index", "INDEX"))
+                .arg(value("config", "FILE"))
+                .arg(pubkey("reward-mint"))
+                .arg(pubkey("reward-vault")),
+        )
+        .subcommand(
+            SubCommand::with_name("extend-stake")
+                .about("Extend the duration and reward pot of a stake")
+                .arg(pubkey("stake"))
+                .arg(value("duration", "SECONDS"))
+                .arg(value("reward-pot", "AMOUNT")),
+        )
+        .subcommand(
+            SubCommand::with_name("set-stake-metadata")
+                .about("Set the metadata uri of a stake")
+                .arg(pubkey("stake"))
+                .arg(value("uri", "URI")),
+        )
+        .subcommand(
+            SubCommand::with_name("remap-gid")
+                .about("Treat a gid of a stake as another gid")
+                .arg(pubkey("stake"))
+                .arg(value("from", "GID"))
+                .arg(value("to", "GID")),
+        )
+        .subcommand(
+            SubCommand::with_name("set-protocol-config")
+                .about("Create or update the protocol config, signed by its authority")
+                .arg(value("max-owners", "COUNT"))
+                .arg(value("max-instructions", "COUNT"))
+                .arg(value("grace-period", "SECONDS"))
+                .arg(value("fee-bps", "BPS"))
+                .arg(pubkey("fee-recipient")),
+        )
+        .subcommand(
+            SubCommand::with_name("set-security-council")
+                .about("Designate the multisig allowed to pause the protocol")
+                .arg(pubkey("council")),
+        )
+        .subcommand(
+            SubCommand::with_name("set-paused")
+                .about("Pause or unpause execution and claims, signed by the security council")
+                .arg(value("paused", "BOOL").possible_values(&["true", "false"])),
+        )
+        .subcommand(
+            SubCommand::with_name("add-audited-program")
+                .about("Add a program to the protocol's audited programs")
+                .arg(pubkey("program")),
+        )
+        .subcommand(
+            SubCommand::with_name("remove-audited-program")
+                .about("Remove a program from the protocol's audited programs")
+                .arg(pubkey("program")),
+        )
+        .subcommand(
+            SubCommand::with_name("set-audited-programs-only")
+                .about("Print an instruction restricting a wallet to invoking audited programs")
+                .arg(pubkey("wallet"))
+                .arg(value("enabled", "BOOL").possible_values(&["true", "false"])),
+        )
+        .subcommand(
+            SubCommand::with_name("set-grace-period")
+                .about("Print an instruction setting how long approvals stay executable, or never expiring them")
+                .arg(pubkey("wallet"))
+                .arg(value("grace-period", "SECONDS|never")),
+        )
+        .subcommand(
+            SubCommand::with_name("resize-wallet")
+                .about("Reallocate a wallet to hold more owners")
+                .arg(pubkey("wallet"))
+                .arg(value("max-owners", "COUNT")),
+        )
+        .subcommand(
+            SubCommand::with_name("sns-transfer")
+                .about("Print an instruction file transferring a .sol domain owned by a wallet")
+                .arg(value("domain", "NAME"))
+                .arg(pubkey("owner"))
+                .arg(pubkey("new-owner")),
+        )
+        .subcommand(
+            SubCommand::with_name("sns-update")
+                .about("Print an instruction file writing the data of a .sol domain owned by a wallet")
+                .arg(value("domain", "NAME"))
+                .arg(pubkey("owner"))
+                .arg(value("offset", "BYTES"))
+                .arg(value("data", "BASE64")),
+        )
+        .subcommand(
+            SubCommand::with_name("loader-v4-deploy")
+                .about("Print an instruction file deploying a loader-v4 program")
+                .arg(pubkey("program"))
+                .arg(pubkey("authority")),
+        )
+        .subcommand(
+            SubCommand::with_name("loader-v4-retract")
+                .about("Print an instruction file retracting a loader-v4 program")
+                .arg(pubkey("program"))
+                .arg(pubkey("authority")),
+        )
+        .subcommand(
+            SubCommand::with_name("loader-v4-transfer-authority")
+                .about("Print an instruction file transferring the authority of a loader-v4 program")
+                .arg(pubkey("program"))
+                .arg(pubkey("authority"))
+                .arg(pubkey("new-authority")),
+        )
+        .subcommand(
+            SubCommand::with_name("vote-authorize")
+                .about("Print an instruction file changing an authority of a vote account")
+                .arg(pubkey("vote-account"))
+                .arg(pubkey("authority"))
+                .arg(pubkey("new-authority"))
+                .arg(value("kind", "KIND").possible_values(&["voter", "withdrawer"])),
+        )
+        .subcommand(
+            SubCommand::with_name("vote-withdraw")
+                .about("Print an instruction file withdrawing rewards from a vote account")
+                .arg(pubkey("vote-account"))
+                .arg(pubkey("authority"))
+                .arg(pubkey("recipient"))
+                .arg(value("lamports", "LAMPORTS")),
+        )
+        .subcommand(
+            SubCommand::with_name("close-token-accounts")
+                .about("Print an instruction file closing empty token accounts and reclaiming their rent")
+                .arg(
+                    pubkey("account")
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .help("Comma separated token accounts"),
+                )
+                .arg(pubkey("authority").help("Close authority of the accounts"))
+                .arg(
+                    pubkey("destination")
+                        .required(false)
+                        .help("Receiver of the rent [default: the authority]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create-rent-pool")
+                .about("Create the pool paying the rent of a wallet's stakers")
+                .arg(pubkey("wallet")),
+        )
+}
+
+fn read_keypair(path: &str) -> Result<Keypair> {
+    read_keypair_file(path).map_err(|e| anyhow!("failed to read keypair {}: {}", path, e))
+}
+
+fn main() -> Result<()> {
+    let matches = app().get_matches();
+    let (name, sub_matches) = matches.subcommand();
+    let sub_matches = sub_matches.unwrap();
+
+    // Offline commands, which only print instructions.
+    match name {
+        "sns-transfer" => return sns_transfer(sub_matches),
+        "sns-update" => return sns_update(sub_matches),
+        "loader-v4-deploy" => return loader_v4_deploy(sub_matches),
+        "loader-v4-retract" => return loader_v4_retract(sub_matches),
+        "loader-v4-transfer-authority" => return loader_v4_transfer_authority(sub_matches),
+        "vote-authorize" => return vote_authorize(sub_matches),
+        "vote-withdraw" => return vote_withdraw(sub_matches),
+        "close-token-accounts" => return close_token_accounts(sub_matches),
+        "set-audited-programs-only" => return set_audited_programs_only(sub_matches),
+        "set-grace-period" => return set_grace_period(sub_matches),
+        _ => {}
+    }
+
+    let keypair = match sub_matches.value_of("keypair") {
+        Some(path) => path.to_string(),
+        None => format!(
+            "{}/.config/solana/id.json",
+            std::env::var("HOME").unwrap_or_default()
+        ),
+    };
+    let ctx = Context {
+        rpc: Rpc::new(sub_matches.value_of("url").unwrap()),
+        payer: read_keypair(&keypair)?,
+        cosigners: sub_matches
+            .values_of("cosigner")
+            .map(|paths| paths.map(read_keypair).collect::<Result<Vec<_>>>())
+            .transpose()?
+            .unwrap_or_default(),
+    };
+
+    match name {
+        "create-wallet" => create_wallet(&ctx, sub_matches),
+        "import-wallet" => import_wallet(&ctx, sub_matches),
+        "import-transaction" => import_transaction(&ctx, sub_matches),
+        "execute" => execute(&ctx, sub_matches),
+        "list-queue" => list_queue(&ctx, sub_matches),
+        "show-transaction" => show_transaction(&ctx, sub_matches),
+        "create-stake" => create_stake(&ctx, sub_matches),
+        "extend-stake" => extend_stake(&ctx, sub_matches),
+        "set-stake-metadata" => set_stake_metadata(&ctx, sub_matches),
+        "remap-gid" => remap_gid(&ctx, sub_matches),
+        "create-rent-pool" => create_rent_pool(&ctx, sub_matches),
+        "set-protocol-config" => set_protocol_config(&ctx, sub_matches),
+        "set-security-council" => set_security_council(&ctx, sub_matches),
+        "set-paused" => set_paused(&ctx, sub_matches),
+        "add-audited-program" => update_audited_programs(&ctx, sub_matches, true),
+        "remove-audited-program" => update_audited_programs(&ctx, sub_matches, false),
+        "resize-wallet" => resize_wallet(&ctx, sub_matches),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,136 @@
+//! Minimal Solana JSON-RPC client.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use solana_sdk::{
+    hash::Hash, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use std::{str::FromStr, thread, time::Duration};
+
+/// Number of times to poll for a confirmation before giving up.
+const CONFIRM_ATTEMPTS: usize = 60;
+
+pub struct Rpc {
+    url: String,
+}
+
+impl Rpc {
+    pub fn new(url: &str) -> Self {
+        Rpc {
+            url: url.to_string(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = ureq::post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            bail!("{} failed: {}", method, error);
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("{} returned no result", method))
+    }
+
+    pub fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("missing blockhash"))?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    /// Data of an account, or [None] if it does not exist.
+    pub fn account_data(&self, key: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([key.to_string(), { "encoding": "base64" }]),
+        )?;
+        if result["value"].is_null() {
+            return Ok(None);
+        }
+        decode_data(&result["value"]["data"]).map(Some)
+    }
+
+    /// Accounts of a program whose data matches `bytes` at `offset`.
+    pub fn program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[(usize, Vec<u8>)],
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+        let filters: Vec<Value> = filters
+            .iter()
+            .map(|(offset, bytes)| {
+                json!({ "memcmp": {
+                    "offset": offset,
+                    "bytes": solana_sdk::bs58::encode(bytes).into_string(),
+                }})
+            })
+            .collect();
+        let result = self.call(
+            "getProgramAccounts",
+            json!([program_id.to_string(), { "encoding": "base64", "filters": filters }]),
+        )?;
+        let accounts = result
+            .as_array()
+            .ok_or_else(|| anyhow!("expected an array of accounts"))?;
+        accounts
+            .iter()
+            .map(|account| {
+                let pubkey = account["pubkey"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("missing pubkey"))?;
+                Ok((
+                    Pubkey::from_str(pubkey)?,
+                    decode_data(&account["account"]["data"])?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Sends a signed transaction and waits for it to be confirmed.
+    pub fn send_and_confirm(&self, tx: &Transaction) -> Result<Signature> {
+        let encoded = base64::encode(bincode::serialize(tx)?);
+        let result = self.call(
+            "sendTransaction",
+            json!([encoded, { "encoding": "base64" }]),
+        )?;
+        let signature = Signature::from_str(
+            result
+                .as_str()
+                .ok_or_else(|| anyhow!("missing signature"))?,
+        )?;
+
+        for _ in 0..CONFIRM_ATTEMPTS {
+            let statuses = self.call(
+                "getSignatureStatuses",
+                json!([[signature.to_string()]]),
+            )?;
+            let status = &statuses["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    bail!("transaction {} failed: {}", signature, status["err"]);
+                }
+                if status["confirmationStatus"] != "processed" {
+                    return Ok(signature);
+                }
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+        bail!("transaction {} was not confirmed", signature)
+    }
+}
+
+fn decode_data(data: &Value) -> Result<Vec<u8>> {
+    let encoded = data[0]
+        .as_str()
+        .ok_or_else(|| anyhow!("missing account data"))?;
+    Ok(base64::decode(encoded)?)
+}
@@ -0,0 +1,71 @@
+//! Instructions managing Solana Name Service domains owned by a wallet PDA.
+//!
+//! The instructions are printed as an instruction file, so they can be executed
+//! or proposed like any other wallet instruction.
+
+use anchor_lang::solana_program::hash::hashv;
+use smart_wallet::{TXAccountMeta, TXInstruction};
+use solana_sdk::pubkey::Pubkey;
+
+/// The SPL Name Service program.
+pub mod name_service {
+    solana_sdk::declare_id!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+}
+
+/// Parent of all `.sol` domains.
+pub mod sol_root {
+    solana_sdk::declare_id!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+}
+
+/// Prefix hashed with a name to derive its name account.
+const HASH_PREFIX: &str = "SPL Name Service";
+
+/// Derives the name account of a `.sol` domain, e.g. `goki` or `goki.sol`.
+pub fn domain_key(domain: &str) -> Pubkey {
+    let name = domain.strip_suffix(".sol").unwrap_or(domain);
+    let hashed_name = hashv(&[HASH_PREFIX.as_bytes(), name.as_bytes()]).to_bytes();
+    Pubkey::find_program_address(
+        &[
+            &hashed_name,
+            &Pubkey::default().to_bytes(),
+            &sol_root::ID.to_bytes(),
+        ],
+        &name_service::ID,
+    )
+    .0
+}
+
+/// Transfers the domain to `new_owner`, signed by its current owner.
+pub fn transfer(domain: Pubkey, owner: Pubkey, new_owner: Pubkey) -> TXInstruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&new_owner.to_bytes());
+    name_instruction(domain, owner, data)
+}
+
+/// Writes `record` into the domain's data at `offset`, signed by its owner.
+pub fn update(domain: Pubkey, owner: Pubkey, offset: u32, record: &[u8]) -> TXInstruction {
+    let mut data = vec![1];
+    data.extend_from_slice(&offset.to_le_bytes());
+    data.extend_from_slice(&(record.len() as u32).to_le_bytes());
+    data.extend_from_slice(record);
+    name_instruction(domain, owner, data)
+}
+
+fn name_instruction(domain: Pubkey, owner: Pubkey, data: Vec<u8>) -> TXInstruction {
+    TXInstruction {
+        program_id: name_service::ID,
+        keys: vec![
+            TXAccountMeta {
+                pubkey: domain,
+                is_signer: false,
+                is_writable: true,
+            },
+            TXAccountMeta {
+                pubkey: owner,
+                is_signer: true,
+                is_writable: false,
+            },
+        ],
+        data,
+    }
+}
@@ -0,0 +1,46 @@
+//! Instructions closing token accounts whose close authority is a wallet PDA,
+//! returning their rent to the wallet.
+//!
+//! Like [crate::sns], the instructions are printed as an instruction file.
+
+use smart_wallet::{TXAccountMeta, TXInstruction};
+use solana_sdk::pubkey::Pubkey;
+
+/// The SPL Token program.
+pub mod token_program {
+    solana_sdk::declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+}
+
+/// Closes an empty token account, sending its rent to `destination`. Signed by
+/// the account's close authority, or its owner if it has none.
+pub fn close_account(account: Pubkey, destination: Pubkey, authority: Pubkey) -> TXInstruction {
+    TXInstruction {
+        program_id: token_program::ID,
+        keys: vec![
+            meta(account, false, true),
+            meta(destination, false, true),
+            meta(authority, true, false),
+        ],
+        data: vec![9],
+    }
+}
+
+/// Closes each of `accounts`, all with the same close authority.
+pub fn close_accounts(
+    accounts: &[Pubkey],
+    destination: Pubkey,
+    authority: Pubkey,
+) -> Vec<TXInstruction> {
+    accounts
+        .iter()
+        .map(|account| close_account(*account, destination, authority))
+        .collect()
+}
+
+fn meta(pubkey: Pubkey, is_signer: bool, is_writable: bool) -> TXAccountMeta {
+    TXAccountMeta {
+        pubkey,
+        is_signer,
+        is_writable,
+    }
+}
@@ -0,0 +1,67 @@
+//! Instructions managing vote accounts whose voter or withdraw authority is a wallet PDA.
+//!
+//! Like [crate::sns], the instructions are printed as an instruction file.
+
+use smart_wallet::{TXAccountMeta, TXInstruction};
+use solana_sdk::{pubkey::Pubkey, sysvar};
+
+/// The vote program.
+pub mod vote_program {
+    solana_sdk::declare_id!("Vote111111111111111111111111111111111111111");
+}
+
+/// Which authority of a vote account to change.
+#[derive(Clone, Copy)]
+pub enum VoteAuthorize {
+    Voter = 0,
+    Withdrawer = 1,
+}
+
+/// Sets the voter or withdraw authority of a vote account, signed by the current one.
+pub fn authorize(
+    vote_account: Pubkey,
+    authority: Pubkey,
+    new_authority: Pubkey,
+    kind: VoteAuthorize,
+) -> TXInstruction {
+    let mut data = 1u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&new_authority.to_bytes());
+    data.extend_from_slice(&(kind as u32).to_le_bytes());
+    TXInstruction {
+        program_id: vote_program::ID,
+        keys: vec![
+            meta(vote_account, false, true),
+            meta(sysvar::clock::ID, false, false),
+            meta(authority, true, false),
+        ],
+        data,
+    }
+}
+
+/// Withdraws `lamports` from a vote account, signed by its withdraw authority.
+pub fn withdraw(
+    vote_account: Pubkey,
+    authority: Pubkey,
+    recipient: Pubkey,
+    lamports: u64,
+) -> TXInstruction {
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    TXInstruction {
+        program_id: vote_program::ID,
+        keys: vec![
+            meta(vote_account, false, true),
+            meta(recipient, false, true),
+            meta(authority, true, false),
+        ],
+        data,
+    }
+}
+
+fn meta(pubkey: Pubkey, is_signer: bool, is_writable: bool) -> TXAccountMeta {
+    TXAccountMeta {
+        pubkey,
+        is_signer,
+        is_writable,
+    }
+}
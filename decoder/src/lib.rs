@@ -0,0 +1,382 @@
+//! Decodes Goki smart wallet [Transaction]s into human-readable form, for review
+//! tooling and signing ceremonies.
+//!
+//! Instructions of the System, SPL Token, BPF Upgradeable Loader, Loader v4,
+//! SPL Name Service, Vote and smart wallet programs are decoded. Others are rendered as hex data with account roles.
+
+use anchor_lang::solana_program::{
+    bpf_loader_upgradeable, hash, loader_upgradeable_instruction::UpgradeableLoaderInstruction,
+    pubkey::Pubkey, system_instruction::SystemInstruction, system_program,
+};
+use anchor_lang::AccountDeserialize;
+use smart_wallet::{TXInstruction, Transaction};
+use std::fmt;
+
+/// Instructions of the smart wallet program, named to match their Anchor sighash.
+const SMART_WALLET_INSTRUCTIONS: &[&str] = &[
+    "init_protocol_config",
+    "set_protocol_config",
+    "set_protocol_authority",
+    "set_security_council",
+    "set_paused",
+    "govern_protocol_config",
+    "add_audited_program",
+    "remove_audited_program",
+    "create_smart_wallet",
+    "import_smart_wallet",
+    "import_transaction",
+    "set_executor_reimbursement",
+    "set_executor_allowlist",
+    "set_audited_programs_only",
+    "set_grace_period",
+    "set_proposer_gate",
+    "set_proposal_bond",
+    "set_proposal_rate_limit",
+    "set_execution_receipts",
+    "set_veto_authority",
+    "init_proposer_activity",
+    "resize_smart_wallet",
+    "suspend_owner",
+    "reinstate_owner",
+    "register_owner_notification",
+    "update_owner_notification",
+    "remove_owner_notification",
+    "init_owner_heartbeat",
+    "owner_heartbeat",
+    "add_allowed_proposer",
+    "remove_allowed_proposer",
+    "create_transaction",
+    "create_draft_transaction",
+    "create_proposer_transaction",
+    "create_committed_transaction",
+    "reserve_transaction_index",
+    "create_reserved_transaction",
+    "execute_transaction",
+    "check_transaction",
+    "attest_configuration",
+    "approve",
+    "unapprove",
+    "approve_with_memo",
+    "approve_many",
+    "approve_with_message",
+    "approve_with_signature",
+    "approve_aggregate",
+    "approve_instructions",
+    "append_transaction",
+    "finalize_transaction",
+    "declare_writable_scope",
+    "declare_dependencies",
+    "set_proposer_executes",
+    "cancel_transaction",
+    "veto_transaction",
+    "mark_expired",
+    "reap_transaction",
+    "release_proposal_bond",
+    "close_transaction",
+    "create_stake",
+    "extend_stake",
+    "set_stake_metadata",
+    "set_reward_burn",
+    "set_reward_emission",
+    "set_claim_window",
+    "set_reward_rounding",
+    "set_owner_ata_claims",
+    "set_stake_viewers",
+    "seal_stake_params",
+    "reveal_stake_params",
+    "add_stake_sponsor",
+    "remove_stake_sponsor",
+    "fund_stake",
+    "set_protected_gids",
+    "remap_gid",
+    "create_bonus_budget",
+    "fund_bonus_budget",
+    "create_quest",
+    "claim_quest",
+    "create_milestone",
+    "claim_milestone",
+    "rollup_entity",
+    "migrate_rollup",
+    "init_owner_rewards",
+    "authorize_crank",
+    "revoke_crank",
+    "crank_claim",
+    "add_allowed_mint",
+    "remove_allowed_mint",
+    "set_participant_allowlist",
+    "add_allowed_participant",
+    "prove_participant",
+    "remove_allowed_participant",
+    "add_fee_exemption",
+    "remove_fee_exemption",
+    "set_yield_venue",
+    "deposit_reward_vault",
+    "recall_reward_vault",
+    "create_rent_pool",
+    "sponsor_rent",
+    "close_ticket",
+    "close_rollup",
+    "register_entity",
+    "claim_entities",
+    "claim_entities_to",
+    "distribute_rewards",
+    "update_entity_by_owner",
+    "activate_entity",
+    "freeze_ticket",
+    "report_rollups",
+    "unfreeze_ticket",
+    "update_entity",
+    "update_entities",
+    "withdraw_entity_by_program",
+    "withdraw_entity",
+    "withdraw_all_entities",
+    "create_subaccount_info",
+    "create_invoker_whitelist",
+    "set_invoker_whitelist",
+    "owner_invoke_instruction",
+    "create_one_shot",
+    "execute_one_shot",
+    "revoke_one_shot",
+    "execute_ixs",
+];
+
+/// The SPL Name Service program, commonly holding `.sol` domains owned by wallets.
+pub mod name_service {
+    anchor_lang::declare_id!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+}
+
+/// The loader-v4 program, for programs whose authority is a wallet.
+pub mod loader_v4 {
+    anchor_lang::declare_id!("LoaderV411111111111111111111111111111111111");
+}
+
+/// The Vote program, for vote accounts whose authorities are wallets.
+pub mod vote_program {
+    anchor_lang::declare_id!("Vote111111111111111111111111111111111111111");
+}
+
+/// Deserializes a [Transaction] account.
+pub fn decode_transaction_account(data: &[u8]) -> Result<Transaction, DecodeError> {
+    let mut data = data;
+    Transaction::try_deserialize(&mut data).map_err(|_| DecodeError::InvalidTransaction)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The account is not a smart wallet [Transaction].
+    InvalidTransaction,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidTransaction => write!(f, "not a smart wallet transaction"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// How an instruction uses an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountRole {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl fmt::Display for AccountRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = match (self.is_signer, self.is_writable) {
+            (true, true) => "signer, writable",
+            (true, false) => "signer",
+            (false, true) => "writable",
+            (false, false) => "readonly",
+        };
+        write!(f, "{} ({})", self.pubkey, role)
+    }
+}
+
+/// A [TXInstruction], decoded as far as its program is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    pub program_id: Pubkey,
+    /// Name of the program, if known.
+    pub program: Option<&'static str>,
+    /// The decoded instruction, or [None] if it could not be decoded.
+    pub instruction: Option<String>,
+    pub accounts: Vec<AccountRole>,
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.program {
+            Some(program) => writeln!(f, "program: {} ({})", program, self.program_id)?,
+            None => writeln!(f, "program: {}", self.program_id)?,
+        }
+        match &self.instruction {
+            Some(instruction) => writeln!(f, "instruction: {}", instruction)?,
+            None => writeln!(f, "data: {}", to_hex(&self.data))?,
+        }
+        writeln!(f, "accounts:")?;
+        for (i, account) in self.accounts.iter().enumerate() {
+            writeln!(f, "  {}: {}", i, account)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes a single instruction.
+pub fn decode_instruction(ix: &TXInstruction) -> DecodedInstruction {
+    let (program, instruction) = if ix.program_id == system_program::ID {
+        (
+            Some("System"),
+            bincode::deserialize::<SystemInstruction>(&ix.data)
+                .ok()
+                .map(|ix| format!("{:?}", ix)),
+        )
+    } else if ix.program_id == spl_token::ID {
+        (
+            Some("SPL Token"),
+            spl_token::instruction::TokenInstruction::unpack(&ix.data)
+                .ok()
+                .map(|ix| format!("{:?}", ix)),
+        )
+    } else if ix.program_id == bpf_loader_upgradeable::ID {
+        (
+            Some("BPF Upgradeable Loader"),
+            bincode::deserialize::<UpgradeableLoaderInstruction>(&ix.data)
+                .ok()
+                .map(|ix| format!("{:?}", ix)),
+        )
+    } else if ix.program_id == loader_v4::ID {
+        (Some("Loader v4"), decode_loader_v4(&ix.data))
+    } else if ix.program_id == name_service::ID {
+        (Some("SPL Name Service"), decode_name_service(&ix.data))
+    } else if ix.program_id == vote_program::ID {
+        (Some("Vote"), decode_vote(&ix.data))
+    } else if ix.program_id == smart_wallet::ID {
+        (Some("Goki Smart Wallet"), decode_smart_wallet(&ix.data))
+    } else {
+        (None, None)
+    };
+    DecodedInstruction {
+        program_id: ix.program_id,
+        program,
+        instruction,
+        accounts: ix
+            .keys
+            .iter()
+            .map(|meta| AccountRole {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: ix.data.clone(),
+    }
+}
+
+/// Renders a [Transaction], the transactions it depends on and each of its instructions.
+pub fn render_transaction(tx: &Transaction) -> String {
+    let mut out = format!(
+        "transaction #{} of {}\nproposer: {}\neta: {}\n",
+        tx.index, tx.smart_wallet, tx.proposer, tx.eta
+    );
+    if !tx.dependencies.is_empty() {
+        let dependencies: Vec<String> = tx.dependencies.iter().map(|i| format!("#{}", i)).collect();
+        out.push_str(&format!("depends on: {}\n", dependencies.join(", ")));
+    }
+    for (i, ix) in tx.instructions.iter().enumerate() {
+        out.push_str(&format!("\n[{}] {}", i, decode_instruction(ix)));
+    }
+    out
+}
+
+fn decode_smart_wallet(data: &[u8]) -> Option<String> {
+    if data.len() < 8 {
+        return None;
+    }
+    let (sighash, args) = data.split_at(8);
+    SMART_WALLET_INSTRUCTIONS
+        .iter()
+        .find(|name| {
+            hash::hash(format!("global:{}", name).as_bytes()).to_bytes()[..8] == *sighash
+        })
+        .map(|name| format!("{} (args: {})", name, to_hex(args)))
+}
+
+fn decode_name_service(data: &[u8]) -> Option<String> {
+    let (tag, args) = data.split_first()?;
+    match tag {
+        0 => Some(format!("Create (args: {})", to_hex(args))),
+        1 if args.len() >= 8 => {
+            let offset = u32::from_le_bytes(args[..4].try_into().ok()?);
+            let len = u32::from_le_bytes(args[4..8].try_into().ok()?) as usize;
+            let record = args.get(8..8 + len)?;
+            Some(format!("Update {{ offset: {}, data: {} }}", offset, to_hex(record)))
+        }
+        2 if args.len() == 32 => Some(format!(
+            "Transfer {{ new_owner: {} }}",
+            Pubkey::new(args)
+        )),
+        3 => Some("Delete".to_string()),
+        _ => None,
+    }
+}
+
+fn decode_loader_v4(data: &[u8]) -> Option<String> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (tag, args) = data.split_at(4);
+    match (u32::from_le_bytes(tag.try_into().ok()?), args.len()) {
+        (0, _) if args.len() >= 4 => Some(format!(
+            "Write {{ offset: {} }}",
+            u32::from_le_bytes(args[..4].try_into().ok()?)
+        )),
+        (2, 4) => Some(format!(
+            "SetProgramLength {{ new_size: {} }}",
+            u32::from_le_bytes(args.try_into().ok()?)
+        )),
+        (3, 0) => Some("Deploy".to_string()),
+        (4, 0) => Some("Retract".to_string()),
+        (5, 0) => Some("TransferAuthority".to_string()),
+        (6, 0) => Some("Finalize".to_string()),
+        _ => None,
+    }
+}
+
+fn decode_vote(data: &[u8]) -> Option<String> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (tag, args) = data.split_at(4);
+    let authority = |kind: &[u8]| match u32::from_le_bytes(kind.try_into().ok()?) {
+        0 => Some("Voter"),
+        1 => Some("Withdrawer"),
+        _ => None,
+    };
+    match u32::from_le_bytes(tag.try_into().ok()?) {
+        1 if args.len() == 36 => Some(format!(
+            "Authorize {{ new_authority: {}, kind: {} }}",
+            Pubkey::new(&args[..32]),
+            authority(&args[32..])?
+        )),
+        3 if args.len() == 8 => Some(format!(
+            "Withdraw {{ lamports: {} }}",
+            u64::from_le_bytes(args.try_into().ok()?)
+        )),
+        5 if args.len() == 1 => Some(format!("UpdateCommission {{ commission: {} }}", args[0])),
+        7 if args.len() == 4 => Some(format!(
+            "AuthorizeChecked {{ kind: {} }}",
+            authority(args)?
+        )),
+        _ => None,
+    }
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
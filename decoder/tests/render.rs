@@ -0,0 +1,43 @@
+//! Tests for rendering [Transaction]s for review.
+
+#[path = "../../programs/smart-wallet/tests/common/mod.rs"]
+mod common;
+
+use common::{instruction, key};
+use smart_wallet::Transaction;
+
+fn transaction(dependencies: Vec<u64>) -> Transaction {
+    Transaction {
+        index: 7,
+        instructions: vec![instruction(vec![0xab])],
+        eta: -1,
+        dependencies,
+        ..common::transaction()
+    }
+}
+
+#[test]
+fn renders_dependencies() {
+    let rendered = goki_decoder::render_transaction(&transaction(vec![3, 5]));
+    assert_eq!(
+        rendered,
+        format!(
+            "transaction #7 of {}\nproposer: {}\neta: -1\ndepends on: #3, #5\n\n\
+             [0] program: {}\ndata: ab\naccounts:\n",
+            key(1),
+            key(2),
+            key(5)
+        )
+    );
+}
+
+#[test]
+fn omits_missing_dependencies() {
+    let rendered = goki_decoder::render_transaction(&transaction(vec![]));
+    assert!(!rendered.contains("depends on"));
+    assert!(rendered.starts_with(&format!(
+        "transaction #7 of {}\nproposer: {}\neta: -1\n\n[0]",
+        key(1),
+        key(2)
+    )));
+}
@@ -0,0 +1,247 @@
+//! Generates a deterministic localnet snapshot of smart wallet accounts.
+//!
+//! Writes a wallet with three owners, a [Transaction] in every [TransactionState],
+//! a [Stake] and [Ticket]s in each of their states as account files loadable by
+//! `solana-test-validator --account`, so clients and indexers can be tested against
+//! known account data. Every address and keypair is derived from a fixed name, so
+//! each run writes the same snapshot.
+
+use anchor_lang::AccountSerialize;
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use smart_wallet::{
+    ProtocolConfig, Rollup, SmartWallet, Stake, TXAccountMeta, TXInstruction, Ticket, Transaction,
+    TransactionState, FROZEN_TICKET, NO_ETA,
+};
+use solana_sdk::{
+    hash::hash,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{keypair_from_seed, write_keypair_file, Keypair, Signer},
+    system_instruction,
+};
+use std::{env, fs, path::PathBuf};
+
+/// Unix time the snapshot is taken at.
+const NOW: i64 = 1_640_000_000;
+
+/// [Ticket::pending_until] of the pending [Ticket], 2100-01-01.
+const PENDING_UNTIL: i64 = 4_102_444_800;
+
+/// Account files written so far, and the validator arguments loading them.
+struct Snapshot {
+    dir: PathBuf,
+    args: Vec<String>,
+}
+
+impl Snapshot {
+    /// Writes `account` at `address` as `<name>.json`, padded to `space` bytes and
+    /// funded for rent exemption.
+    fn write<T: AccountSerialize>(
+        &mut self,
+        name: &str,
+        address: Pubkey,
+        account: &T,
+        space: usize,
+    ) -> Result<()> {
+        let mut data = vec![];
+        account.try_serialize(&mut data)?;
+        data.resize(space.max(data.len()), 0);
+
+        let path = self.dir.join(format!("{}.json", name));
+        let file = json!({
+            "pubkey": address.to_string(),
+            "account": {
+                "lamports": Rent::default().minimum_balance(data.len()),
+                "data": [base64::encode(&data), "base64"],
+                "owner": smart_wallet::ID.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+            },
+        });
+        fs::write(&path, serde_json::to_string_pretty(&file)?)?;
+        self.args
+            .push(format!("--account {} {}", address, path.display()));
+        Ok(())
+    }
+
+    /// Writes `keypair` as `<name>.json`, so tests can sign as it.
+    fn write_keypair(&self, name: &str, keypair: &Keypair) -> Result<()> {
+        let path = self.dir.join(format!("{}.json", name));
+        write_keypair_file(keypair, &path)
+            .map_err(|e| anyhow!("failed to write keypair {}: {}", path.display(), e))?;
+        Ok(())
+    }
+}
+
+/// The keypair derived from `name`.
+fn keypair(name: &str) -> Keypair {
+    keypair_from_seed(hash(name.as_bytes()).as_ref()).unwrap()
+}
+
+fn main() -> Result<()> {
+    let dir = PathBuf::from(env::args().nth(1).unwrap_or_else(|| "localnet".to_string()));
+    fs::create_dir_all(&dir)?;
+    let mut snapshot = Snapshot { dir, args: vec![] };
+
+    let owners: Vec<Keypair> = (0..3).map(|i| keypair(&format!("owner-{}", i))).collect();
+    for (i, owner) in owners.iter().enumerate() {
+        snapshot.write_keypair(&format!("owner-{}", i), owner)?;
+    }
+    let holder = keypair("holder");
+    snapshot.write_keypair("holder", &holder)?;
+
+    let base = keypair("base").pubkey();
+    let (wallet, bump) = SmartWallet::address(base);
+    let grace_period = ProtocolConfig::default().default_grace_period;
+    let states = [
+        TransactionState::Draft,
+        TransactionState::Active,
+        TransactionState::Approved,
+        TransactionState::Executed,
+        TransactionState::Cancelled,
+        TransactionState::Expired,
+    ];
+    snapshot.write(
+        "smart_wallet",
+        wallet,
+        &SmartWallet {
+            base,
+            bump,
+            threshold: 2,
+            minimum_delay: 0,
+            grace_period,
+            owner_set_seqno: 0,
+            num_transactions: states.len() as u64,
+            owners: owners.iter().map(|owner| owner.pubkey()).collect(),
+            num_executed: 1,
+            last_executed_at: NOW,
+            last_approved_at: NOW,
+            ..SmartWallet::default()
+        },
+        SmartWallet::space(owners.len() as u8),
+    )?;
+
+    let transfer = system_instruction::transfer(&wallet, &owners[0].pubkey(), LAMPORTS_PER_SOL);
+    let instructions = vec![TXInstruction {
+        program_id: transfer.program_id,
+        keys: transfer
+            .accounts
+            .iter()
+            .map(|meta| TXAccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: transfer.data,
+    }];
+    for (index, state) in states.iter().enumerate() {
+        let index = index as u64;
+        let (address, bump) = Transaction::address(wallet, index);
+        let mut tx = Transaction {
+            smart_wallet: wallet,
+            index,
+            bump,
+            proposer: owners[0].pubkey(),
+            instructions: instructions.clone(),
+            signers: vec![true, true, false],
+            owner_set_seqno: 0,
+            eta: NO_ETA,
+            executor: Pubkey::default(),
+            executed_at: -1,
+            state: *state,
+            ..Transaction::default()
+        };
+        match state {
+            TransactionState::Draft => tx.signers = vec![false; owners.len()],
+            TransactionState::Active => tx.signers = vec![true, false, false],
+            TransactionState::Executed => {
+                tx.executor = owners[1].pubkey();
+                tx.executed_at = NOW;
+                tx.instructions_executed = 1;
+            }
+            TransactionState::Cancelled => tx.cancelled = true,
+            TransactionState::Expired => {
+                tx.eta = NOW - grace_period - 1;
+                tx.expired = true;
+            }
+            TransactionState::Approved => {}
+        }
+        snapshot.write(
+            &format!("transaction_{:?}", state).to_lowercase(),
+            address,
+            &tx,
+            Transaction::space(instructions.clone()),
+        )?;
+    }
+
+    let (stake, bump) = Stake::address(wallet, 0);
+    let tickets = [
+        ("accruing", 0),
+        ("pending", PENDING_UNTIL),
+        ("frozen", FROZEN_TICKET),
+    ];
+    snapshot.write(
+        "stake",
+        stake,
+        &Stake {
+            bump,
+            duration: 86_400,
+            genesis_epoch: NOW.to_le_bytes().to_vec(),
+            name: b"Fixture".to_vec(),
+            reward_pot: 1_000_000,
+            uuid: b"00000000-0000-0000-0000-000000000000".to_vec(),
+            total_mints: tickets.len() as u64,
+            smart_wallet: wallet,
+            index: 0,
+            reward_mint: keypair("reward-mint").pubkey(),
+            reward_vault: keypair("reward-vault").pubkey(),
+            distribution_interval: 3600,
+            ..Stake::default()
+        },
+        Stake::space(0, 0),
+    )?;
+
+    for (name, pending_until) in tickets.iter() {
+        let mint = keypair(&format!("mint-{}", name)).pubkey();
+        let (address, bump) = Ticket::address(wallet, mint);
+        snapshot.write(
+            &format!("ticket_{}", name),
+            address,
+            &Ticket {
+                enrollment_epoch: NOW.to_le_bytes().to_vec(),
+                bump,
+                gid: 0,
+                mint,
+                owner: holder.pubkey(),
+                pending_until: *pending_until,
+            },
+            Ticket::space(),
+        )?;
+    }
+
+    let (rollup, bump) = Rollup::address(stake, holder.pubkey(), 0);
+    snapshot.write(
+        "rollup",
+        rollup,
+        &Rollup {
+            bump,
+            timestamp: NOW.to_le_bytes().to_vec(),
+            gid: 0,
+            mints: 1,
+            owner: holder.pubkey(),
+            stake,
+            ..Rollup::default()
+        },
+        Rollup::space(),
+    )?;
+
+    println!(
+        "solana-test-validator --reset --bpf-program {} target/deploy/smart_wallet.so {}",
+        smart_wallet::ID,
+        snapshot.args.join(" ")
+    );
+    Ok(())
+}
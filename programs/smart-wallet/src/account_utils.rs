@@ -0,0 +1,52 @@
+use crate::*;
+
+/// Reallocates a program-owned account to `new_len` bytes, with `payer` topping
+/// up its rent exemption.
+pub fn realloc<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> ProgramResult {
+    let rent = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if rent > 0 {
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(&payer.key(), &account.key(), rent),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_len, true)
+}
+
+/// Creates a program-owned PDA of `space` bytes signed for by `seeds`, with `payer`
+/// funding its rent exemption. Lamports already held by the address are kept.
+pub fn create_pda<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    seeds: &[&[u8]],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> ProgramResult {
+    let rent = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if rent > 0 {
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(&payer.key(), &account.key(), rent),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    let accounts = &[account.clone(), system_program.clone()];
+    solana_program::program::invoke_signed(
+        &solana_program::system_instruction::allocate(&account.key(), space as u64),
+        accounts,
+        &[seeds],
+    )?;
+    solana_program::program::invoke_signed(
+        &solana_program::system_instruction::assign(&account.key(), &crate::ID),
+        accounts,
+        &[seeds],
+    )
+}
@@ -1,4 +1,7 @@
 //! Events emitted.
+//!
+//! Every event carries the `event_seq` of its [SmartWallet], which increases by one
+//! per event, so indexers can detect gaps, deduplicate and order events.
 
 use crate::*;
 
@@ -7,6 +10,7 @@ use crate::*;
 pub struct WalletCreateEvent {
     #[index]
     pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     pub owners: Vec<Pubkey>,
     pub threshold: u64,
     pub minimum_delay: i64,
@@ -18,49 +22,696 @@ pub struct WalletCreateEvent {
 pub struct CreateStakeEvent {
     #[index]
     pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     pub stake: Pubkey,
 }
 
+/// Emitted when a [Stake] is extended.
+#[event]
+pub struct ExtendStakeEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    /// New duration of the [Stake].
+    pub duration: i32,
+    /// New reward pot of the [Stake].
+    pub reward_pot: i64,
+    pub timestamp: i64,
+}
+
 /// Emitted when a [SmartWallet] is created.
 #[event]
-pub struct ClaimEntitiesEvent {
+pub struct ClaimEntitiesEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub duration: Vec<u8>,
+    pub last_epoch: Vec<u8>,
+    pub reset_epoch: Vec<u8>,
+    pub mints: u32,
+    /// Rewards accrued since the last epoch at the [Stake]'s current rate.
+    pub rewards: u64,
+    pub rollup: Pubkey,
+    pub stake: Pubkey,
+    pub owner: Pubkey,
+    /// Token account the rewards were paid to.
+    pub destination: Pubkey,
+}
+/// Emitted when the metadata uri of a [Stake] is set.
+#[event]
+pub struct StakeMetadataEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub uri: String,
+    pub timestamp: i64,
+}
+/// Emitted when the protected gids of a [Stake] are replaced.
+#[event]
+pub struct StakeSetProtectedGidsEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub protected_gids: Vec<u16>,
+    pub protected_gid_ranges: Vec<GidRange>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the reward burn of a [Stake] is set.
+#[event]
+pub struct StakeSetRewardBurnEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub burn_bps: u16,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [RewardEmission] of a [Stake] is set.
+#[event]
+pub struct StakeSetRewardEmissionEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub emission: Option<RewardEmissionParams>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [ClaimWindow] of a [Stake] is set.
+#[event]
+pub struct StakeSetClaimWindowEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub claim_window: Option<ClaimWindow>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [YieldVenue] of a [Stake] is set.
+#[event]
+pub struct StakeSetYieldVenueEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub venue: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when rewards are deposited into the [YieldVenue] of a [Stake].
+#[event]
+pub struct StakeYieldDepositEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub amount: u64,
+    pub deposited: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when rewards are recalled from the [YieldVenue] of a [Stake].
+#[event]
+pub struct StakeYieldRecallEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub amount: u64,
+    /// Part of `amount` in excess of the deposit.
+    pub yield_earned: u64,
+    pub deposited: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when [Stake::owner_ata_claims] is set.
+#[event]
+pub struct StakeSetOwnerAtaClaimsEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when a sponsor of a [Stake] is added or removed.
+#[event]
+pub struct StakeSponsorEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub sponsor: Pubkey,
+    /// True if added, false if removed.
+    pub added: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when rewards are added to a [Stake] with [crate::smart_wallet::fund_stake].
+#[event]
+pub struct StakeFundEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub amount: u64,
+    pub reward_pot: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [Stake::bonus_budget] is created.
+#[event]
+pub struct BonusBudgetCreateEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub vault: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when tokens are added to the [Stake::bonus_budget].
+#[event]
+pub struct BonusBudgetFundEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub amount: u64,
+    pub funded: u64,
+    pub spent: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [Stake::viewers] are set.
+#[event]
+pub struct StakeSetViewersEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub viewers: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when parameters of a [Stake] are sealed.
+#[event]
+pub struct StakeSealParamsEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub commitment: [u8; 32],
+    pub ciphertext: Vec<u8>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the sealed parameters of a [Stake] are revealed.
+#[event]
+pub struct StakeRevealParamsEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub commitment: [u8; 32],
+    pub params: Vec<u8>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [RewardRounding] of a [Stake] is set.
+#[event]
+pub struct StakeSetRewardRoundingEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub rounding: RewardRounding,
+    pub timestamp: i64,
+}
+
+/// Emitted when part of a claimed reward is burned.
+#[event]
+pub struct RewardBurnEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a legacy [Rollup] is migrated into a [Stake].
+#[event]
+pub struct MigrateRollupEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub legacy_rollup: Pubkey,
+    pub rollup: Pubkey,
+    pub mints: u32,
+}
+
+/// Emitted when rewards are distributed to a page of [Rollup]s.
+#[event]
+pub struct DistributeRewardsEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    /// Number of [Rollup]s credited.
+    pub rollups: u32,
+    /// Total rewards credited.
+    pub rewards: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a pending [Ticket] starts accruing.
+#[event]
+pub struct ActivateEntityEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub ticket: Pubkey,
+    pub rollup: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when governance freezes or unfreezes a [Ticket].
+#[event]
+pub struct TicketFreezeEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub ticket: Pubkey,
+    pub frozen: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when an owner authorizes or revokes a crank.
+#[event]
+pub struct CrankPermissionEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub owner: Pubkey,
+    pub crank: Pubkey,
+    pub destination: Pubkey,
+    pub interval: i64,
+    pub authorized: bool,
+}
+
+/// Emitted when a [Milestone] is created.
+#[event]
+pub struct MilestoneCreateEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub milestone: Pubkey,
+    pub min_claimed: u64,
+}
+
+/// Emitted when the badge of a [Milestone] is minted.
+#[event]
+pub struct MilestoneClaimEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub milestone: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a mint is added to or removed from the allowlist of a [Stake].
+#[event]
+pub struct AllowedMintEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub mint: Pubkey,
+    pub allowed: bool,
+}
+
+/// Emitted when the [ParticipantAllowlist] of a [Stake] is set.
+#[event]
+pub struct StakeSetParticipantAllowlistEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub allowlist: Option<ParticipantAllowlist>,
+    pub timestamp: i64,
+}
+
+/// Emitted when an [AllowedParticipant] is added to or removed from a [Stake].
+#[event]
+pub struct AllowedParticipantEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub owner: Pubkey,
+    pub allowed: bool,
+}
+
+/// Emitted when a [FeeExemption] is created or closed.
+#[event]
+pub struct FeeExemptionEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub account: Pubkey,
+    pub exempt: bool,
+}
+
+/// Emitted when a [RentPool] is created.
+#[event]
+pub struct RentPoolCreateEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub rent_pool: Pubkey,
+}
+
+/// Emitted when a [RentPool] pays the rent of a [Ticket] or [Rollup].
+#[event]
+pub struct RentSponsorEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub owner: Pubkey,
+    /// The [Ticket] or [Rollup] address funded.
+    pub account: Pubkey,
+    pub lamports: u64,
+}
+
+/// Emitted when a [Ticket] or [Rollup] is closed and its rent returned.
+#[event]
+pub struct RentReclaimEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub account: Pubkey,
+    /// The [RentPool] if the wallet has one, otherwise the owner.
+    pub receiver: Pubkey,
+    pub lamports: u64,
+}
+
+/// Emitted when a gid of a [Stake] is remapped.
+#[event]
+pub struct RemapGidEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub from: u16,
+    pub to: u16,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [Quest] is created.
+#[event]
+pub struct QuestCreateEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub quest: Pubkey,
+    pub requirement: QuestRequirement,
+    pub bonus: u64,
+}
+
+/// Emitted when a [Quest] bonus is claimed.
+#[event]
+pub struct QuestClaimEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub quest: Pubkey,
+    pub rollup: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimEntityEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub duration: Vec<u8>,
+    pub mint: Pubkey,
+    pub ticket: Pubkey,
+    pub stake: Pubkey,
+    pub owner: Pubkey,
+}
+/// Emitted when a [SmartWallet] is created.
+#[event]
+pub struct WithdrawEntityEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub mint: Pubkey,
+    pub ticket: Pubkey,
+    pub stake: Pubkey,
+    pub owner: Pubkey,
+}
+
+/// Emitted once when an owner withdraws all their tickets with
+/// [crate::smart_wallet::withdraw_all_entities].
+#[event]
+pub struct WithdrawAllEntitiesEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub owner: Pubkey,
+    /// [Ticket]s that were withdrawn.
+    pub tickets: Vec<Pubkey>,
+    /// [Rollup]s of the tickets.
+    pub rollups: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// State of a [Rollup] in a [RollupReportEvent].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RollupSummary {
+    pub owner: Pubkey,
+    pub gid: u16,
+    pub mints: u32,
+    /// Rewards credited but not yet claimed.
+    pub accrued: u64,
+    /// Epoch rewards were last credited at.
+    pub last_epoch: i64,
+}
+
+/// Emitted by [crate::smart_wallet::report_rollups] for a page of [Rollup]s.
+#[event]
+pub struct RollupReportEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub stake: Pubkey,
+    pub rollups: Vec<RollupSummary>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the epochs of many [Ticket]s are corrected by the [SmartWallet].
+#[event]
+pub struct UpdateEntitiesEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    /// [Ticket]s that were updated.
+    pub tickets: Vec<Pubkey>,
+    /// Epoch the tickets and their rollups were set to.
+    pub timestamp: i64,
+    pub updated_at: i64,
+}
+
+/// Emitted when the owners of a [SmartWallet] are changed.
+#[event]
+pub struct WalletSetOwnersEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub owners: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the executor allowlist of a [SmartWallet] is changed.
+#[event]
+pub struct WalletSetExecutorAllowlistEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub executors: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when audited-programs mode of a [SmartWallet] is changed.
+#[event]
+pub struct WalletSetAuditedProgramsOnlyEvent {
     #[index]
     pub smart_wallet: Pubkey,
-    pub duration: Vec<u8>,
-    pub last_epoch: Vec<u8>,
-    pub reset_epoch: Vec<u8>,
-    pub mints: u32,
-    pub rollup: Pubkey,
-    pub stake: Pubkey,
+    pub event_seq: u64,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when an [AllowedProposer] is added to or removed from a [SmartWallet].
+#[event]
+pub struct AllowedProposerEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub proposer: Pubkey,
+    pub allowed: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when an owner registers, updates or removes their [OwnerNotification].
+#[event]
+pub struct OwnerNotificationEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     pub owner: Pubkey,
+    pub notification: Pubkey,
+    pub notification_key: Pubkey,
+    pub webhook_hash: [u8; 32],
+    /// False if the [OwnerNotification] was removed.
+    pub registered: bool,
+    pub timestamp: i64,
 }
+
+/// Emitted when an owner sends a heartbeat.
 #[event]
-pub struct ClaimEntityEvent {
+pub struct OwnerHeartbeatEvent {
     #[index]
     pub smart_wallet: Pubkey,
-    pub duration: Vec<u8>,
-    pub mint: Pubkey,
-    pub ticket: Pubkey,
-    pub stake: Pubkey,
+    pub event_seq: u64,
     pub owner: Pubkey,
+    pub num_heartbeats: u64,
+    pub timestamp: i64,
 }
-/// Emitted when a [SmartWallet] is created.
+
+/// Emitted when the configuration of a [SmartWallet] is attested.
 #[event]
-pub struct WithdrawEntityEvent {
+pub struct WalletAttestEvent {
     #[index]
     pub smart_wallet: Pubkey,
-    pub mint: Pubkey,
-    pub ticket: Pubkey,
-    pub stake: Pubkey,
+    pub event_seq: u64,
+    /// [SmartWallet::config_digest].
+    pub digest: [u8; 32],
+    pub owner_set_seqno: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [SmartWallet::grace_period] is set.
+#[event]
+pub struct WalletSetGracePeriodEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub grace_period: i64,
+    /// True if approved transactions never become stale.
+    pub never_stale: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [ProposerGate] of a [SmartWallet] is set.
+#[event]
+pub struct WalletSetProposerGateEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub gate: Option<ProposerGate>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [SmartWallet::proposal_bond] is set.
+#[event]
+pub struct WalletSetProposalBondEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [ProposalRateLimit] of a [SmartWallet] is set.
+#[event]
+pub struct WalletSetProposalRateLimitEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub limit: Option<ProposalRateLimit>,
+    pub timestamp: i64,
+}
+
+/// Emitted when [SmartWallet::execution_receipts] is set.
+#[event]
+pub struct WalletSetExecutionReceiptsEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [SmartWallet::veto_authority] is set.
+#[event]
+pub struct WalletSetVetoAuthorityEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub authority: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [SmartWallet] is reallocated for more owners.
+#[event]
+pub struct WalletResizeEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub max_owners: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted when an owner of a [SmartWallet] is suspended.
+#[event]
+pub struct OwnerSuspendEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     pub owner: Pubkey,
+    pub suspended_by: Pubkey,
+    pub timestamp: i64,
 }
 
-/// Emitted when the owners of a [SmartWallet] are changed.
+/// Emitted when a suspended owner of a [SmartWallet] is reinstated.
 #[event]
-pub struct WalletSetOwnersEvent {
+pub struct OwnerReinstateEvent {
     #[index]
     pub smart_wallet: Pubkey,
-    pub owners: Vec<Pubkey>,
+    pub event_seq: u64,
+    pub owner: Pubkey,
     pub timestamp: i64,
 }
 
@@ -69,21 +720,192 @@ pub struct WalletSetOwnersEvent {
 pub struct WalletChangeThresholdEvent {
     #[index]
     pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     pub threshold: u64,
     pub timestamp: i64,
 }
 
+/// Emitted when the [ExecutorReimbursement] of a [SmartWallet] is changed.
+#[event]
+pub struct WalletSetExecutorReimbursementEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub reimbursement: Option<ExecutorReimbursement>,
+    pub timestamp: i64,
+}
+
+/// Emitted when an executor is reimbursed for executing instructions.
+#[event]
+pub struct ExecutorReimbursedEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub executor: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [SubaccountInfo] is created.
+#[event]
+pub struct SubaccountCreateEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub subaccount: Pubkey,
+    pub subaccount_type: SubaccountType,
+    pub index: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when an owner invokes an instruction through an owner invoker.
+#[event]
+pub struct OwnerInvokeEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    /// Index of the owner invoker.
+    pub index: u64,
+    /// The owner that invoked.
+    pub invoker: Pubkey,
+    /// Program of the invoked instruction.
+    pub program_id: Pubkey,
+    pub timestamp: i64,
+}
+
 /// Emitted when a [Transaction] is proposed.
 #[event]
 pub struct TransactionCreateEvent {
     #[index]
     pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     #[index]
     pub transaction: Pubkey,
     pub proposer: Pubkey,
     /// Instructions associated with the [Transaction].
     pub instructions: Vec<TXInstruction>,
+    /// Hash of the instructions if only it was stored.
+    pub instructions_hash: Option<[u8; 32]>,
     pub eta: i64,
+    /// [OwnerNotification]s passed by the proposer, of owners to alert for approval.
+    pub notifications: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [Transaction] index is reserved.
+#[event]
+pub struct TransactionReserveEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub reservation: Pubkey,
+    pub proposer: Pubkey,
+    pub index: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [Transaction] past its grace period is marked as expired.
+#[event]
+pub struct TransactionExpiredEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub expires_at: i64,
+    /// Bond slashed to the treasury.
+    pub bond_slashed: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the bond of an executed [Transaction] is refunded to its proposer.
+#[event]
+pub struct ProposalBondReleaseEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub proposer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [Transaction] is closed and its rent reclaimed.
+#[event]
+pub struct TransactionCloseEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub receiver: Pubkey,
+    /// Bond refunded to the proposer on close.
+    pub bond_refunded: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when governance creates or revokes a [OneShot].
+#[event]
+pub struct OneShotEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub one_shot: Pubkey,
+    pub key: Pubkey,
+    /// False if the [OneShot] was revoked.
+    pub created: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when the instruction of a [OneShot] is executed.
+#[event]
+pub struct OneShotExecuteEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub one_shot: Pubkey,
+    pub key: Pubkey,
+    pub program_id: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted after [TransactionCreateEvent], so execution tooling can size a [Transaction]
+/// without fetching the account. The estimate of a committed [Transaction] is empty,
+/// as its instructions are only revealed on execution.
+#[event]
+pub struct TransactionEstimateEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub estimate: TransactionEstimate,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [Transaction] is cancelled.
+#[event]
+pub struct TransactionCancelEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    /// The proposer or the [SmartWallet].
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [Transaction] is vetoed by the [SmartWallet::veto_authority].
+#[event]
+pub struct TransactionVetoEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub veto_authority: Pubkey,
     pub timestamp: i64,
 }
 
@@ -92,9 +914,25 @@ pub struct TransactionCreateEvent {
 pub struct TransactionApproveEvent {
     #[index]
     pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub owner: Pubkey,
+    /// Rationale given with [crate::smart_wallet::approve_with_memo].
+    pub memo: Option<Vec<u8>>,
+    pub timestamp: i64,
+}
+
+/// Emitted when an owner approves a subset of a [Transaction]'s instructions.
+#[event]
+pub struct TransactionApproveInstructionsEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     #[index]
     pub transaction: Pubkey,
     pub owner: Pubkey,
+    pub approved: Vec<bool>,
     pub timestamp: i64,
 }
 
@@ -103,6 +941,7 @@ pub struct TransactionApproveEvent {
 pub struct TransactionUnapproveEvent {
     #[index]
     pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     #[index]
     pub transaction: Pubkey,
     pub owner: Pubkey,
@@ -114,8 +953,111 @@ pub struct TransactionUnapproveEvent {
 pub struct TransactionExecuteEvent {
     #[index]
     pub smart_wallet: Pubkey,
+    pub event_seq: u64,
     #[index]
     pub transaction: Pubkey,
     pub executor: Pubkey,
+    /// Whether execution was reserved for the proposer.
+    pub proposer_executes: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when a proposer reserves execution of a [Transaction] for themselves,
+/// or lifts the reservation.
+#[event]
+pub struct TransactionSetProposerExecutesEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub transaction: Pubkey,
+    pub proposer_executes: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when the [InvokerWhitelist] of an owner invoker is created or replaced.
+#[event]
+pub struct WalletSetInvokerWhitelistEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub index: u64,
+    pub permissions: Vec<InvokerPermission>,
+    pub timestamp: i64,
+}
+
+/// Emitted when a [SmartWallet] is imported from the upstream Goki program.
+#[event]
+pub struct WalletImportEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    pub legacy_smart_wallet: Pubkey,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u64,
+    pub minimum_delay: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a pending [Transaction] is imported from the upstream Goki program.
+#[event]
+pub struct TransactionImportEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub legacy_transaction: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the proposer finalizes a draft [Transaction].
+#[event]
+pub struct TransactionFinalizeEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub eta: i64,
+    /// Size of the finalized instructions.
+    pub estimate: TransactionEstimate,
+    pub timestamp: i64,
+}
+
+/// Emitted when the proposer appends instructions to a [Transaction].
+#[event]
+pub struct TransactionAppendEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub instructions: Vec<TXInstruction>,
+    /// Number of instructions of the [Transaction] after appending.
+    pub num_instructions: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when the proposer declares the [Transaction::dependencies].
+#[event]
+pub struct TransactionDeclareDependenciesEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub dependencies: Vec<u64>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the proposer declares the [Transaction::writable_scope].
+#[event]
+pub struct TransactionDeclareScopeEvent {
+    #[index]
+    pub smart_wallet: Pubkey,
+    pub event_seq: u64,
+    #[index]
+    pub transaction: Pubkey,
+    pub writable_scope: Vec<Pubkey>,
     pub timestamp: i64,
 }
@@ -10,7 +10,7 @@
 //! 1. Owners - the set of addresses that sign transactions for the smart wallet.
 //! 2. Threshold - the number of signers required to execute a transaction.
 //! 3. Minimum Delay - the minimum amount of time that must pass before a [Transaction]
-//!                    can be executed. If 0, this is ignored.
+//!    can be executed. If 0, this is ignored.
 //!
 //! Once the [SmartWallet] account is created, one can create a [Transaction]
 //! account, specifying the parameters for a normal Solana instruction.
@@ -22,8 +22,10 @@
 #![allow(rustdoc::missing_doc_code_examples)]
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 use anchor_lang::solana_program;
+use anchor_lang::Discriminator;
 use anchor_lang::Key;
 use std::convert::Into;
 use std::vec::Vec;
@@ -32,13 +34,20 @@ use vipers::unwrap_int;
 use vipers::unwrap_or_err;
 use vipers::validate::Validate;
 
+mod account_utils;
 mod events;
+mod protocol_config_utils;
+mod signature_utils;
 mod smart_wallet_utils;
+mod stake_errors;
+mod stake_utils;
 mod state;
+mod token_metadata;
 mod transaction;
 mod validators;
 
 pub use events::*;
+pub use stake_errors::StakeErrorCode;
 pub use state::*;
 
 /// Number of seconds in a day.
@@ -53,6 +62,134 @@ pub const DEFAULT_GRACE_PERIOD: i64 = 14 * SECONDS_PER_DAY;
 /// Constant declaring that there is no ETA of the transaction.
 pub const NO_ETA: i64 = -1;
 
+/// Grace period declaring that transactions of a wallet never become stale.
+pub const NO_GRACE_PERIOD: i64 = -1;
+
+/// Maximum length of an approval memo, in bytes.
+pub const MAX_APPROVAL_MEMO_LEN: usize = 128;
+
+/// Maximum length of the domain in a signed approval message.
+pub const MAX_APPROVAL_DOMAIN_LEN: usize = 64;
+
+/// Maximum number of keys of a stored [TXInstruction]. Every key is passed to the
+/// execution, and a Solana transaction locks at most 64 accounts.
+pub const MAX_INSTRUCTION_KEYS: usize = 64;
+
+/// Maximum length of the data of a stored [TXInstruction], the limit of a
+/// cross-program invocation.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 10 * 1024;
+
+/// Maximum number of [Transaction::dependencies] of a [Transaction].
+pub const MAX_TRANSACTION_DEPENDENCIES: usize = 8;
+
+/// Maximum number of accounts on a [SmartWallet]'s executor allowlist.
+pub const MAX_EXECUTORS: usize = 8;
+
+/// [Ticket::pending_until] of a [Ticket] frozen by governance.
+pub const FROZEN_TICKET: i64 = i64::MAX;
+
+/// Fractional bits of the fixed-point [Stake::reward_rate].
+pub const REWARD_RATE_FRACTIONAL_BITS: u32 = 64;
+
+/// Maximum number of gid remappings on a [Stake].
+pub const MAX_GID_REMAPS: usize = 16;
+
+/// Maximum length of a [Stake]'s metadata uri.
+pub const MAX_STAKE_URI_LENGTH: usize = 200;
+
+/// Maximum lamports reimbursed to an executor per execution.
+pub const MAX_EXECUTOR_REIMBURSEMENT: u64 = solana_program::native_token::LAMPORTS_PER_SOL / 10;
+
+/// Maximum number of [Stake::sponsors].
+pub const MAX_STAKE_SPONSORS: usize = 4;
+
+/// Maximum length of the ciphertext of [Stake::sealed_params], in bytes.
+pub const MAX_SEALED_PARAMS_LEN: usize = 256;
+
+/// Maximum number of [Stake::viewers].
+pub const MAX_STAKE_VIEWERS: usize = 8;
+
+/// Maximum number of programs in [ProtocolConfig::audited_programs].
+pub const MAX_AUDITED_PROGRAMS: usize = 32;
+
+/// Maximum [ProtocolConfig::protocol_fee_bps], 10%.
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+
+/// Seed prefix of [SmartWallet] addresses.
+pub const SMART_WALLET_SEED: &[u8] = b"GokiSmartWallet";
+
+/// Seed prefix of the addresses of [SubaccountType::Derived] wallets.
+pub const DERIVED_WALLET_SEED: &[u8] = b"GokiSmartWalletDerived";
+
+/// Seed prefix of the addresses of [SubaccountType::OwnerInvoker] wallets.
+pub const OWNER_INVOKER_SEED: &[u8] = b"GokiSmartWalletOwnerInvoker";
+
+/// Seed prefix of [SubaccountInfo] addresses.
+pub const SUBACCOUNT_INFO_SEED: &[u8] = b"GokiSubaccountInfo";
+
+/// Seed prefix of [Transaction] addresses.
+pub const TRANSACTION_SEED: &[u8] = b"GokiTransaction";
+
+/// Seed prefix of [TransactionReservation] addresses.
+pub const TRANSACTION_RESERVATION_SEED: &[u8] = b"GokiTransactionReservation";
+
+/// Seed prefix of [ExecutionReceipt] addresses.
+pub const EXECUTION_RECEIPT_SEED: &[u8] = b"GokiExecutionReceipt";
+
+/// Seed prefix of [AllowedProposer] addresses.
+pub const ALLOWED_PROPOSER_SEED: &[u8] = b"GokiAllowedProposer";
+
+/// Seed prefix of [ProposerActivity] addresses.
+pub const PROPOSER_ACTIVITY_SEED: &[u8] = b"GokiProposerActivity";
+
+/// Seed prefix of [OwnerNotification] addresses.
+pub const OWNER_NOTIFICATION_SEED: &[u8] = b"GokiOwnerNotification";
+
+/// Seed prefix of [OwnerHeartbeat] addresses.
+pub const OWNER_HEARTBEAT_SEED: &[u8] = b"GokiOwnerHeartbeat";
+
+/// Seed prefix of [OneShot] addresses.
+pub const ONE_SHOT_SEED: &[u8] = b"GokiOneShot";
+
+/// Seed prefix of [InvokerWhitelist] addresses.
+pub const INVOKER_WHITELIST_SEED: &[u8] = b"GokiInvokerWhitelist";
+
+/// Seed of the [ProtocolConfig] address.
+pub const PROTOCOL_CONFIG_SEED: &[u8] = b"GokiProtocolConfig";
+
+/// Seed prefix of [RentPool] addresses.
+pub const RENT_POOL_SEED: &[u8] = b"RentPool";
+
+/// Seed prefix of [Stake] addresses.
+pub const STAKE_SEED: &[u8] = b"Stake";
+
+/// Seed prefix of [OwnerRewards] addresses.
+pub const OWNER_REWARDS_SEED: &[u8] = b"OwnerRewards";
+
+/// Seed prefix of [AllowedParticipant] addresses.
+pub const ALLOWED_PARTICIPANT_SEED: &[u8] = b"AllowedParticipant";
+
+/// Seed prefix of [FeeExemption] addresses.
+pub const FEE_EXEMPTION_SEED: &[u8] = b"FeeExemption";
+
+/// Seed prefix of [AllowedMint] addresses.
+pub const ALLOWED_MINT_SEED: &[u8] = b"AllowedMint";
+
+/// Seed prefix of [CrankPermission] addresses.
+pub const CRANK_PERMISSION_SEED: &[u8] = b"CrankPermission";
+
+/// Seed prefix of [Quest] addresses.
+pub const QUEST_SEED: &[u8] = b"Quest";
+
+/// Seed prefix of [QuestClaim] addresses.
+pub const QUEST_CLAIM_SEED: &[u8] = b"QuestClaim";
+
+/// Seed prefix of [Milestone] addresses.
+pub const MILESTONE_SEED: &[u8] = b"Milestone";
+
+/// Seed prefix of [MilestoneClaim] addresses.
+pub const MILESTONE_CLAIM_SEED: &[u8] = b"MilestoneClaim";
+
 declare_id!("9UgyDew11rjMzcrWa8BMNQVkPSuU2Gv33YocZhfMQVuR");
 
 #[program]
@@ -60,10 +197,91 @@ declare_id!("9UgyDew11rjMzcrWa8BMNQVkPSuU2Gv33YocZhfMQVuR");
 pub mod smart_wallet {
     use super::*;
 
+    /// Creates the [ProtocolConfig]. Must be signed by the program's upgrade authority,
+    /// who becomes [ProtocolConfig::authority].
+    pub fn init_protocol_config(
+        ctx: Context<InitProtocolConfig>,
+        bump: u8,
+        params: ProtocolParams,
+    ) -> ProgramResult {
+        let (program_data, _) = Pubkey::find_program_address(
+            &[crate::ID.as_ref()],
+            &solana_program::bpf_loader_upgradeable::ID,
+        );
+        require!(ctx.accounts.program_data.key() == program_data, NotUpgradeAuthority);
+        require!(
+            ctx.accounts.program_data.upgrade_authority_address == Some(ctx.accounts.authority.key()),
+            NotUpgradeAuthority
+        );
+
+        let config = &mut ctx.accounts.protocol_config;
+        config.bump = bump;
+        config.authority = ctx.accounts.authority.key();
+        config.set_params(params)
+    }
+
+    /// Sets the parameters of the [ProtocolConfig].
+    pub fn set_protocol_config(ctx: Context<SetProtocolConfig>, params: ProtocolParams) -> ProgramResult {
+        ctx.accounts.protocol_config.set_params(params)
+    }
+
+    /// Transfers the authority over the [ProtocolConfig].
+    pub fn set_protocol_authority(
+        ctx: Context<SetProtocolConfig>,
+        new_authority: Pubkey,
+    ) -> ProgramResult {
+        ctx.accounts.protocol_config.authority = new_authority;
+        Ok(())
+    }
+
+    /// Designates the [ProtocolConfig::security_council], which may pause the protocol.
+    pub fn set_security_council(
+        ctx: Context<SetProtocolConfig>,
+        security_council: Pubkey,
+    ) -> ProgramResult {
+        ctx.accounts.protocol_config.security_council = security_council;
+        Ok(())
+    }
+
+    /// Adds a program to [ProtocolConfig::audited_programs].
+    pub fn add_audited_program(ctx: Context<SetProtocolConfig>, program: Pubkey) -> ProgramResult {
+        ctx.accounts.protocol_config.add_audited_program(program)
+    }
+
+    /// Removes a program from [ProtocolConfig::audited_programs].
+    pub fn remove_audited_program(ctx: Context<SetProtocolConfig>, program: Pubkey) -> ProgramResult {
+        ctx.accounts.protocol_config.remove_audited_program(program);
+        Ok(())
+    }
+
+    /// Pauses or unpauses execution and reward claims across all wallets.
+    /// Only the [ProtocolConfig::security_council] may call this.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> ProgramResult {
+        ctx.accounts.protocol_config.paused = paused;
+        Ok(())
+    }
+
+    /// Applies `update` to the [ProtocolConfig] for the [SmartWallet] governing it,
+    /// signed by the wallet from one of its executed [Transaction]s. Only a
+    /// [SmartWallet] is accepted, so its owners approve every change under its
+    /// threshold and timelock. Runs while the protocol is paused, so a wallet holding
+    /// the pause can lift it.
+    pub fn govern_protocol_config(
+        ctx: Context<GovernProtocolConfig>,
+        update: ProtocolConfigUpdate,
+    ) -> ProgramResult {
+        ctx.accounts
+            .protocol_config
+            .apply_update(ctx.accounts.smart_wallet.key(), update)?;
+        Ok(())
+    }
+
     /// Initializes a new [SmartWallet] account with a set of owners and a threshold.
+    /// Passing the [SubaccountInfo] of derived wallet 0 also registers it as the
+    /// wallet's treasury.
     #[access_control(ctx.accounts.validate())]
-    pub fn create_smart_wallet(
-        ctx: Context<CreateSmartWallet>,
+    pub fn create_smart_wallet<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateSmartWallet<'info>>,
         bump: u8,
         max_owners: u8,
         owners: Vec<Pubkey>,
@@ -74,6 +292,8 @@ pub mod smart_wallet {
         require!(minimum_delay < MAX_DELAY_SECONDS, DelayTooHigh);
 
         invariant!((max_owners as usize) >= owners.len(), "max_owners");
+        let config = ProtocolConfig::load(ctx.remaining_accounts)?;
+        require!(max_owners <= config.max_owners, TooManyOwners);
 
         let smart_wallet = &mut ctx.accounts.smart_wallet;
         smart_wallet.base = ctx.accounts.base.key();
@@ -81,12 +301,56 @@ pub mod smart_wallet {
 
         smart_wallet.threshold = threshold;
         smart_wallet.minimum_delay = minimum_delay;
-        smart_wallet.grace_period = DEFAULT_GRACE_PERIOD;
+        smart_wallet.grace_period = config.default_grace_period;
 
         smart_wallet.owner_set_seqno = 0;
         smart_wallet.num_transactions = 0;
 
         smart_wallet.owners = owners.clone();
+        smart_wallet.executor_reimbursement = None;
+        smart_wallet.suspended_owners = vec![];
+        smart_wallet.executor_allowlist = vec![];
+        smart_wallet.event_seq = 0;
+        smart_wallet.audited_programs_only = false;
+        smart_wallet.num_executed = 0;
+        smart_wallet.last_executed_at = 0;
+        smart_wallet.last_approved_at = 0;
+        smart_wallet.proposer_gate = None;
+        smart_wallet.proposal_bond = 0;
+        smart_wallet.proposal_rate_limit = None;
+        smart_wallet.veto_authority = None;
+        smart_wallet.execution_receipts = false;
+        smart_wallet.expiry_cursor = 0;
+
+        // Registers the treasury, derived wallet 0, if its SubaccountInfo is passed.
+        let (treasury, _) =
+            SmartWallet::subaccount_address(smart_wallet.key(), SubaccountType::Derived, 0);
+        let (info_address, info_bump) = SubaccountInfo::address(treasury);
+        if let Some(info) = ctx.remaining_accounts.iter().find(|a| a.key() == info_address) {
+            account_utils::create_pda(
+                info,
+                SubaccountInfo::space(),
+                &[SUBACCOUNT_INFO_SEED, &treasury.to_bytes(), &[info_bump]],
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+            SubaccountInfo {
+                smart_wallet: smart_wallet.key(),
+                subaccount_type: SubaccountType::Derived,
+                index: 0,
+            }
+            .try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+            let event_seq = smart_wallet.next_event_seq()?;
+            emit!(SubaccountCreateEvent {
+                smart_wallet: smart_wallet.key(),
+                event_seq,
+                subaccount: treasury,
+                subaccount_type: SubaccountType::Derived,
+                index: 0,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
 
         /*
         emit!(WalletCreateEvent {
@@ -100,303 +364,5785 @@ pub mod smart_wallet {
         Ok(())
     }
 
-    /// Registers participant.
-    pub fn create_stake(
-        ctx: Context<CreateStake>,
+    /// Creates a [SmartWallet] from a wallet of the upstream Goki program, at the address
+    /// derived from the same base, copying its owners and parameters.
+    /// Requires the upstream wallet's threshold of owners to sign, passed as remaining accounts.
+    pub fn import_smart_wallet(
+        ctx: Context<ImportSmartWallet>,
         bump: u8,
-        abs_index: u64,
-        stake_data: StakeData,
+        max_owners: u8,
     ) -> ProgramResult {
-        let stake_account = &mut ctx.accounts.stake;
-        stake_account.bump = bump;
-        stake_account.genesis_epoch = stake_data.genesis_epoch;
-        stake_account.name = stake_data.name;
-        stake_account.reward_pot = stake_data.reward_pot;
-        stake_account.duration = stake_data.duration;
-        stake_account.protected_gids = stake_data.protected_gids;
-        stake_account.uuid = stake_data.uuid;
+        let legacy = LegacySmartWallet::load(&ctx.accounts.legacy_smart_wallet)?;
+        legacy.check_owner_signers(ctx.remaining_accounts)?;
+        invariant!((max_owners as usize) >= legacy.owners.len(), "max_owners");
+        let config = ProtocolConfig::load(ctx.remaining_accounts)?;
+        require!(max_owners <= config.max_owners, TooManyOwners);
 
-        // msg!("Stake genesis for {:?} with {:?} genesis_epoch", stake_account.key(), stake_account.genesis_epoch);
-        // msg!("{:?} duration", stake_account.duration);
-        emit!(CreateStakeEvent {
-            smart_wallet: ctx.accounts.smart_wallet.key(),
-            stake: ctx.accounts.stake.key(),
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        smart_wallet.base = legacy.base;
+        smart_wallet.bump = bump;
+        smart_wallet.threshold = legacy.threshold;
+        smart_wallet.minimum_delay = legacy.minimum_delay;
+        smart_wallet.grace_period = legacy.grace_period;
+        smart_wallet.owner_set_seqno = 0;
+        smart_wallet.num_transactions = 0;
+        smart_wallet.owners = legacy.owners.clone();
+        smart_wallet.executor_reimbursement = None;
+        smart_wallet.suspended_owners = vec![];
+        smart_wallet.executor_allowlist = vec![];
+        smart_wallet.event_seq = 0;
+        smart_wallet.audited_programs_only = false;
+        smart_wallet.num_executed = 0;
+        smart_wallet.last_executed_at = 0;
+        smart_wallet.last_approved_at = 0;
+        smart_wallet.proposer_gate = None;
+        smart_wallet.proposal_bond = 0;
+        smart_wallet.proposal_rate_limit = None;
+        smart_wallet.veto_authority = None;
+        smart_wallet.execution_receipts = false;
+        smart_wallet.expiry_cursor = 0;
+
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletImportEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            legacy_smart_wallet: ctx.accounts.legacy_smart_wallet.key(),
+            owners: legacy.owners,
+            threshold: legacy.threshold,
+            minimum_delay: legacy.minimum_delay,
+            timestamp: Clock::get()?.unix_timestamp
         });
         Ok(())
     }
-    /// inits rollup account.
-    pub fn rollup_entity(
-        ctx: Context<RollupEntityInit>,
-        bump: u8,
-        gid: u16,
-    ) -> ProgramResult {
-        let enrollment_epoch: i64 = Clock::get()?.unix_timestamp;
 
-        let rollup_account = &mut ctx.accounts.rollup;
-        rollup_account.timestamp = enrollment_epoch.to_le_bytes().to_vec();
-        rollup_account.bump = bump;
-        rollup_account.gid = gid;
-        rollup_account.mints = 0;
-        require!(rollup_account.gid == gid, NoGIDJack);
+    /// Copies a pending [Transaction] of the upstream wallet a [SmartWallet] was
+    /// imported from, giving it the next index.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn import_transaction(ctx: Context<ImportTransaction>, bump: u8) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let legacy_wallet = LegacySmartWallet::load(&ctx.accounts.legacy_smart_wallet)?;
+        require!(
+            SmartWallet::address(legacy_wallet.base).0 == ctx.accounts.smart_wallet.key(),
+            InvalidLegacyAccount
+        );
+        let legacy = LegacyTransaction::load(&ctx.accounts.legacy_transaction)?;
+        require!(
+            legacy.smart_wallet == ctx.accounts.legacy_smart_wallet.key(),
+            InvalidLegacyAccount
+        );
+        Transaction::check_instruction_limits(&legacy.instructions)?;
+
+        let index = ctx.accounts.smart_wallet.next_transaction_index()?;
+        ctx.accounts.transaction.import(
+            &ctx.accounts.smart_wallet,
+            index,
+            bump,
+            legacy,
+            &legacy_wallet,
+        )?;
 
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionImportEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            legacy_transaction: ctx.accounts.legacy_transaction.key(),
+            timestamp: Clock::get()?.unix_timestamp
+        });
         Ok(())
     }
-    /// Registers participant.
-    pub fn register_entity(
-        ctx: Context<RegisterEntity>,
-        bump: u8,
-        gid: u16,
+
+    /// Sets the [ExecutorReimbursement] paid out of a wallet-derived vault on execution.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_executor_reimbursement(
+        ctx: Context<SetExecutorReimbursement>,
+        reimbursement: Option<ExecutorReimbursement>,
     ) -> ProgramResult {
-        let enrollment_epoch: i64 = Clock::get()?.unix_timestamp;
-        let ticket_account = &mut ctx.accounts.ticket;
-        let rollup_account = &mut ctx.accounts.rollup;
-        require!(rollup_account.gid == gid, NoGIDJack);
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        if let Some(reimbursement) = reimbursement {
+            let total = unwrap_int!(reimbursement.total_lamports());
+            require!(total <= MAX_EXECUTOR_REIMBURSEMENT, ReimbursementTooHigh);
+        }
+        smart_wallet.executor_reimbursement = reimbursement;
 
-        ticket_account.enrollment_epoch = enrollment_epoch.to_le_bytes().to_vec();
-        ticket_account.bump = bump;
-        ticket_account.gid = gid;
-        ticket_account.mint = ctx.accounts.mint.key();
-        ticket_account.owner = ctx.accounts.owner.key();
-        rollup_account.mints = unwrap_int!(rollup_account.mints.checked_add(1));
-        msg!("{:?}", rollup_account.mints);
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetExecutorReimbursementEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            reimbursement,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Sets the accounts allowed to execute transactions. An empty list allows anyone.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_executor_allowlist(
+        ctx: Context<SetExecutorAllowlist>,
+        executors: Vec<Pubkey>,
+    ) -> ProgramResult {
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        require!(executors.len() <= MAX_EXECUTORS, TooManyExecutors);
+        smart_wallet.executor_allowlist = executors.clone();
 
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetExecutorAllowlistEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            executors,
+            timestamp: Clock::get()?.unix_timestamp
+        });
         Ok(())
     }
 
-    /// claims all in participant.
-    pub fn claim_entities(
-        ctx: Context<ClaimEntities>,
-        bump: u8,
+    /// Enables or disables audited-programs mode, restricting executed instructions to
+    /// [ProtocolConfig::audited_programs].
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_audited_programs_only(
+        ctx: Context<SetAuditedProgramsOnly>,
+        enabled: bool,
     ) -> ProgramResult {
-        let reset_epoch: i64 = Clock::get()?.unix_timestamp;
-        require!(ctx.accounts.rollup.bump == bump, InvalidBump);
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        smart_wallet.audited_programs_only = enabled;
 
-        let rollup_account = &mut ctx.accounts.rollup;
-        // rollup_account.timestamp = reset_epoch.to_le_bytes().to_vec();
-        require!(!ctx.accounts.stake.protected_gids.contains(&rollup_account.gid), ProtectedGid);
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetAuditedProgramsOnlyEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
 
-        let former_epoch = rollup_account.timestamp.clone();
-        let duration = reset_epoch - i64::from_le_bytes(former_epoch.try_into().unwrap());
-        let former_epoch = rollup_account.timestamp.clone();
-        emit!(ClaimEntitiesEvent {
-            smart_wallet: ctx.accounts.smart_wallet.key(),
-            duration: duration.to_le_bytes().to_vec(),
-            last_epoch: former_epoch,
-            reset_epoch: reset_epoch.to_le_bytes().to_vec(),
-            mints: rollup_account.mints,
-            rollup: rollup_account.key(),
-            stake: ctx.accounts.stake.key(),
-            owner: ctx.accounts.owner.key(),
+    /// Sets the [SmartWallet::grace_period] after which approved transactions become
+    /// stale. [NO_GRACE_PERIOD] keeps approvals valid indefinitely.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_grace_period(ctx: Context<SetGracePeriod>, grace_period: i64) -> ProgramResult {
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        require!(
+            grace_period > 0 || grace_period == NO_GRACE_PERIOD,
+            InvalidGracePeriod
+        );
+        smart_wallet.grace_period = grace_period;
+
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetGracePeriodEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            grace_period,
+            never_stale: grace_period == NO_GRACE_PERIOD,
+            timestamp: Clock::get()?.unix_timestamp
         });
         Ok(())
     }
-    /// Updates participant.
-    pub fn update_entity_by_owner(
-        ctx: Context<UpdateEntityByOwner>,
-        bump: u8,
+
+    /// Sets the [ProposerGate] letting token holders who are not owners propose
+    /// [Transaction]s. [None] restricts proposing to the owners.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_proposer_gate(
+        ctx: Context<SetProposerGate>,
+        gate: Option<ProposerGate>,
     ) -> ProgramResult {
-        let reset_epoch: i64 = Clock::get()?.unix_timestamp;
-        let rollup_account = &mut ctx.accounts.rollup;
-        let ticket_account = &mut ctx.accounts.ticket;
-        let ata = anchor_spl::associated_token::get_associated_token_address(
-            &ctx.accounts.owner.key(),
-            &ctx.accounts.mint.key(),
-        );
-        require!(ata == ctx.accounts.mint_ata.key(), InvalidATA);
-        require!(ticket_account.bump == bump, InvalidBump);
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        smart_wallet.proposer_gate = gate;
 
-        rollup_account.mints = unwrap_int!(rollup_account.mints.checked_add(1));
-        ticket_account.enrollment_epoch = reset_epoch.to_le_bytes().to_vec();
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetProposerGateEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            gate,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Sets the [SmartWallet::proposal_bond] locked by proposers. 0 disables it.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_proposal_bond(ctx: Context<SetProposalBond>, lamports: u64) -> ProgramResult {
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        smart_wallet.proposal_bond = lamports;
 
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetProposalBondEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            lamports,
+            timestamp: Clock::get()?.unix_timestamp
+        });
         Ok(())
     }
-    /// Updates participant.
-    pub fn update_entity(
-        ctx: Context<UpdateEntity>,
-        bump: u8,
-        timestamp: Vec<u8>,
+
+    /// Sets the [ProposalRateLimit] on proposals per proposer. [None] removes the cap.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_proposal_rate_limit(
+        ctx: Context<SetProposalRateLimit>,
+        limit: Option<ProposalRateLimit>,
     ) -> ProgramResult {
-        let _owner_index = ctx.accounts.smart_wallet.owner_index(ctx.accounts.smart_wallet_owner.key())?;
-        let timestamp_i = i64::from_le_bytes(timestamp.try_into().unwrap());
-        let ticket_account = &mut ctx.accounts.ticket;
-        let rollup_account = &mut ctx.accounts.rollup;
-        require!(ticket_account.bump == bump, InvalidBump);
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        if let Some(limit) = limit {
+            invariant!(limit.max_proposals > 0, "max_proposals");
+            invariant!(limit.window > 0, "window");
+        }
+        smart_wallet.proposal_rate_limit = limit;
 
-        ticket_account.enrollment_epoch = timestamp_i.to_le_bytes().to_vec();
-        rollup_account.timestamp = timestamp_i.to_le_bytes().to_vec();
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetProposalRateLimitEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            limit,
+            timestamp: Clock::get()?.unix_timestamp
+        });
         Ok(())
     }
-    pub fn withdraw_entity_by_program(
-        ctx: Context<WithdrawEntityByProgram>,
-        bump: u8,
-    ) -> ProgramResult {
-        let _owner_index = ctx.accounts.smart_wallet.owner_index(ctx.accounts.smart_wallet_owner.key())?;
-        // -1 is !false
-        let reset_epoch: i64 = -1;
-        // let rollup_account = &mut ctx.accounts.rollup;
-        let ticket_account = &mut ctx.accounts.ticket;
 
-        require!(ticket_account.bump == bump, InvalidBump);
-        require!(ticket_account.mint == ctx.accounts.mint.key(), InvalidMint);
-        // require!(!ctx.accounts.stake.protected_gids.contains(&ticket_account.gid), ProtectedGid);
+    /// Enables or disables writing an [ExecutionReceipt] on every execution of a
+    /// [Transaction]. When enabled, executors must pass the receipt address and the
+    /// [System] program as remaining accounts, and pay its rent.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_execution_receipts(
+        ctx: Context<SetExecutionReceipts>,
+        enabled: bool,
+    ) -> ProgramResult {
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        smart_wallet.execution_receipts = enabled;
 
-        ticket_account.enrollment_epoch = reset_epoch.to_le_bytes().to_vec();
-        emit!(WithdrawEntityEvent {
-            smart_wallet: ctx.accounts.smart_wallet.key(),
-            mint: ctx.accounts.mint.key(),
-            ticket: ticket_account.key(),
-            stake: ctx.accounts.stake.key(),
-            owner: ctx.accounts.owner.key(),
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetExecutionReceiptsEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp
         });
+        Ok(())
+    }
+
+    /// Sets the [SmartWallet::veto_authority] allowed to veto queued [Transaction]s.
+    /// [None] removes it.
+    /// Signed by the [SmartWallet] from one of its executed [Transaction]s.
+    pub fn set_veto_authority(
+        ctx: Context<SetVetoAuthority>,
+        authority: Option<Pubkey>,
+    ) -> ProgramResult {
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        smart_wallet.veto_authority = authority;
 
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(WalletSetVetoAuthorityEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            authority,
+            timestamp: Clock::get()?.unix_timestamp
+        });
         Ok(())
     }
-    pub fn withdraw_entity(
-        ctx: Context<WithdrawEntity>,
+
+    /// Creates the [ProposerActivity] tracking a proposer against the
+    /// [ProposalRateLimit]. Anyone may call this.
+    pub fn init_proposer_activity(
+        ctx: Context<InitProposerActivity>,
         bump: u8,
+        proposer: Pubkey,
     ) -> ProgramResult {
-        let reset_epoch: i64 = 0;
-        let rollup_account = &mut ctx.accounts.rollup;
-        let ticket_account = &mut ctx.accounts.ticket;
+        let activity = &mut ctx.accounts.proposer_activity;
+        activity.bump = bump;
+        activity.smart_wallet = ctx.accounts.smart_wallet.key();
+        activity.proposer = proposer;
+        activity.window_start = 0;
+        activity.num_proposals = 0;
+        Ok(())
+    }
+
+    /// Reallocates the [SmartWallet] to hold up to `max_owners` owners, with the payer
+    /// funding the additional rent. The address is unchanged, so the base, owner set
+    /// and pending [Transaction]s are unaffected.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn resize_smart_wallet(ctx: Context<ResizeSmartWallet>, max_owners: u8) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let config = ProtocolConfig::load(ctx.remaining_accounts)?;
+        require!(max_owners <= config.max_owners, TooManyOwners);
+        invariant!(
+            (max_owners as usize) >= ctx.accounts.smart_wallet.owners.len(),
+            "max_owners"
+        );
 
-        require!(ticket_account.bump == bump, InvalidBump);
-        require!(ticket_account.mint == ctx.accounts.mint.key(), InvalidMint);
-        require!(!ctx.accounts.stake.protected_gids.contains(&ticket_account.gid), ProtectedGid);
+        let info = ctx.accounts.smart_wallet.to_account_info();
+        let new_len = SmartWallet::space(max_owners);
+        invariant!(new_len >= info.data_len(), "cannot shrink smart wallet");
+        account_utils::realloc(
+            &info,
+            new_len,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
 
-        rollup_account.mints = unwrap_int!(rollup_account.mints.checked_sub(1));
-        ticket_account.enrollment_epoch = reset_epoch.to_le_bytes().to_vec();
-        emit!(WithdrawEntityEvent {
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(WalletResizeEvent {
             smart_wallet: ctx.accounts.smart_wallet.key(),
-            mint: ctx.accounts.mint.key(),
-            ticket: ticket_account.key(),
-            stake: ctx.accounts.stake.key(),
-            owner: ctx.accounts.owner.key(),
+            event_seq,
+            max_owners,
+            timestamp: Clock::get()?.unix_timestamp
         });
-
         Ok(())
     }
 
-    /// Executes ixs arg
+    /// Suspends an owner of the [SmartWallet]. Any other active owner may do this
+    /// without waiting for a threshold approval.
     #[access_control(ctx.accounts.validate())]
-    pub fn execute_ixs(
-        ctx: Context<ExecuteInstructions>,
-        index: u64,
-        bump: u8,
-        ixs: Vec<TXInstruction>,
-    ) -> ProgramResult {
-        let smart_wallet = &ctx.accounts.smart_wallet;
-        let wallet_seeds: &[&[&[u8]]] = &[&[
-            b"GokiSmartWalletDerived" as &[u8],
-            &smart_wallet.key().to_bytes(),
-            &index.to_le_bytes(),
-            &[bump],
-        ]];
+    pub fn suspend_owner(ctx: Context<SuspendOwner>, owner: Pubkey) -> ProgramResult {
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        invariant!(owner != ctx.accounts.owner.key(), "cannot suspend self");
+        smart_wallet.owner_index(owner)?;
+        require!(!smart_wallet.is_suspended(owner), OwnerSuspended);
+        smart_wallet.suspended_owners.push(owner);
 
-        for ix in ixs.iter() {
-            solana_program::program::invoke_signed(&(ix).into(), ctx.remaining_accounts, wallet_seeds)?;
-        }
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(OwnerSuspendEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            owner,
+            suspended_by: ctx.accounts.owner.key(),
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Reinstates a suspended owner of the [SmartWallet].
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    /// Suspended owners count towards this threshold.
+    pub fn reinstate_owner(ctx: Context<ReinstateOwner>, owner: Pubkey) -> ProgramResult {
+        let smart_wallet = &mut ctx.accounts.smart_wallet;
+        smart_wallet.check_all_owner_signers(ctx.remaining_accounts)?;
+        let index = unwrap_or_err!(
+            smart_wallet.suspended_owners.iter().position(|a| *a == owner),
+            OwnerNotSuspended
+        );
+        smart_wallet.suspended_owners.remove(index);
+
+        let event_seq = smart_wallet.next_event_seq()?;
+        emit!(OwnerReinstateEvent {
+            smart_wallet: smart_wallet.key(),
+            event_seq,
+            owner,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Allows an account that is not an owner to propose [Transaction]s, such as a
+    /// program PDA proposing via CPI.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn add_allowed_proposer(ctx: Context<AddAllowedProposer>, bump: u8) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let allowed_proposer = &mut ctx.accounts.allowed_proposer;
+        allowed_proposer.bump = bump;
+        allowed_proposer.smart_wallet = ctx.accounts.smart_wallet.key();
+        allowed_proposer.proposer = ctx.accounts.proposer.key();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(AllowedProposerEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            proposer: allowed_proposer.proposer,
+            allowed: true,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Removes an [AllowedProposer]. Its pending [Transaction]s are unaffected.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn remove_allowed_proposer(ctx: Context<RemoveAllowedProposer>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(AllowedProposerEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            proposer: ctx.accounts.allowed_proposer.proposer,
+            allowed: false,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Registers where an owner wants to be alerted that their approval is needed.
+    pub fn register_owner_notification(
+        ctx: Context<RegisterOwnerNotification>,
+        bump: u8,
+        notification_key: Pubkey,
+        webhook_hash: [u8; 32],
+    ) -> ProgramResult {
+        let owner = ctx.accounts.owner.key();
+        ctx.accounts.smart_wallet.owner_index(owner)?;
+
+        let notification = &mut ctx.accounts.notification;
+        notification.bump = bump;
+        notification.smart_wallet = ctx.accounts.smart_wallet.key();
+        notification.owner = owner;
+        notification.notification_key = notification_key;
+        notification.webhook_hash = webhook_hash;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OwnerNotificationEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            owner,
+            notification: notification.key(),
+            notification_key,
+            webhook_hash,
+            registered: true,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Updates the [OwnerNotification] of an owner.
+    pub fn update_owner_notification(
+        ctx: Context<UpdateOwnerNotification>,
+        notification_key: Pubkey,
+        webhook_hash: [u8; 32],
+    ) -> ProgramResult {
+        let notification = &mut ctx.accounts.notification;
+        notification.notification_key = notification_key;
+        notification.webhook_hash = webhook_hash;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OwnerNotificationEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            owner: notification.owner,
+            notification: notification.key(),
+            notification_key,
+            webhook_hash,
+            registered: true,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Removes the [OwnerNotification] of an owner, refunding its rent to the owner.
+    pub fn remove_owner_notification(ctx: Context<RemoveOwnerNotification>) -> ProgramResult {
+        let notification = &ctx.accounts.notification;
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OwnerNotificationEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            owner: notification.owner,
+            notification: notification.key(),
+            notification_key: notification.notification_key,
+            webhook_hash: notification.webhook_hash,
+            registered: false,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Creates the [OwnerHeartbeat] of an owner, recording a first heartbeat.
+    pub fn init_owner_heartbeat(ctx: Context<InitOwnerHeartbeat>, bump: u8) -> ProgramResult {
+        let owner = ctx.accounts.owner.key();
+        ctx.accounts.smart_wallet.owner_index(owner)?;
+
+        let heartbeat = &mut ctx.accounts.heartbeat;
+        heartbeat.bump = bump;
+        heartbeat.smart_wallet = ctx.accounts.smart_wallet.key();
+        heartbeat.owner = owner;
+        heartbeat.num_heartbeats = 0;
+        let now = Clock::get()?.unix_timestamp;
+        heartbeat.record(now)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OwnerHeartbeatEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            owner: ctx.accounts.owner.key(),
+            num_heartbeats: ctx.accounts.heartbeat.num_heartbeats,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Records that an owner is responsive, in their [OwnerHeartbeat].
+    pub fn owner_heartbeat(ctx: Context<OwnerHeartbeatUpdate>) -> ProgramResult {
+        ctx.accounts
+            .smart_wallet
+            .owner_index(ctx.accounts.owner.key())?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.heartbeat.record(now)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OwnerHeartbeatEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            owner: ctx.accounts.owner.key(),
+            num_heartbeats: ctx.accounts.heartbeat.num_heartbeats,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Proposes a [Transaction] at the next index, [SmartWallet::num_transactions],
+    /// with no ETA. The proposer approves it if they are an owner.
+    pub fn create_transaction(
+        ctx: Context<CreateTransaction>,
+        bump: u8,
+        instructions: Vec<TXInstruction>,
+    ) -> ProgramResult {
+        init_transaction(
+            &mut ctx.accounts.transaction,
+            &mut ctx.accounts.smart_wallet,
+            &ctx.accounts.proposer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            None,
+            bump,
+            instructions,
+            None,
+            NO_ETA,
+            TransactionState::Active,
+        )
+    }
+
+    /// Proposes a [TransactionState::Draft] [Transaction] at the next index, like
+    /// [smart_wallet::create_transaction], whose proposer can add more instructions
+    /// with [smart_wallet::append_transaction]. Owners cannot approve it until the
+    /// proposer calls [smart_wallet::finalize_transaction].
+    pub fn create_draft_transaction(
+        ctx: Context<CreateDraftTransaction>,
+        bump: u8,
+        instructions: Vec<TXInstruction>,
+    ) -> ProgramResult {
+        init_transaction(
+            &mut ctx.accounts.transaction,
+            &mut ctx.accounts.smart_wallet,
+            &ctx.accounts.proposer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            None,
+            bump,
+            instructions,
+            None,
+            NO_ETA,
+            TransactionState::Draft,
+        )
+    }
+
+    /// Proposes a [Transaction] at an address derived from the proposer and a nonce of
+    /// their choosing rather than from [SmartWallet::num_transactions], so owners
+    /// proposing in the same slot do not race for the same address. The [Transaction]
+    /// still takes the next index.
+    pub fn create_proposer_transaction(
+        ctx: Context<CreateProposerTransaction>,
+        bump: u8,
+        _proposer_nonce: u64,
+        instructions: Vec<TXInstruction>,
+        eta: i64,
+    ) -> ProgramResult {
+        init_transaction(
+            &mut ctx.accounts.transaction,
+            &mut ctx.accounts.smart_wallet,
+            &ctx.accounts.proposer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            None,
+            bump,
+            instructions,
+            None,
+            eta,
+            TransactionState::Active,
+        )
+    }
+
+    /// Proposes a [Transaction] storing only the hash of its instructions, like
+    /// [smart_wallet::create_proposer_transaction]. Owners approve the hash, and the
+    /// instructions are supplied and checked against it on execution, which is also
+    /// when [ProtocolConfig::max_instructions] and the instruction limits apply.
+    pub fn create_committed_transaction(
+        ctx: Context<CreateCommittedTransaction>,
+        bump: u8,
+        _proposer_nonce: u64,
+        instructions_hash: [u8; 32],
+        eta: i64,
+    ) -> ProgramResult {
+        init_transaction(
+            &mut ctx.accounts.transaction,
+            &mut ctx.accounts.smart_wallet,
+            &ctx.accounts.proposer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            None,
+            bump,
+            vec![],
+            Some(instructions_hash),
+            eta,
+            TransactionState::Active,
+        )
+    }
+
+    /// Reserves the next [Transaction] index for an owner, so the instructions can be
+    /// uploaded later with [smart_wallet::create_reserved_transaction] without
+    /// contending for [SmartWallet::num_transactions].
+    pub fn reserve_transaction_index(
+        ctx: Context<ReserveTransactionIndex>,
+        bump: u8,
+    ) -> ProgramResult {
+        let proposer = ctx.accounts.proposer.key();
+        ctx.accounts.smart_wallet.check_proposer(
+            ctx.accounts.smart_wallet.key(),
+            proposer,
+            ctx.remaining_accounts,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.smart_wallet.check_rate_limit(
+            ctx.accounts.smart_wallet.key(),
+            proposer,
+            ctx.remaining_accounts,
+            now,
+        )?;
+        let index = ctx.accounts.smart_wallet.next_transaction_index()?;
+
+        let reservation = &mut ctx.accounts.reservation;
+        reservation.bump = bump;
+        reservation.smart_wallet = ctx.accounts.smart_wallet.key();
+        reservation.proposer = proposer;
+        reservation.index = index;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionReserveEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            reservation: ctx.accounts.reservation.key(),
+            proposer,
+            index,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Redeems a [TransactionReservation], proposing a [Transaction] at the reserved
+    /// index. The reservation is closed to the payer.
+    pub fn create_reserved_transaction(
+        ctx: Context<CreateReservedTransaction>,
+        bump: u8,
+        instructions: Vec<TXInstruction>,
+        eta: i64,
+    ) -> ProgramResult {
+        init_transaction(
+            &mut ctx.accounts.transaction,
+            &mut ctx.accounts.smart_wallet,
+            &ctx.accounts.proposer,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            Some(ctx.accounts.reservation.index),
+            bump,
+            instructions,
+            None,
+            eta,
+            TransactionState::Active,
+        )
+    }
+
+    /// Executes a [Transaction] once [SmartWallet::threshold] owners approved it and
+    /// its ETA passed, signing its instructions as the [SmartWallet]. Instructions
+    /// approved individually run only if they passed on their own, once every other
+    /// instruction was rejected, see [Transaction::instructions_settled].
+    ///
+    /// A committed [Transaction] takes its instructions in `revealed`; otherwise it is
+    /// ignored. At most `max_instructions` run per call, resuming from
+    /// [Transaction::instructions_executed], so a [Transaction] too large for one
+    /// Solana transaction executes over several calls. The instructions' accounts and
+    /// the [ProtocolConfig] are passed as remaining accounts, along with any
+    /// [Transaction::dependencies], or the [ExecutionReceipt]s of those closed.
+    pub fn execute_transaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteTransaction<'info>>,
+        revealed: Vec<TXInstruction>,
+        max_instructions: u16,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        tx.check_executable(smart_wallet, now)?;
+        let executor = ctx.accounts.executor.key();
+        tx.check_executor(executor)?;
+        smart_wallet.check_executor(executor)?;
+        require!(!smart_wallet.is_suspended(executor), OwnerSuspended);
+
+        let instructions = tx.reveal_instructions(revealed)?;
+        let config = ProtocolConfig::load(ctx.remaining_accounts)?;
+        require!(
+            instructions.len() <= config.max_instructions as usize,
+            TooManyInstructions
+        );
+        Transaction::check_instruction_limits(&instructions)?;
+        config.check_invoke(smart_wallet, &instructions)?;
+        tx.check_writable_scope(&instructions)?;
+        tx.check_dependencies(ctx.remaining_accounts)?;
+
+        let passed = tx.passed_instructions(smart_wallet, instructions.len());
+        let range = tx.checkpoint_range(instructions.len(), max_instructions as usize);
+        let wallet_seeds: &[&[&[u8]]] = &[&[
+            SMART_WALLET_SEED,
+            &smart_wallet.base.to_bytes(),
+            &[smart_wallet.bump],
+        ]];
+        for index in range.clone() {
+            if passed[index] {
+                solana_program::program::invoke_signed(
+                    &(&instructions[index]).into(),
+                    ctx.remaining_accounts,
+                    wallet_seeds,
+                )?;
+            }
+        }
+        // The instructions may have updated either account, e.g. to advance
+        // [SmartWallet::event_seq], so the copies loaded before are stale.
+        ctx.accounts.smart_wallet.reload()?;
+        ctx.accounts.transaction.reload()?;
+        if !ctx
+            .accounts
+            .transaction
+            .record_checkpoint(range.end, instructions.len())?
+        {
+            return Ok(());
+        }
+
+        let tx = &mut ctx.accounts.transaction;
+        tx.transition(TransactionState::Executed)?;
+        tx.executor = executor;
+        tx.executed_at = now;
+        let proposer_executes = tx.proposer_executes;
+        ctx.accounts.smart_wallet.record_execution(now)?;
+
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let executor_info = ctx.accounts.executor.to_account_info();
+        if smart_wallet.execution_receipts {
+            ExecutionReceipt::write(
+                &ctx.accounts.transaction,
+                &instructions,
+                &executor_info,
+                ctx.remaining_accounts,
+            )?;
+        }
+        let amount = smart_wallet.reimburse_executor(
+            smart_wallet.key(),
+            &executor_info,
+            ctx.remaining_accounts,
+        )?;
+        if amount > 0 {
+            let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+            emit!(ExecutorReimbursedEvent {
+                smart_wallet: ctx.accounts.smart_wallet.key(),
+                event_seq,
+                executor,
+                amount,
+                timestamp: now
+            });
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionExecuteEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            executor,
+            proposer_executes,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Writes a [TransactionCheck] describing whether the [Transaction] can be
+    /// executed to the return data.
+    pub fn check_transaction(ctx: Context<CheckTransaction>) -> ProgramResult {
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        let now = Clock::get()?.unix_timestamp;
+
+        let check = TransactionCheck {
+            readiness: tx.readiness(smart_wallet, now)?,
+            num_signers: tx.num_active_signers(smart_wallet) as u64,
+            threshold: smart_wallet.threshold,
+            eta: tx.eta,
+            expires_at: unwrap_int!(tx.expires_at(smart_wallet)),
+        };
+        solana_program::program::set_return_data(&check.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Emits the [SmartWallet::config_digest], so auditors and counterparties can attest
+    /// to the governance configuration at a point in time. Anyone may call this.
+    pub fn attest_configuration(ctx: Context<AttestConfiguration>) -> ProgramResult {
+        let digest = ctx.accounts.smart_wallet.config_digest()?;
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(WalletAttestEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            digest,
+            owner_set_seqno: ctx.accounts.smart_wallet.owner_set_seqno,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Marks a [Transaction] that is past its grace period as expired. Anyone may call this.
+    /// Advances the [SmartWallet::expiry_cursor].
+    pub fn mark_expired(ctx: Context<MarkExpired>) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!ctx.accounts.transaction.expired, TransactionAlreadyExpired);
+        let index = ctx.accounts.transaction.index;
+        ctx.accounts.smart_wallet.advance_expiry_cursor(index);
+        let expires_at = unwrap_int!(ctx.accounts.transaction.expires_at(&ctx.accounts.smart_wallet));
+        let transaction = ctx.accounts.transaction.to_account_info();
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let bond_slashed = ctx.accounts.transaction.expire(
+            &ctx.accounts.smart_wallet,
+            &transaction,
+            &treasury,
+            now,
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionExpiredEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            expires_at,
+            bond_slashed,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Marks a [Transaction] that is past its grace period as expired, if it is not
+    /// already, and closes it, refunding its rent to the proposer. Anyone may call this.
+    /// Advances the [SmartWallet::expiry_cursor].
+    pub fn reap_transaction(ctx: Context<ReapTransaction>) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let index = ctx.accounts.transaction.index;
+        ctx.accounts.smart_wallet.advance_expiry_cursor(index);
+        if !ctx.accounts.transaction.expired {
+            let expires_at = unwrap_int!(ctx
+                .accounts
+                .transaction
+                .expires_at(&ctx.accounts.smart_wallet));
+            let transaction = ctx.accounts.transaction.to_account_info();
+            let treasury = ctx.accounts.treasury.to_account_info();
+            let bond_slashed = ctx.accounts.transaction.expire(
+                &ctx.accounts.smart_wallet,
+                &transaction,
+                &treasury,
+                now,
+            )?;
+
+            let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+            emit!(TransactionExpiredEvent {
+                smart_wallet: ctx.accounts.smart_wallet.key(),
+                event_seq,
+                transaction: ctx.accounts.transaction.key(),
+                expires_at,
+                bond_slashed,
+                timestamp: now
+            });
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionCloseEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            receiver: ctx.accounts.proposer.key(),
+            bond_refunded: 0,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Refunds the bond of an executed [Transaction] to its proposer. Anyone may call this.
+    pub fn release_proposal_bond(ctx: Context<ReleaseProposalBond>) -> ProgramResult {
+        require!(
+            ctx.accounts.transaction.executed_at != -1,
+            TransactionNotExecuted
+        );
+        let transaction = ctx.accounts.transaction.to_account_info();
+        let proposer = ctx.accounts.proposer.to_account_info();
+        let amount = ctx
+            .accounts
+            .transaction
+            .release_bond(&transaction, &proposer)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(ProposalBondReleaseEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            proposer: proposer.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Closes an executed, cancelled or expired [Transaction], sending its rent to the
+    /// receiver. Any bond still locked in it is refunded to the proposer first.
+    /// Only the proposer of the [Transaction] or the [SmartWallet] itself may do this.
+    pub fn close_transaction(ctx: Context<CloseTransaction>) -> ProgramResult {
+        let authority = ctx.accounts.authority.key();
+        let tx = &ctx.accounts.transaction;
+        require!(
+            authority == tx.proposer || authority == ctx.accounts.smart_wallet.key(),
+            InvalidCloseAuthority
+        );
+        require!(tx.executed_at != -1 || tx.cancelled || tx.expired, TransactionPending);
+        let transaction = ctx.accounts.transaction.to_account_info();
+        let proposer = ctx.accounts.proposer.to_account_info();
+        let bond_refunded = ctx
+            .accounts
+            .transaction
+            .release_bond(&transaction, &proposer)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionCloseEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            receiver: ctx.accounts.receiver.key(),
+            bond_refunded,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Approves a [Transaction], setting the owner's slot in [Transaction::signers].
+    pub fn approve(ctx: Context<Approve>) -> ProgramResult {
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        tx.check_approvable(smart_wallet)?;
+        let owner = ctx.accounts.owner.key();
+        let owner_index = smart_wallet.active_owner_index(owner)?;
+
+        ctx.accounts.transaction.signers[owner_index] = true;
+        ctx.accounts
+            .transaction
+            .refresh_approval_state(&ctx.accounts.smart_wallet)?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.smart_wallet.record_approval(now);
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionApproveEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            owner,
+            memo: None,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Withdraws an owner's approval of a [Transaction] they could still approve,
+    /// including any approval of individual instructions. Advances the owner's
+    /// [Transaction::approval_nonces], so their signed approvals cannot be relayed again.
+    /// The owner pays for any additional space.
+    pub fn unapprove(ctx: Context<Unapprove>) -> ProgramResult {
+        ctx.accounts
+            .transaction
+            .check_approvable(&ctx.accounts.smart_wallet)?;
+        let owner = ctx.accounts.owner.key();
+        let owner_index = ctx.accounts.smart_wallet.active_owner_index(owner)?;
+        let num_owners = ctx.accounts.smart_wallet.owners.len();
+        let tx = &mut ctx.accounts.transaction;
+        tx.signers[owner_index] = false;
+        tx.refresh_approval_state(&ctx.accounts.smart_wallet)?;
+        if let Some(approved) = tx.instruction_signers.get_mut(owner_index) {
+            approved.clear();
+        }
+        if let Some(memo) = tx.approval_memos.get_mut(owner_index) {
+            memo.clear();
+        }
+        tx.bump_approval_nonce(num_owners, owner_index)?;
+        Transaction::realloc_to_fit(
+            tx,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionUnapproveEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            owner,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Approves a [Transaction] like [smart_wallet::approve], storing a memo with the
+    /// owner's rationale in [Transaction::approval_memos]. The owner pays for any
+    /// additional space.
+    pub fn approve_with_memo(ctx: Context<ApproveWithMemo>, memo: Vec<u8>) -> ProgramResult {
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        tx.check_approvable(smart_wallet)?;
+        let owner = ctx.accounts.owner.key();
+        let owner_index = smart_wallet.active_owner_index(owner)?;
+        let num_owners = smart_wallet.owners.len();
+
+        let tx = &mut ctx.accounts.transaction;
+        tx.signers[owner_index] = true;
+        tx.refresh_approval_state(&ctx.accounts.smart_wallet)?;
+        tx.set_approval_memo(num_owners, owner_index, memo.clone())?;
+        Transaction::realloc_to_fit(
+            tx,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.smart_wallet.record_approval(now);
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionApproveEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            owner,
+            memo: Some(memo),
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Approves several [Transaction]s of the [SmartWallet] at once, passed as remaining
+    /// accounts. Fails unless all of them can be approved.
+    pub fn approve_many<'info>(
+        ctx: Context<'_, '_, '_, 'info, ApproveMany<'info>>,
+    ) -> ProgramResult {
+        invariant!(!ctx.remaining_accounts.is_empty(), "no transactions");
+        let smart_wallet_key = ctx.accounts.smart_wallet.key();
+        let owner = ctx.accounts.owner.key();
+        let owner_index = ctx.accounts.smart_wallet.active_owner_index(owner)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        for info in ctx.remaining_accounts.iter() {
+            let mut tx: Account<Transaction> = Account::try_from(info)?;
+            require!(tx.smart_wallet == smart_wallet_key, TransactionWalletMismatch);
+            tx.check_approvable(&ctx.accounts.smart_wallet)?;
+            tx.signers[owner_index] = true;
+            tx.refresh_approval_state(&ctx.accounts.smart_wallet)?;
+            tx.exit(ctx.program_id)?;
+
+            let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+            emit!(TransactionApproveEvent {
+                smart_wallet: smart_wallet_key,
+                event_seq,
+                transaction: info.key(),
+                owner,
+                memo: None,
+                timestamp: now
+            });
+        }
+        ctx.accounts.smart_wallet.record_approval(now);
+        Ok(())
+    }
+
+    /// Records an owner's approval carried as a signed message, for owners who cannot
+    /// easily send transactions. Anyone may relay it, along with an ed25519 program
+    /// instruction verifying the owner's signature over
+    /// [Transaction::approval_message] for `domain` and `expiry`.
+    pub fn approve_with_message(
+        ctx: Context<ApproveWithMessage>,
+        domain: String,
+        expiry: i64,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < expiry, ApprovalMessageExpired);
+        require!(
+            domain.len() <= MAX_APPROVAL_DOMAIN_LEN,
+            ApprovalDomainTooLong
+        );
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        tx.check_approvable(smart_wallet)?;
+        let owner = ctx.accounts.owner.key();
+        let owner_index = smart_wallet.active_owner_index(owner)?;
+
+        let message = tx.approval_message(&domain, owner, owner_index, tx.key(), expiry)?;
+        signature_utils::check_ed25519_signature(
+            &ctx.accounts.instructions,
+            &owner,
+            message.as_bytes(),
+        )?;
+        ctx.accounts.transaction.signers[owner_index] = true;
+        ctx.accounts
+            .transaction
+            .refresh_approval_state(&ctx.accounts.smart_wallet)?;
+        ctx.accounts.smart_wallet.record_approval(now);
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionApproveEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            owner,
+            memo: None,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Records an owner's approval signed offline, e.g. on a hardware wallet, so a
+    /// relayer can post it without the owner signing the transaction. Anyone may relay
+    /// it, along with an ed25519 program instruction verifying the owner's signature
+    /// over [Transaction::approval_payload].
+    pub fn approve_with_signature(ctx: Context<ApproveWithSignature>) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        tx.check_approvable(smart_wallet)?;
+        let owner = ctx.accounts.owner.key();
+        let owner_index = smart_wallet.active_owner_index(owner)?;
+
+        signature_utils::check_ed25519_signature(
+            &ctx.accounts.instructions,
+            &owner,
+            &tx.approval_payload(owner_index)?,
+        )?;
+        ctx.accounts.transaction.signers[owner_index] = true;
+        ctx.accounts
+            .transaction
+            .refresh_approval_state(&ctx.accounts.smart_wallet)?;
+        ctx.accounts.smart_wallet.record_approval(now);
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionApproveEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            owner,
+            memo: None,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Records the approvals of several owners at once, each signed offline over their
+    /// own [Transaction::approval_payload] like for [smart_wallet::approve_with_signature],
+    /// so an owner's unapproval invalidates only their signature. Anyone may relay them,
+    /// along with ed25519 program instructions verifying each of the `signatures`.
+    pub fn approve_aggregate(
+        ctx: Context<ApproveAggregate>,
+        signatures: Vec<OwnerSig>,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        tx.check_approvable(smart_wallet)?;
+
+        for sig in signatures.iter() {
+            let owner_index = ctx.accounts.smart_wallet.active_owner_index(sig.owner)?;
+            let payload = ctx.accounts.transaction.approval_payload(owner_index)?;
+            signature_utils::check_ed25519_owner_sig(&ctx.accounts.instructions, sig, &payload)?;
+            ctx.accounts.transaction.signers[owner_index] = true;
+            ctx.accounts
+                .transaction
+                .refresh_approval_state(&ctx.accounts.smart_wallet)?;
+
+            let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+            emit!(TransactionApproveEvent {
+                smart_wallet: ctx.accounts.smart_wallet.key(),
+                event_seq,
+                transaction: ctx.accounts.transaction.key(),
+                owner: sig.owner,
+                memo: None,
+                timestamp: now
+            });
+        }
+        ctx.accounts.smart_wallet.record_approval(now);
+        Ok(())
+    }
+
+    /// Approves only some of a [Transaction]'s instructions, marked in `approved`.
+    /// Execution runs the instructions that met [SmartWallet::threshold] individually,
+    /// so part of a bundled proposal can pass without re-proposing it, once the owners
+    /// leaving out the others made their threshold unreachable. Calling it again
+    /// replaces the owner's previous selection.
+    pub fn approve_instructions(
+        ctx: Context<ApproveInstructions>,
+        approved: Vec<bool>,
+    ) -> ProgramResult {
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let tx = &ctx.accounts.transaction;
+        tx.check_approvable(smart_wallet)?;
+        let owner = ctx.accounts.owner.key();
+        let owner_index = smart_wallet.active_owner_index(owner)?;
+        let num_owners = smart_wallet.owners.len();
+
+        let tx = &mut ctx.accounts.transaction;
+        tx.approve_instructions(num_owners, owner_index, approved.clone())?;
+        Transaction::realloc_to_fit(
+            tx,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.smart_wallet.record_approval(now);
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionApproveInstructionsEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            owner,
+            approved,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Appends `instructions` to a [TransactionState::Draft] [Transaction], so proposals
+    /// too large for one Solana transaction, e.g. program upgrades with many accounts,
+    /// can be uploaded in chunks. The [ProtocolConfig] is passed as a remaining account.
+    pub fn append_transaction(
+        ctx: Context<AppendTransaction>,
+        instructions: Vec<TXInstruction>,
+    ) -> ProgramResult {
+        let tx = &ctx.accounts.transaction;
+        require!(tx.state == TransactionState::Draft, TransactionNotDraft);
+        let config = ProtocolConfig::load(ctx.remaining_accounts)?;
+        require!(
+            tx.instructions.len() + instructions.len() <= config.max_instructions as usize,
+            TooManyInstructions
+        );
+        Transaction::check_instruction_limits(&instructions)?;
+        tx.check_writable_scope(&instructions)?;
+
+        let tx = &mut ctx.accounts.transaction;
+        tx.instructions.extend(instructions.iter().cloned());
+        Transaction::realloc_to_fit(
+            tx,
+            &ctx.accounts.proposer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionAppendEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            instructions,
+            num_instructions: ctx.accounts.transaction.instructions.len() as u32,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Finalizes a [TransactionState::Draft] [Transaction], locking its instructions.
+    /// Owners may approve it from now on, and `eta` is checked against the
+    /// [SmartWallet::minimum_delay] from now.
+    pub fn finalize_transaction(ctx: Context<FinalizeTransaction>, eta: i64) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let tx = &ctx.accounts.transaction;
+        require!(tx.state == TransactionState::Draft, TransactionNotDraft);
+        ctx.accounts
+            .transaction
+            .activate(&ctx.accounts.smart_wallet, eta, now)?;
+        if ctx.accounts.transaction.signers.contains(&true) {
+            ctx.accounts.smart_wallet.record_approval(now);
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionFinalizeEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            eta,
+            estimate: TransactionEstimate::new(&ctx.accounts.transaction.instructions),
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Declares the accounts a [Transaction]'s instructions may write to, so approvers
+    /// can bound its effects without decoding them. It is checked on execution, and
+    /// right away for stored instructions. It cannot be changed once declared.
+    pub fn declare_writable_scope(
+        ctx: Context<DeclareWritableScope>,
+        writable_scope: Vec<Pubkey>,
+    ) -> ProgramResult {
+        let tx = &ctx.accounts.transaction;
+        require!(tx.executed_at == -1, AlreadyExecuted);
+        require!(tx.writable_scope.is_none(), WritableScopeAlreadyDeclared);
+
+        let tx = &mut ctx.accounts.transaction;
+        tx.writable_scope = Some(writable_scope.clone());
+        tx.check_writable_scope(&tx.instructions)?;
+        Transaction::realloc_to_fit(
+            tx,
+            &ctx.accounts.proposer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionDeclareScopeEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: tx.key(),
+            writable_scope,
+            timestamp: Clock::get()?.unix_timestamp
+        });
         Ok(())
     }
+
+    /// Declares the indexes of [Transaction]s that must execute before this one, so a
+    /// multi-step migration can be queued at once, in any order, and executed safely.
+    /// Dependencies may be any other [Transaction]s, including later ones; a cycle only
+    /// blocks its own members, which can be cancelled. They cannot be changed once
+    /// declared.
+    pub fn declare_dependencies(
+        ctx: Context<DeclareDependencies>,
+        dependencies: Vec<u64>,
+    ) -> ProgramResult {
+        let tx = &ctx.accounts.transaction;
+        require!(tx.executed_at == -1, AlreadyExecuted);
+        require!(tx.dependencies.is_empty(), DependenciesAlreadyDeclared);
+        require!(
+            dependencies.len() <= MAX_TRANSACTION_DEPENDENCIES,
+            TooManyDependencies
+        );
+        require!(
+            dependencies.iter().all(|&index| index != tx.index),
+            InvalidDependency
+        );
+
+        let tx = &mut ctx.accounts.transaction;
+        tx.dependencies = dependencies.clone();
+        Transaction::realloc_to_fit(
+            tx,
+            &ctx.accounts.proposer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionDeclareDependenciesEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            dependencies,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Reserves execution of a [Transaction] for its proposer, who prepares it and
+    /// executes it after sign-off, or lifts the reservation. It can only change
+    /// before any other owner approves.
+    pub fn set_proposer_executes(
+        ctx: Context<SetProposerExecutes>,
+        proposer_executes: bool,
+    ) -> ProgramResult {
+        let tx = &ctx.accounts.transaction;
+        require!(tx.executed_at == -1, AlreadyExecuted);
+        require!(
+            !tx.approved_by_others(&ctx.accounts.smart_wallet),
+            TransactionAlreadyApproved
+        );
+        ctx.accounts.transaction.proposer_executes = proposer_executes;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionSetProposerExecutesEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            proposer_executes,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Cancels a pending [Transaction], so it can never be executed. Only its proposer
+    /// or the [SmartWallet] itself, through an executed [Transaction], may do this.
+    pub fn cancel_transaction(ctx: Context<CancelTransaction>) -> ProgramResult {
+        let authority = ctx.accounts.authority.key();
+        let tx = &ctx.accounts.transaction;
+        require!(
+            authority == tx.proposer || authority == ctx.accounts.smart_wallet.key(),
+            InvalidCancelAuthority
+        );
+        require!(tx.executed_at == -1, AlreadyExecuted);
+        require!(!tx.cancelled, TransactionCancelled);
+        ctx.accounts
+            .transaction
+            .transition(TransactionState::Cancelled)?;
+        ctx.accounts.transaction.cancelled = true;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionCancelEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            authority,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Permanently invalidates a queued [Transaction] before its ETA.
+    /// Only the [SmartWallet::veto_authority] may do this.
+    pub fn veto_transaction(ctx: Context<VetoTransaction>) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let authority = ctx.accounts.veto_authority.key();
+        require!(
+            ctx.accounts.smart_wallet.veto_authority == Some(authority),
+            InvalidVetoAuthority
+        );
+        let tx = &ctx.accounts.transaction;
+        require!(tx.executed_at == -1, AlreadyExecuted);
+        require!(!tx.cancelled, TransactionCancelled);
+        require!(tx.eta != NO_ETA && now < tx.eta, VetoWindowClosed);
+        ctx.accounts
+            .transaction
+            .transition(TransactionState::Cancelled)?;
+        ctx.accounts.transaction.cancelled = true;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TransactionVetoEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            transaction: ctx.accounts.transaction.key(),
+            veto_authority: authority,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Creates a [Stake].
+    /// Requires [SmartWallet::threshold] owners to sign: the owner, plus any others
+    /// passed as remaining accounts.
+    pub fn create_stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateStake<'info>>,
+        bump: u8,
+        abs_index: u64,
+        stake_data: StakeData,
+    ) -> ProgramResult {
+        let mut signers = ctx.remaining_accounts.to_vec();
+        signers.push(ctx.accounts.owner.to_account_info());
+        ctx.accounts.smart_wallet.check_owner_signers(&signers)?;
+        invariant!(stake_data.distribution_interval >= 0, "distribution_interval");
+        require!(
+            stake_data
+                .protected_gid_ranges
+                .iter()
+                .all(|range| range.start <= range.end),
+            StakeErrorCode::InvalidGidRange
+        );
+
+        let stake_account = &mut ctx.accounts.stake;
+        stake_account.bump = bump;
+        stake_account.genesis_epoch = stake_data.genesis_epoch;
+        stake_account.name = stake_data.name;
+        stake_account.reward_pot = stake_data.reward_pot;
+        stake_account.duration = stake_data.duration;
+        stake_account.protected_gids = stake_data.protected_gids;
+        stake_account.uuid = stake_data.uuid;
+        stake_account.reward_curve = stake_data.reward_curve;
+        stake_account.total_mints = 0;
+        stake_account.smart_wallet = ctx.accounts.smart_wallet.key();
+        stake_account.index = abs_index;
+        stake_account.reward_mint = ctx.accounts.reward_mint.key();
+        stake_account.reward_vault = ctx.accounts.reward_vault.key();
+        stake_account.distribution_interval = stake_data.distribution_interval;
+        stake_account.rewards_credited = 0;
+        stake_account.max_rewards_per_owner = stake_data.max_rewards_per_owner;
+        stake_account.gid_remaps = vec![];
+        stake_account.mint_allowlist = stake_data.mint_allowlist;
+        stake_account.min_hold_seconds = stake_data.min_hold_seconds;
+        stake_account.enrollment_fee = stake_data.enrollment_fee;
+        stake_account.metadata_uri = String::new();
+        stake_account.require_master_edition = stake_data.require_master_edition;
+        stake_account.burn_bps = 0;
+        stake_account.protected_gid_ranges = stake_data.protected_gid_ranges;
+        stake_account.reward_emission = None;
+        stake_account.claim_window = None;
+        stake_account.participant_allowlist = None;
+
+        // msg!("Stake genesis for {:?} with {:?} genesis_epoch", stake_account.key(), stake_account.genesis_epoch);
+        // msg!("{:?} duration", stake_account.duration);
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(CreateStakeEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+        });
+        Ok(())
+    }
+    /// Extends the duration and reward pot of a live [Stake].
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn extend_stake(
+        ctx: Context<ExtendStake>,
+        additional_duration: i32,
+        additional_reward_pot: i64,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        invariant!(additional_duration >= 0, "additional_duration");
+        invariant!(additional_reward_pot >= 0, "additional_reward_pot");
+
+        let now = Clock::get()?.unix_timestamp;
+        let stake = &mut ctx.accounts.stake;
+        require!(now < stake.ends_at()?, StakeErrorCode::StakeEnded);
+        stake.duration = unwrap_int!(stake.duration.checked_add(additional_duration));
+        stake.reward_pot = unwrap_int!(stake.reward_pot.checked_add(additional_reward_pot));
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(ExtendStakeEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            duration: stake.duration,
+            reward_pot: stake.reward_pot,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Sets the uri of off-chain JSON (artwork, description, rules) describing a [Stake].
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_stake_metadata(ctx: Context<SetStakeMetadata>, uri: String) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        invariant!(uri.len() <= MAX_STAKE_URI_LENGTH, StakeErrorCode::StakeMetadataUriTooLong);
+
+        let stake = &mut ctx.accounts.stake;
+        stake.metadata_uri = uri.clone();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeMetadataEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            uri,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets the basis points of each claimed reward burned instead of paid out.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_reward_burn(ctx: Context<SetRewardBurn>, burn_bps: u16) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        invariant!(burn_bps <= 10_000, StakeErrorCode::InvalidBurnBps);
+
+        let stake = &mut ctx.accounts.stake;
+        stake.burn_bps = burn_bps;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetRewardBurnEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            burn_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Switches a [Stake] to minting rewards on claim, signed by the [Stake] as mint
+    /// authority of the reward mint, at most `max_per_epoch` per `epoch_duration`
+    /// seconds. [None] returns to paying rewards out of the vault.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_reward_emission(
+        ctx: Context<SetRewardEmission>,
+        emission: Option<RewardEmissionParams>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let stake = &mut ctx.accounts.stake;
+        stake.reward_emission = match emission {
+            Some(params) => {
+                invariant!(params.epoch_duration > 0, "epoch_duration");
+                Some(RewardEmission {
+                    epoch_duration: params.epoch_duration,
+                    max_per_epoch: params.max_per_epoch,
+                    epoch_start: now,
+                    minted_in_epoch: 0,
+                })
+            }
+            None => None,
+        };
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetRewardEmissionEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            emission,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Restricts reward claims on a [Stake] to a [ClaimWindow], e.g. the last week of
+    /// each month, reallocating it to fit with the payer funding any additional rent.
+    /// [None] lets rewards be claimed at any time.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_claim_window(
+        ctx: Context<SetClaimWindow>,
+        claim_window: Option<ClaimWindow>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        if let Some(window) = claim_window {
+            window.validate()?;
+        }
+
+        Stake::realloc_to_fit(
+            &ctx.accounts.stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.claim_window = claim_window;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetClaimWindowEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            claim_window,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets the [RewardRounding] of a [Stake]'s reward credits. The remainder is carried
+    /// in each [Rollup], so no rewards are lost whatever the mode.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_reward_rounding(
+        ctx: Context<SetRewardRounding>,
+        rounding: RewardRounding,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        Stake::realloc_to_fit(
+            &ctx.accounts.stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.reward_rounding = rounding;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetRewardRoundingEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            rounding,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets whether rewards of a [Stake] may only be paid to the associated token
+    /// account of the reward mint owned by the [Rollup] owner, so phishing flows
+    /// cannot redirect claims.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_owner_ata_claims(ctx: Context<SetOwnerAtaClaims>, enabled: bool) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        Stake::realloc_to_fit(
+            &ctx.accounts.stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.owner_ata_claims = enabled;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetOwnerAtaClaimsEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets the encryption keys of the delegates allowed to read a [Stake]'s
+    /// [Stake::sealed_params]. The program only stores them for clients to encrypt to.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_stake_viewers(ctx: Context<SetStakeViewers>, viewers: Vec<Pubkey>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        require!(
+            viewers.len() <= MAX_STAKE_VIEWERS,
+            StakeErrorCode::TooManyViewers
+        );
+
+        Stake::realloc_to_fit(
+            &ctx.accounts.stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.viewers = viewers.clone();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetViewersEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            viewers,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Commits to private parameters of a [Stake], e.g. an unannounced reward rate,
+    /// with their keccak hash, and stores them encrypted to the [Stake::viewers]. They
+    /// cannot be replaced until revealed with [smart_wallet::reveal_stake_params].
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn seal_stake_params(
+        ctx: Context<SealStakeParams>,
+        commitment: [u8; 32],
+        ciphertext: Vec<u8>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        require!(
+            ctx.accounts.stake.sealed_params.is_none(),
+            StakeErrorCode::SealedParamsPending
+        );
+        require!(
+            ciphertext.len() <= MAX_SEALED_PARAMS_LEN,
+            StakeErrorCode::SealedParamsTooLong
+        );
+
+        Stake::realloc_to_fit(
+            &ctx.accounts.stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.sealed_params = Some(SealedParams {
+            commitment,
+            ciphertext: ciphertext.clone(),
+        });
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSealParamsEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            commitment,
+            ciphertext,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Reveals the [Stake::sealed_params], checking that `params` hash to their
+    /// commitment, and clears them.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn reveal_stake_params(ctx: Context<RevealStakeParams>, params: Vec<u8>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let sealed = ctx
+            .accounts
+            .stake
+            .sealed_params
+            .take()
+            .ok_or(StakeErrorCode::NoSealedParams)?;
+        sealed.check_reveal(&params)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeRevealParamsEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            commitment: sealed.commitment,
+            params,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Adds a [SmartWallet] as a sponsor of a [Stake], allowing it to fund the [Stake].
+    /// Requires [SmartWallet::threshold] owners of both wallets to sign, passed as
+    /// remaining accounts.
+    pub fn add_stake_sponsor(ctx: Context<AddStakeSponsor>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        ctx.accounts.sponsor.check_owner_signers(ctx.remaining_accounts)?;
+        let sponsor = ctx.accounts.sponsor.key();
+        let stake = &ctx.accounts.stake;
+        require!(!stake.is_admin(sponsor), StakeErrorCode::SponsorAlreadyAdded);
+        require!(stake.sponsors.len() < MAX_STAKE_SPONSORS, StakeErrorCode::TooManySponsors);
+
+        Stake::realloc_to_fit(
+            stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.sponsors.push(StakeSponsor {
+            smart_wallet: sponsor,
+            funded: 0,
+        });
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSponsorEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            sponsor,
+            added: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Removes a sponsor of a [Stake]. Its contributions stay in the reward pot.
+    /// May be done by the [Stake]'s [SmartWallet] or by the sponsor itself.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn remove_stake_sponsor(
+        ctx: Context<RemoveStakeSponsor>,
+        sponsor: Pubkey,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        let stake = &mut ctx.accounts.stake;
+        require!(
+            smart_wallet == stake.smart_wallet || smart_wallet == sponsor,
+            StakeErrorCode::NotStakeAdmin
+        );
+        let position = stake
+            .sponsors
+            .iter()
+            .position(|s| s.smart_wallet == sponsor)
+            .ok_or(StakeErrorCode::SponsorNotFound)?;
+        stake.sponsors.remove(position);
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSponsorEvent {
+            smart_wallet,
+            event_seq,
+            stake: stake.key(),
+            sponsor,
+            added: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Transfers rewards into the vault of a [Stake] and adds them to its reward pot,
+    /// crediting them to the funding [SmartWallet] if it is a sponsor and authorized the
+    /// transfer, as the authority or through a derived wallet whose [SubaccountInfo] is
+    /// passed as a remaining account.
+    pub fn fund_stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, FundStake<'info>>,
+        amount: u64,
+    ) -> ProgramResult {
+        invariant!(amount <= i64::MAX as u64, "amount");
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        let authorized = SubaccountInfo::is_wallet_authority(
+            smart_wallet,
+            ctx.accounts.authority.key(),
+            ctx.remaining_accounts,
+        );
+        let stake = &mut ctx.accounts.stake;
+        stake.reward_pot = unwrap_int!(stake.reward_pot.checked_add(amount as i64));
+        if let Some(sponsor) = stake
+            .sponsors
+            .iter_mut()
+            .find(|s| authorized && s.smart_wallet == smart_wallet)
+        {
+            sponsor.funded = unwrap_int!(sponsor.funded.checked_add(amount));
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeFundEvent {
+            smart_wallet,
+            event_seq,
+            stake: stake.key(),
+            amount,
+            reward_pot: stake.reward_pot,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Replaces the protected gids and gid ranges of a [Stake], reallocating it to
+    /// fit them with the payer funding any additional rent.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_protected_gids(
+        ctx: Context<SetProtectedGids>,
+        protected_gids: Vec<u16>,
+        protected_gid_ranges: Vec<GidRange>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        require!(
+            protected_gid_ranges
+                .iter()
+                .all(|range| range.start <= range.end),
+            StakeErrorCode::InvalidGidRange
+        );
+
+        account_utils::realloc(
+            &ctx.accounts.stake.to_account_info(),
+            Stake::space(protected_gids.len(), protected_gid_ranges.len()),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.protected_gids = protected_gids.clone();
+        stake.protected_gid_ranges = protected_gid_ranges.clone();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetProtectedGidsEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            protected_gids,
+            protected_gid_ranges,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Treats tickets and rollups registered with gid `from` as gid `to`.
+    /// Remapping a gid onto itself removes its mapping.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn remap_gid(ctx: Context<RemapGid>, from: u16, to: u16) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let stake = &mut ctx.accounts.stake;
+        stake.gid_remaps.retain(|remap| remap.from != from);
+        if from != to {
+            require!(stake.gid_remaps.len() < MAX_GID_REMAPS, StakeErrorCode::TooManyGidRemaps);
+            stake.gid_remaps.push(GidRemap { from, to });
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(RemapGidEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            from,
+            to,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates the [Stake::bonus_budget], a budget for promotional bonuses such as
+    /// [Quest]s held in its own vault, so they cannot eat into the reward pot.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn create_bonus_budget(ctx: Context<CreateBonusBudget>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        require!(
+            ctx.accounts.stake.bonus_budget.is_none(),
+            StakeErrorCode::BonusBudgetAlreadyCreated
+        );
+
+        Stake::realloc_to_fit(
+            &ctx.accounts.stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let vault = ctx.accounts.bonus_vault.key();
+        let stake = &mut ctx.accounts.stake;
+        stake.bonus_budget = Some(BonusBudget {
+            vault,
+            funded: 0,
+            spent: 0,
+        });
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(BonusBudgetCreateEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            vault,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Transfers tokens into the vault of the [Stake::bonus_budget] and adds them to
+    /// the budget.
+    pub fn fund_bonus_budget(ctx: Context<FundBonusBudget>, amount: u64) -> ProgramResult {
+        let budget = ctx
+            .accounts
+            .stake
+            .bonus_budget
+            .ok_or(StakeErrorCode::NoBonusBudget)?;
+        require!(
+            budget.vault == ctx.accounts.bonus_vault.key(),
+            StakeErrorCode::InvalidBonusVault
+        );
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.bonus_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stake = &mut ctx.accounts.stake;
+        let budget = stake.fund_bonus_budget(amount)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(BonusBudgetFundEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            amount,
+            funded: budget.funded,
+            spent: budget.spent,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates a [Quest] paying a one-time bonus from the [Stake]'s bonus budget, or
+    /// from its reward pot if it has none.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn create_quest(
+        ctx: Context<CreateQuest>,
+        bump: u8,
+        index: u64,
+        requirement: QuestRequirement,
+        bonus: u64,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let quest = &mut ctx.accounts.quest;
+        quest.bump = bump;
+        quest.stake = ctx.accounts.stake.key();
+        quest.index = index;
+        quest.requirement = requirement;
+        quest.bonus = bonus;
+        quest.num_claims = 0;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(QuestCreateEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: quest.stake,
+            quest: quest.key(),
+            requirement,
+            bonus,
+        });
+        Ok(())
+    }
+
+    /// Claims the bonus of a [Quest] for a [Rollup] whose [Ticket] satisfies its requirement.
+    pub fn claim_quest<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimQuest<'info>>,
+        bump: u8,
+        quest_claim_bump: u8,
+    ) -> ProgramResult {
+        ProtocolConfig::check_not_paused(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        require!(ctx.accounts.rollup.bump == bump, StakeErrorCode::InvalidBump);
+        ctx.accounts
+            .stake
+            .check_claim_destination(ctx.accounts.rollup.owner, ctx.accounts.owner_tokens.key())?;
+        ctx.accounts.ticket.check_address(smart_wallet, ctx.accounts.ticket.key())?;
+        require!(ctx.accounts.ticket.owner == ctx.accounts.owner.key(), StakeErrorCode::OwnerMismatch);
+        require!(ctx.accounts.ticket.gid == ctx.accounts.rollup.gid, StakeErrorCode::GidMismatch);
+        ctx.accounts.quest.check_eligible(
+            &ctx.accounts.stake,
+            &ctx.accounts.ticket,
+            &ctx.accounts.rollup,
+            now,
+        )?;
+
+        let amount = ctx.accounts.quest.bonus;
+        let budgeted = ctx.accounts.stake.bonus_budget.is_some();
+        if budgeted {
+            ctx.accounts
+                .stake
+                .spend_bonus_budget(ctx.accounts.reward_vault.key(), amount)?;
+        } else {
+            require!(
+                ctx.accounts.reward_vault.key() == ctx.accounts.stake.reward_vault,
+                StakeErrorCode::AccountMismatch
+            );
+            ctx.accounts.stake.reserve_bonus(amount)?;
+        }
+        let quest = &mut ctx.accounts.quest;
+        quest.num_claims = unwrap_int!(quest.num_claims.checked_add(1));
+
+        let quest_claim = &mut ctx.accounts.quest_claim;
+        quest_claim.bump = quest_claim_bump;
+        quest_claim.quest = quest.key();
+        quest_claim.rollup = ctx.accounts.rollup.key();
+        quest_claim.amount = amount;
+        quest_claim.claimed_at = now;
+
+        if amount > 0 && budgeted {
+            let stake_info = ctx.accounts.stake.to_account_info();
+            ctx.accounts.stake.pay_bonus(
+                stake_info,
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.owner_tokens.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                amount,
+                now,
+            )?;
+        } else if amount > 0 {
+            let stake_info = ctx.accounts.stake.to_account_info();
+            let burned = ctx.accounts.stake.pay_rewards(
+                stake_info,
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.owner_tokens.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.remaining_accounts,
+                ctx.accounts.rollup.owner,
+                amount,
+                now,
+            )?;
+            if burned > 0 {
+                let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+                emit!(RewardBurnEvent {
+                    smart_wallet: ctx.accounts.smart_wallet.key(),
+                    event_seq,
+                    stake: ctx.accounts.stake.key(),
+                    amount: burned,
+                    timestamp: Clock::get()?.unix_timestamp
+                });
+            }
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(QuestClaimEvent {
+            smart_wallet,
+            event_seq,
+            quest: ctx.accounts.quest.key(),
+            rollup: ctx.accounts.rollup.key(),
+            owner: ctx.accounts.owner.key(),
+            amount,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Creates a [Milestone] minting a badge NFT to owners who claim at least `min_claimed` rewards.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn create_milestone(
+        ctx: Context<CreateMilestone>,
+        bump: u8,
+        index: u64,
+        min_claimed: u64,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        Milestone::check_metadata(&name, &symbol, &uri)?;
+
+        let milestone = &mut ctx.accounts.milestone;
+        milestone.bump = bump;
+        milestone.stake = ctx.accounts.stake.key();
+        milestone.index = index;
+        milestone.min_claimed = min_claimed;
+        milestone.name = name;
+        milestone.symbol = symbol;
+        milestone.uri = uri;
+        milestone.num_minted = 0;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(MilestoneCreateEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: milestone.stake,
+            milestone: milestone.key(),
+            min_claimed,
+        });
+        Ok(())
+    }
+
+    /// Mints the badge of a [Milestone] to an owner whose [OwnerRewards] reached it.
+    ///
+    /// The [SmartWallet] is the mint authority, update authority and verified creator.
+    /// Its mint authority is revoked after minting a supply of one.
+    pub fn claim_milestone(ctx: Context<ClaimMilestone>, bump: u8) -> ProgramResult {
+        ProtocolConfig::check_not_paused(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.owner_rewards.claimed >= ctx.accounts.milestone.min_claimed,
+            StakeErrorCode::MilestoneNotReached
+        );
+        require!(
+            ctx.accounts.metadata.key() == token_metadata::metadata_address(ctx.accounts.mint.key()),
+            StakeErrorCode::InvalidMint
+        );
+
+        let milestone = &mut ctx.accounts.milestone;
+        milestone.num_minted = unwrap_int!(milestone.num_minted.checked_add(1));
+
+        let milestone_claim = &mut ctx.accounts.milestone_claim;
+        milestone_claim.bump = bump;
+        milestone_claim.milestone = milestone.key();
+        milestone_claim.owner = ctx.accounts.owner.key();
+        milestone_claim.mint = ctx.accounts.mint.key();
+        milestone_claim.claimed_at = now;
+
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let wallet_seeds: &[&[&[u8]]] = &[&[
+            SMART_WALLET_SEED,
+            &smart_wallet.base.to_bytes(),
+            &[smart_wallet.bump],
+        ]];
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.owner_tokens.to_account_info(),
+                    authority: smart_wallet.to_account_info(),
+                },
+                wallet_seeds,
+            ),
+            1,
+        )?;
+
+        let ix = token_metadata::create_metadata_account_v2(
+            ctx.accounts.metadata.key(),
+            ctx.accounts.mint.key(),
+            smart_wallet.key(),
+            ctx.accounts.owner.key(),
+            smart_wallet.key(),
+            token_metadata::DataV2 {
+                name: milestone.name.clone(),
+                symbol: milestone.symbol.clone(),
+                uri: milestone.uri.clone(),
+                seller_fee_basis_points: 0,
+                creators: Some(vec![token_metadata::Creator {
+                    address: smart_wallet.key(),
+                    verified: true,
+                    share: 100,
+                }]),
+                collection: None,
+                uses: None,
+            },
+            true,
+        )?;
+        solana_program::program::invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.metadata.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                smart_wallet.to_account_info(),
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            wallet_seeds,
+        )?;
+
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::SetAuthority {
+                    account_or_mint: ctx.accounts.mint.to_account_info(),
+                    current_authority: smart_wallet.to_account_info(),
+                },
+                wallet_seeds,
+            ),
+            spl_token::instruction::AuthorityType::MintTokens,
+            None,
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(MilestoneClaimEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            milestone: milestone.key(),
+            owner: ctx.accounts.owner.key(),
+            mint: ctx.accounts.mint.key(),
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// inits rollup account.
+    pub fn rollup_entity(
+        ctx: Context<RollupEntityInit>,
+        bump: u8,
+        gid: u16,
+    ) -> ProgramResult {
+        let enrollment_epoch: i64 = Clock::get()?.unix_timestamp;
+
+        let rollup_account = &mut ctx.accounts.rollup;
+        rollup_account.timestamp = enrollment_epoch.to_le_bytes().to_vec();
+        rollup_account.bump = bump;
+        rollup_account.gid = gid;
+        rollup_account.mints = 0;
+        rollup_account.owner = ctx.accounts.owner.key();
+        rollup_account.accrued = 0;
+        rollup_account.stake = ctx.accounts.stake.key();
+        rollup_account.accrued_dust = 0;
+        rollup_account.dust_owed = false;
+        require!(rollup_account.gid == gid, StakeErrorCode::GidMismatch);
+
+        Ok(())
+    }
+    /// Moves a legacy [Rollup] derived per [SmartWallet] into a [Rollup] derived per
+    /// [Stake], closing the legacy account to the owner.
+    pub fn migrate_rollup(ctx: Context<MigrateRollup>, bump: u8) -> ProgramResult {
+        let legacy_info = ctx.accounts.legacy_rollup.to_account_info();
+        let legacy = LegacyRollup::load(&legacy_info)?;
+        legacy.check_address(
+            ctx.accounts.smart_wallet.key(),
+            ctx.accounts.owner.key(),
+            legacy_info.key(),
+        )?;
+
+        let rollup = &mut ctx.accounts.rollup;
+        rollup.bump = bump;
+        rollup.timestamp = legacy.timestamp;
+        rollup.gid = legacy.gid;
+        rollup.mints = legacy.mints;
+        rollup.owner = ctx.accounts.owner.key();
+        rollup.accrued = 0;
+        rollup.stake = ctx.accounts.stake.key();
+        rollup.accrued_dust = 0;
+        rollup.dust_owed = false;
+
+        // Mints registered before stakes tracked their totals.
+        let stake = &mut ctx.accounts.stake;
+        stake.total_mints = unwrap_int!(stake.total_mints.checked_add(legacy.mints.into()));
+
+        let owner_info = ctx.accounts.owner.to_account_info();
+        let lamports = legacy_info.lamports();
+        **legacy_info.try_borrow_mut_lamports()? = 0;
+        **owner_info.try_borrow_mut_lamports()? = unwrap_int!(owner_info.lamports().checked_add(lamports));
+        legacy_info.try_borrow_mut_data()?.fill(0);
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(MigrateRollupEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            legacy_rollup: legacy_info.key(),
+            rollup: rollup.key(),
+            mints: rollup.mints,
+        });
+        Ok(())
+    }
+    /// Inits the [OwnerRewards] account tracking an owner's claims from a [Stake].
+    pub fn init_owner_rewards(ctx: Context<InitOwnerRewards>, bump: u8) -> ProgramResult {
+        let owner_rewards = &mut ctx.accounts.owner_rewards;
+        owner_rewards.bump = bump;
+        owner_rewards.stake = ctx.accounts.stake.key();
+        owner_rewards.owner = ctx.accounts.owner.key();
+        owner_rewards.claimed = 0;
+        Ok(())
+    }
+    /// Authorizes a crank to claim the owner's rewards from a [Stake] at most once
+    /// every `interval` seconds, paying them to `destination`.
+    pub fn authorize_crank(
+        ctx: Context<AuthorizeCrank>,
+        bump: u8,
+        crank: Pubkey,
+        interval: i64,
+    ) -> ProgramResult {
+        invariant!(interval >= 0, StakeErrorCode::InvalidCrankInterval);
+        let permission = &mut ctx.accounts.crank_permission;
+        permission.bump = bump;
+        permission.stake = ctx.accounts.stake.key();
+        permission.owner = ctx.accounts.owner.key();
+        permission.crank = crank;
+        permission.destination = ctx.accounts.destination.key();
+        permission.interval = interval;
+        permission.last_claimed_at = 0;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(CrankPermissionEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: permission.stake,
+            owner: permission.owner,
+            crank,
+            destination: permission.destination,
+            interval,
+            authorized: true,
+        });
+        Ok(())
+    }
+    /// Revokes the owner's [CrankPermission].
+    pub fn revoke_crank(ctx: Context<RevokeCrank>) -> ProgramResult {
+        let permission = &ctx.accounts.crank_permission;
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(CrankPermissionEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: permission.stake,
+            owner: permission.owner,
+            crank: permission.crank,
+            destination: permission.destination,
+            interval: permission.interval,
+            authorized: false,
+        });
+        Ok(())
+    }
+    /// Claims rewards of a [Rollup] on behalf of its owner, as authorized by a [CrankPermission].
+    pub fn crank_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrankClaim<'info>>,
+        bump: u8,
+    ) -> ProgramResult {
+        ProtocolConfig::check_not_paused(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.rollup.bump == bump, StakeErrorCode::InvalidBump);
+        ctx.accounts
+            .stake
+            .check_claim_destination(ctx.accounts.rollup.owner, ctx.accounts.destination.key())?;
+        let permission = &mut ctx.accounts.crank_permission;
+        let next_claim_at = unwrap_int!(permission.last_claimed_at.checked_add(permission.interval));
+        require!(now >= next_claim_at, StakeErrorCode::CrankTooEarly);
+        permission.last_claimed_at = now;
+
+        let rollup_account = &mut ctx.accounts.rollup;
+        require!(!ctx.accounts.stake.is_protected(rollup_account.gid), StakeErrorCode::ProtectedGid);
+
+        let former_epoch = rollup_account.timestamp.clone();
+        let duration = now - rollup_account.last_epoch()?;
+        let rewards = ctx.accounts.stake.claim_rollup(
+            rollup_account,
+            &mut ctx.accounts.owner_rewards,
+            now,
+        )?;
+        if rewards > 0 {
+            let stake_info = ctx.accounts.stake.to_account_info();
+            let burned = ctx.accounts.stake.pay_rewards(
+                stake_info,
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.remaining_accounts,
+                rollup_account.owner,
+                rewards,
+                now,
+            )?;
+            if burned > 0 {
+                let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+                emit!(RewardBurnEvent {
+                    smart_wallet: ctx.accounts.smart_wallet.key(),
+                    event_seq,
+                    stake: ctx.accounts.stake.key(),
+                    amount: burned,
+                    timestamp: Clock::get()?.unix_timestamp
+                });
+            }
+        }
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(ClaimEntitiesEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            duration: duration.to_le_bytes().to_vec(),
+            last_epoch: former_epoch,
+            reset_epoch: now.to_le_bytes().to_vec(),
+            mints: rollup_account.mints,
+            rewards,
+            rollup: rollup_account.key(),
+            stake: ctx.accounts.stake.key(),
+            owner: ctx.accounts.owner.key(),
+            destination: ctx.accounts.destination.key(),
+        });
+        Ok(())
+    }
+    /// Allows a mint to be registered in a [Stake] with [Stake::mint_allowlist] set.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn add_allowed_mint(ctx: Context<AddAllowedMint>, bump: u8) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let allowed_mint = &mut ctx.accounts.allowed_mint;
+        allowed_mint.bump = bump;
+        allowed_mint.stake = ctx.accounts.stake.key();
+        allowed_mint.mint = ctx.accounts.mint.key();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(AllowedMintEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: allowed_mint.stake,
+            mint: allowed_mint.mint,
+            allowed: true,
+        });
+        Ok(())
+    }
+    /// Removes a mint from the allowlist of a [Stake]. Registered tickets are unaffected.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn remove_allowed_mint(ctx: Context<RemoveAllowedMint>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(AllowedMintEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            mint: ctx.accounts.allowed_mint.mint,
+            allowed: false,
+        });
+        Ok(())
+    }
+    /// Restricts registration in a [Stake] to owners with an [AllowedParticipant],
+    /// added by governance or, with a Merkle root, proven by the owner with
+    /// [smart_wallet::prove_participant]. [None] lets anyone register. Reallocates the
+    /// [Stake] to fit, with the payer funding any additional rent.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_participant_allowlist(
+        ctx: Context<SetParticipantAllowlist>,
+        allowlist: Option<ParticipantAllowlist>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        Stake::realloc_to_fit(
+            &ctx.accounts.stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.participant_allowlist = allowlist;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetParticipantAllowlistEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            allowlist,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /// Allows an owner to register in a [Stake] with [Stake::participant_allowlist] set.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn add_allowed_participant(ctx: Context<AddAllowedParticipant>, bump: u8) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let allowed = &mut ctx.accounts.allowed_participant;
+        allowed.bump = bump;
+        allowed.stake = ctx.accounts.stake.key();
+        allowed.owner = ctx.accounts.owner.key();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(AllowedParticipantEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: allowed.stake,
+            owner: allowed.owner,
+            allowed: true,
+        });
+        Ok(())
+    }
+    /// Creates the signing owner's [AllowedParticipant] from a proof that they are in
+    /// the Merkle root of the [Stake]'s [ParticipantAllowlist].
+    pub fn prove_participant(
+        ctx: Context<ProveParticipant>,
+        bump: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> ProgramResult {
+        let root = match ctx.accounts.stake.participant_allowlist {
+            Some(ParticipantAllowlist::Merkle { root }) => root,
+            _ => return Err(StakeErrorCode::NoMerkleAllowlist.into()),
+        };
+        let owner = ctx.accounts.owner.key();
+        require!(
+            stake_utils::verify_merkle_proof(&proof, root, owner),
+            StakeErrorCode::InvalidMerkleProof
+        );
+
+        let allowed = &mut ctx.accounts.allowed_participant;
+        allowed.bump = bump;
+        allowed.stake = ctx.accounts.stake.key();
+        allowed.owner = owner;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(AllowedParticipantEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: allowed.stake,
+            owner,
+            allowed: true,
+        });
+        Ok(())
+    }
+    /// Removes an owner from the allowlist of a [Stake]. Registered tickets are unaffected.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn remove_allowed_participant(ctx: Context<RemoveAllowedParticipant>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(AllowedParticipantEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            owner: ctx.accounts.allowed_participant.owner,
+            allowed: false,
+        });
+        Ok(())
+    }
+
+    /// Creates the [FeeExemption] of an account in a [Stake], so its registrations
+    /// skip the enrollment fee and its claims skip the reward burn.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn add_fee_exemption(ctx: Context<AddFeeExemption>, bump: u8) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let exemption = &mut ctx.accounts.fee_exemption;
+        exemption.bump = bump;
+        exemption.stake = ctx.accounts.stake.key();
+        exemption.account = ctx.accounts.account.key();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(FeeExemptionEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: exemption.stake,
+            account: exemption.account,
+            exempt: true,
+        });
+        Ok(())
+    }
+    /// Closes a [FeeExemption].
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemption>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(FeeExemptionEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            account: ctx.accounts.fee_exemption.account,
+            exempt: false,
+        });
+        Ok(())
+    }
+
+    /// Sets the [YieldVenue] idle rewards of a [Stake] may be deposited into, or
+    /// removes it. It can only change while nothing is deposited.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_yield_venue(ctx: Context<SetYieldVenue>, venue: Option<Pubkey>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let stake = &ctx.accounts.stake;
+        require!(
+            !stake.has_yield_deposit(),
+            StakeErrorCode::RewardsDeposited
+        );
+
+        Stake::realloc_to_fit(
+            stake,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let stake = &mut ctx.accounts.stake;
+        stake.yield_venue = venue.map(|program_id| YieldVenue {
+            program_id,
+            deposited: 0,
+        });
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeSetYieldVenueEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: stake.key(),
+            venue,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Deposits up to `amount` of a [Stake]'s reward vault into its [YieldVenue] by
+    /// invoking the venue with `data`, signed by the [Stake]. The venue's accounts are
+    /// passed as remaining accounts, alongside the owners. Claims are blocked until
+    /// the deposit is recalled with [smart_wallet::recall_reward_vault].
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn deposit_reward_vault<'info>(
+        ctx: Context<'_, '_, '_, 'info, RouteRewardVault<'info>>,
+        amount: u64,
+        data: Vec<u8>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let before = ctx.accounts.reward_vault.amount;
+        ctx.accounts.stake.invoke_yield_venue(
+            &ctx.accounts.stake.to_account_info(),
+            &ctx.accounts.venue_program,
+            ctx.remaining_accounts,
+            data,
+        )?;
+        ctx.accounts.reward_vault.reload()?;
+        let deposit = before
+            .checked_sub(ctx.accounts.reward_vault.amount)
+            .ok_or(StakeErrorCode::InvalidYieldTransfer)?;
+        require!(deposit <= amount, StakeErrorCode::YieldDepositExceeded);
+
+        let venue = ctx
+            .accounts
+            .stake
+            .yield_venue
+            .as_mut()
+            .ok_or(StakeErrorCode::YieldVenueNotSet)?;
+        venue.deposited = unwrap_int!(venue.deposited.checked_add(deposit));
+        let deposited = venue.deposited;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeYieldDepositEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            amount: deposit,
+            deposited,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Recalls rewards of a [Stake] from its [YieldVenue] into the reward vault by
+    /// invoking the venue with `data`, signed by the [Stake]. Anything recalled beyond
+    /// the deposit is yield, and stays in the vault as rewards.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn recall_reward_vault<'info>(
+        ctx: Context<'_, '_, '_, 'info, RouteRewardVault<'info>>,
+        data: Vec<u8>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let before = ctx.accounts.reward_vault.amount;
+        ctx.accounts.stake.invoke_yield_venue(
+            &ctx.accounts.stake.to_account_info(),
+            &ctx.accounts.venue_program,
+            ctx.remaining_accounts,
+            data,
+        )?;
+        ctx.accounts.reward_vault.reload()?;
+        let amount = ctx
+            .accounts
+            .reward_vault
+            .amount
+            .checked_sub(before)
+            .ok_or(StakeErrorCode::InvalidYieldTransfer)?;
+
+        let venue = ctx
+            .accounts
+            .stake
+            .yield_venue
+            .as_mut()
+            .ok_or(StakeErrorCode::YieldVenueNotSet)?;
+        let recalled = amount.min(venue.deposited);
+        venue.deposited = unwrap_int!(venue.deposited.checked_sub(recalled));
+        let deposited = venue.deposited;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(StakeYieldRecallEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            amount,
+            yield_earned: unwrap_int!(amount.checked_sub(recalled)),
+            deposited,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates the [RentPool] of a [SmartWallet], which pays the rent of its stakers'
+    /// [Ticket]s and [Rollup]s. Fund it by transferring lamports to it.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn create_rent_pool(ctx: Context<CreateRentPool>, bump: u8) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let rent_pool = &mut ctx.accounts.rent_pool;
+        rent_pool.bump = bump;
+        rent_pool.smart_wallet = ctx.accounts.smart_wallet.key();
+        rent_pool.outstanding = 0;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(RentPoolCreateEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            rent_pool: ctx.accounts.rent_pool.key(),
+        });
+        Ok(())
+    }
+    /// Pays the rent of the [Ticket] of a held mint, and of the owner's [Rollup] of `gid`
+    /// if it does not exist yet, out of the [RentPool]. Must precede
+    /// [smart_wallet::rollup_entity] and [smart_wallet::register_entity], which then
+    /// create the accounts without charging the payer. The mint must be registrable
+    /// as for [smart_wallet::register_entity], with the same remaining accounts.
+    pub fn sponsor_rent<'info>(
+        ctx: Context<'_, '_, '_, 'info, SponsorRent<'info>>,
+        _gid: u16,
+    ) -> ProgramResult {
+        require!(ctx.accounts.ticket.lamports() == 0, StakeErrorCode::RentAlreadySponsored);
+        ctx.accounts.stake.check_registrable(
+            ctx.accounts.stake.key(),
+            &ctx.accounts.mint,
+            ctx.accounts.owner.key(),
+            ctx.remaining_accounts,
+        )?;
+
+        let pool_info = ctx.accounts.rent_pool.to_account_info();
+        let owner = ctx.accounts.owner.key();
+        let mut funded = vec![(
+            ctx.accounts.ticket.key(),
+            ctx.accounts
+                .rent_pool
+                .sponsor(&pool_info, &ctx.accounts.ticket, Ticket::space())?,
+        )];
+        if ctx.accounts.rollup.lamports() == 0 {
+            funded.push((
+                ctx.accounts.rollup.key(),
+                ctx.accounts
+                    .rent_pool
+                    .sponsor(&pool_info, &ctx.accounts.rollup, Rollup::space())?,
+            ));
+        }
+
+        for (account, lamports) in funded {
+            let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+            emit!(RentSponsorEvent {
+                smart_wallet: ctx.accounts.smart_wallet.key(),
+                event_seq,
+                owner,
+                account,
+                lamports,
+            });
+        }
+        Ok(())
+    }
+    /// Closes a withdrawn [Ticket]. Its rent goes to the [RentPool] if the wallet has
+    /// one, otherwise to the owner.
+    pub fn close_ticket(ctx: Context<CloseTicket>) -> ProgramResult {
+        require!(ctx.accounts.ticket.enrollment()? <= 0, StakeErrorCode::TicketNotWithdrawn);
+        let lamports = ctx.accounts.ticket.to_account_info().lamports();
+        RentPool::reclaim(
+            &ctx.accounts.rent_pool,
+            ctx.accounts.rent_receiver.key(),
+            ctx.accounts.owner.key(),
+            lamports,
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(RentReclaimEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            account: ctx.accounts.ticket.key(),
+            receiver: ctx.accounts.rent_receiver.key(),
+            lamports,
+        });
+        Ok(())
+    }
+    /// Closes a [Rollup] with no mints and no unclaimed rewards. Its rent goes to the
+    /// [RentPool] if the wallet has one, otherwise to the owner.
+    pub fn close_rollup(ctx: Context<CloseRollup>) -> ProgramResult {
+        let rollup = &ctx.accounts.rollup;
+        require!(rollup.mints == 0 && rollup.accrued == 0, StakeErrorCode::RollupNotEmpty);
+        let lamports = rollup.to_account_info().lamports();
+        RentPool::reclaim(
+            &ctx.accounts.rent_pool,
+            ctx.accounts.rent_receiver.key(),
+            ctx.accounts.owner.key(),
+            lamports,
+        )?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(RentReclaimEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            account: ctx.accounts.rollup.key(),
+            receiver: ctx.accounts.rent_receiver.key(),
+            lamports,
+        });
+        Ok(())
+    }
+    /// Registers participant.
+    pub fn register_entity<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterEntity<'info>>,
+        bump: u8,
+        gid: u16,
+    ) -> ProgramResult {
+        let enrollment_epoch: i64 = Clock::get()?.unix_timestamp;
+        ctx.accounts.stake.check_registrable(
+            ctx.accounts.stake.key(),
+            &ctx.accounts.mint,
+            ctx.accounts.owner.key(),
+            ctx.remaining_accounts,
+        )?;
+        let config = ProtocolConfig::load(ctx.remaining_accounts)?;
+        if !FeeExemption::is_exempt(
+            ctx.accounts.stake.key(),
+            ctx.accounts.owner.key(),
+            ctx.remaining_accounts,
+        ) {
+            ctx.accounts.stake.charge_enrollment_fee(
+                &config,
+                &ctx.accounts.payer.to_account_info(),
+                ctx.remaining_accounts,
+            )?;
+        }
+        let ticket_account = &mut ctx.accounts.ticket;
+        let rollup_account = &mut ctx.accounts.rollup;
+        require!(rollup_account.gid == gid, StakeErrorCode::GidMismatch);
+
+        ticket_account.bump = bump;
+        ticket_account.gid = gid;
+        ticket_account.mint = ctx.accounts.mint.key();
+        ticket_account.owner = ctx.accounts.owner.key();
+        let stake_account = &mut ctx.accounts.stake;
+        stake_account.enroll(ticket_account, rollup_account, enrollment_epoch)?;
+        msg!("{:?}", rollup_account.mints);
+
+        Ok(())
+    }
+
+    /// claims all in participant.
+    pub fn claim_entities<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimEntities<'info>>,
+        bump: u8,
+    ) -> ProgramResult {
+        ProtocolConfig::check_not_paused(ctx.remaining_accounts)?;
+        let reset_epoch: i64 = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.rollup.bump == bump, StakeErrorCode::InvalidBump);
+        ctx.accounts
+            .stake
+            .check_claim_destination(ctx.accounts.rollup.owner, ctx.accounts.owner_tokens.key())?;
+
+        let rollup_account = &mut ctx.accounts.rollup;
+        // rollup_account.timestamp = reset_epoch.to_le_bytes().to_vec();
+        require!(!ctx.accounts.stake.is_protected(rollup_account.gid), StakeErrorCode::ProtectedGid);
+
+        let former_epoch = rollup_account.timestamp.clone();
+        let duration = reset_epoch - rollup_account.last_epoch()?;
+        let rewards = ctx.accounts.stake.claim_rollup(
+            rollup_account,
+            &mut ctx.accounts.owner_rewards,
+            reset_epoch,
+        )?;
+        if rewards > 0 {
+            let stake_info = ctx.accounts.stake.to_account_info();
+            let burned = ctx.accounts.stake.pay_rewards(
+                stake_info,
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.owner_tokens.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.remaining_accounts,
+                rollup_account.owner,
+                rewards,
+                reset_epoch,
+            )?;
+            if burned > 0 {
+                let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+                emit!(RewardBurnEvent {
+                    smart_wallet: ctx.accounts.smart_wallet.key(),
+                    event_seq,
+                    stake: ctx.accounts.stake.key(),
+                    amount: burned,
+                    timestamp: Clock::get()?.unix_timestamp
+                });
+            }
+        }
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(ClaimEntitiesEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            duration: duration.to_le_bytes().to_vec(),
+            last_epoch: former_epoch,
+            reset_epoch: reset_epoch.to_le_bytes().to_vec(),
+            mints: rollup_account.mints,
+            rewards,
+            rollup: rollup_account.key(),
+            stake: ctx.accounts.stake.key(),
+            owner: ctx.accounts.owner.key(),
+            destination: ctx.accounts.owner_tokens.key(),
+        });
+        Ok(())
+    }
+    /// Claims rewards like [smart_wallet::claim_entities], paying them to a token
+    /// account owned by `destination_owner` instead of the owner, e.g. a cold wallet.
+    ///
+    /// The destination must have no delegate or close authority, so nobody but
+    /// `destination_owner` can move the rewards once paid.
+    pub fn claim_entities_to<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimEntitiesTo<'info>>,
+        bump: u8,
+        destination_owner: Pubkey,
+    ) -> ProgramResult {
+        ProtocolConfig::check_not_paused(ctx.remaining_accounts)?;
+        let reset_epoch: i64 = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.rollup.bump == bump, StakeErrorCode::InvalidBump);
+        ctx.accounts
+            .stake
+            .check_claim_destination(ctx.accounts.rollup.owner, ctx.accounts.destination.key())?;
+        let destination = &ctx.accounts.destination;
+        require!(destination.owner == destination_owner, StakeErrorCode::InvalidDestination);
+        require!(
+            destination.delegate.is_none() && destination.close_authority.is_none(),
+            StakeErrorCode::InvalidDestination
+        );
+
+        let rollup_account = &mut ctx.accounts.rollup;
+        require!(!ctx.accounts.stake.is_protected(rollup_account.gid), StakeErrorCode::ProtectedGid);
+
+        let former_epoch = rollup_account.timestamp.clone();
+        let duration = reset_epoch - rollup_account.last_epoch()?;
+        let rewards = ctx.accounts.stake.claim_rollup(
+            rollup_account,
+            &mut ctx.accounts.owner_rewards,
+            reset_epoch,
+        )?;
+        if rewards > 0 {
+            let stake_info = ctx.accounts.stake.to_account_info();
+            let burned = ctx.accounts.stake.pay_rewards(
+                stake_info,
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.remaining_accounts,
+                rollup_account.owner,
+                rewards,
+                reset_epoch,
+            )?;
+            if burned > 0 {
+                let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+                emit!(RewardBurnEvent {
+                    smart_wallet: ctx.accounts.smart_wallet.key(),
+                    event_seq,
+                    stake: ctx.accounts.stake.key(),
+                    amount: burned,
+                    timestamp: Clock::get()?.unix_timestamp
+                });
+            }
+        }
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(ClaimEntitiesEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            duration: duration.to_le_bytes().to_vec(),
+            last_epoch: former_epoch,
+            reset_epoch: reset_epoch.to_le_bytes().to_vec(),
+            mints: rollup_account.mints,
+            rewards,
+            rollup: rollup_account.key(),
+            stake: ctx.accounts.stake.key(),
+            owner: ctx.accounts.owner.key(),
+            destination: ctx.accounts.destination.key(),
+        });
+        Ok(())
+    }
+    /// Credits accrued rewards to the [Rollup]s passed as remaining accounts.
+    /// Anyone may call this. Rollups credited within the [Stake]'s
+    /// distribution interval, and rollups of protected gids, are skipped. The last
+    /// rollup passed is stored in [Stake::distribution_cursor].
+    pub fn distribute_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeRewards<'info>>,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        let stake = &mut ctx.accounts.stake;
+
+        let mut rollups: u32 = 0;
+        let mut rewards: u64 = 0;
+        for info in ctx.remaining_accounts.iter() {
+            let mut rollup: Account<Rollup> = Account::try_from(info)?;
+            rollup.check_address(info.key())?;
+            require!(rollup.stake == stake.key(), StakeErrorCode::AccountMismatch);
+            if stake.is_protected(rollup.gid) {
+                continue;
+            }
+            let elapsed = unwrap_int!(now.checked_sub(rollup.last_epoch()?));
+            if elapsed < stake.distribution_interval {
+                continue;
+            }
+            let credited = stake.credit_rollup(&mut rollup, now)?;
+            rollup.exit(ctx.program_id)?;
+
+            rollups = unwrap_int!(rollups.checked_add(1));
+            rewards = unwrap_int!(rewards.checked_add(credited));
+        }
+        if let Some(last) = ctx.remaining_accounts.last() {
+            stake.distribution_cursor = last.key();
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(DistributeRewardsEvent {
+            smart_wallet,
+            event_seq,
+            stake: stake.key(),
+            rollups,
+            rewards,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Updates participant.
+    pub fn update_entity_by_owner(
+        ctx: Context<UpdateEntityByOwner>,
+        bump: u8,
+    ) -> ProgramResult {
+        let reset_epoch: i64 = Clock::get()?.unix_timestamp;
+        let rollup_account = &mut ctx.accounts.rollup;
+        let ticket_account = &mut ctx.accounts.ticket;
+        let ata = anchor_spl::associated_token::get_associated_token_address(
+            &ctx.accounts.owner.key(),
+            &ctx.accounts.mint.key(),
+        );
+        require!(ata == ctx.accounts.mint_ata.key(), StakeErrorCode::InvalidTokenAccount);
+        require!(ticket_account.bump == bump, StakeErrorCode::InvalidBump);
+        ticket_account.check_address(ctx.accounts.smart_wallet.key(), ticket_account.key())?;
+        require!(
+            ticket_account.mint == ctx.accounts.mint.key(),
+            StakeErrorCode::InvalidTokenAccount
+        );
+        require!(ticket_account.owner == ctx.accounts.owner.key(), StakeErrorCode::OwnerMismatch);
+        require!(rollup_account.owner == ticket_account.owner, StakeErrorCode::OwnerMismatch);
+        require!(rollup_account.gid == ticket_account.gid, StakeErrorCode::GidMismatch);
+        require!(!ticket_account.is_frozen(), StakeErrorCode::TicketFrozen);
+        require!(ticket_account.enrollment()? <= 0, StakeErrorCode::TicketNotWithdrawn);
+
+        let stake_account = &mut ctx.accounts.stake;
+        stake_account.enroll(ticket_account, rollup_account, reset_epoch)?;
+
+        Ok(())
+    }
+    /// Counts a pending [Ticket] in its [Rollup] once [Stake::min_hold_seconds] have
+    /// passed since enrollment and the owner still holds the mint. Anyone may call this.
+    pub fn activate_entity(ctx: Context<ActivateEntity>) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.check_address(ctx.accounts.smart_wallet.key(), ticket.key())?;
+        require!(!ticket.is_frozen(), StakeErrorCode::TicketFrozen);
+        require!(ticket.enrollment()? > 0 && ticket.pending_until != 0, StakeErrorCode::TicketNotPending);
+        require!(now >= ticket.pending_until, StakeErrorCode::HoldTimeNotReached);
+        let rollup = &mut ctx.accounts.rollup;
+        require!(rollup.owner == ticket.owner, StakeErrorCode::OwnerMismatch);
+        require!(rollup.gid == ticket.gid, StakeErrorCode::GidMismatch);
+        let mint_ata = &ctx.accounts.mint_ata;
+        require!(
+            mint_ata.mint == ticket.mint && mint_ata.owner == ticket.owner && mint_ata.amount > 0,
+            StakeErrorCode::InvalidTokenAccount
+        );
+
+        ticket.pending_until = 0;
+        ctx.accounts.stake.count_ticket(rollup, now)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(ActivateEntityEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            ticket: ticket.key(),
+            rollup: rollup.key(),
+            timestamp: now,
+        });
+        Ok(())
+    }
+    /// Freezes a [Ticket] during a dispute, e.g. over a suspected stolen mint. It
+    /// stops accruing and cannot be withdrawn until unfrozen; rewards already
+    /// credited to its [Rollup] are unaffected.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn freeze_ticket(ctx: Context<FreezeTicket>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.check_address(ctx.accounts.smart_wallet.key(), ticket.key())?;
+        require!(!ticket.is_frozen(), StakeErrorCode::TicketFrozen);
+        require!(ticket.enrollment()? > 0, StakeErrorCode::TicketNotEnrolled);
+        let rollup = &mut ctx.accounts.rollup;
+        require!(rollup.owner == ticket.owner, StakeErrorCode::OwnerMismatch);
+        require!(rollup.gid == ticket.gid, StakeErrorCode::GidMismatch);
+
+        ctx.accounts.stake.uncount_ticket(ticket, rollup, now)?;
+        ticket.pending_until = FROZEN_TICKET;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TicketFreezeEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            ticket: ticket.key(),
+            frozen: true,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Unfreezes a [Ticket]. It is left pending, so it accrues again once
+    /// [smart_wallet::activate_entity] checks that the owner still holds the mint.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn unfreeze_ticket(ctx: Context<FreezeTicket>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.check_address(ctx.accounts.smart_wallet.key(), ticket.key())?;
+        require!(ticket.is_frozen(), StakeErrorCode::TicketNotFrozen);
+        ticket.pending_until = now;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(TicketFreezeEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            ticket: ticket.key(),
+            frozen: false,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Emits a [RollupReportEvent] summarizing a page of [Rollup]s of the [Stake],
+    /// passed as remaining accounts, for off-chain reconciliation without an
+    /// indexer. Anyone may call this.
+    pub fn report_rollups(ctx: Context<ReportRollups>) -> ProgramResult {
+        let stake = ctx.accounts.stake.key();
+        let mut rollups: Vec<RollupSummary> = vec![];
+        for info in ctx.remaining_accounts {
+            let rollup: Account<Rollup> = Account::try_from(info)?;
+            rollup.check_address(info.key())?;
+            require!(rollup.stake == stake, StakeErrorCode::AccountMismatch);
+            rollups.push(RollupSummary {
+                owner: rollup.owner,
+                gid: rollup.gid,
+                mints: rollup.mints,
+                accrued: rollup.accrued,
+                last_epoch: rollup.last_epoch()?,
+            });
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(RollupReportEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            stake,
+            rollups,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Updates participant.
+    pub fn update_entity(
+        ctx: Context<UpdateEntity>,
+        bump: u8,
+        timestamp: Vec<u8>,
+    ) -> ProgramResult {
+        let _owner_index = ctx.accounts.smart_wallet.active_owner_index(ctx.accounts.smart_wallet_owner.key())?;
+        let timestamp: [u8; 8] = timestamp
+            .try_into()
+            .map_err(|_| StakeErrorCode::InvalidTimestamp)?;
+        let timestamp_i = i64::from_le_bytes(timestamp);
+        let ticket_account = &mut ctx.accounts.ticket;
+        let rollup_account = &mut ctx.accounts.rollup;
+        require!(ticket_account.bump == bump, StakeErrorCode::InvalidBump);
+        ticket_account.check_address(ctx.accounts.smart_wallet.key(), ticket_account.key())?;
+        rollup_account.check_address(rollup_account.key())?;
+        require!(rollup_account.owner == ticket_account.owner, StakeErrorCode::OwnerMismatch);
+        require!(rollup_account.gid == ticket_account.gid, StakeErrorCode::GidMismatch);
+        require!(ticket_account.enrollment()? > 0, StakeErrorCode::TicketNotEnrolled);
+
+        ticket_account.enrollment_epoch = timestamp_i.to_le_bytes().to_vec();
+        rollup_account.timestamp = timestamp_i.to_le_bytes().to_vec();
+        Ok(())
+    }
+    /// Sets the enrollment epoch of many [Ticket]s, and the epoch of their [Rollup]s,
+    /// to `timestamp`, crediting the rollups up to it as [Stake::set_entity_epoch].
+    /// The non-signer remaining accounts are (ticket, rollup) pairs; withdrawn tickets
+    /// are skipped.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn update_entities<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateEntities<'info>>,
+        timestamp: i64,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        let stake = ctx.accounts.stake.key();
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let page: Vec<&AccountInfo<'info>> = ctx
+            .remaining_accounts
+            .iter()
+            .filter(|a| !a.is_signer)
+            .collect();
+        let pairs = page.chunks_exact(2);
+        invariant!(pairs.remainder().is_empty(), "accounts must be (ticket, rollup) pairs");
+
+        let mut tickets: Vec<Pubkey> = vec![];
+        for pair in pairs {
+            let mut ticket: Account<Ticket> = Account::try_from(pair[0])?;
+            let mut rollup: Account<Rollup> = Account::try_from(pair[1])?;
+            ticket.check_address(smart_wallet, pair[0].key())?;
+            rollup.check_address(pair[1].key())?;
+            require!(rollup.stake == stake, StakeErrorCode::AccountMismatch);
+            require!(rollup.owner == ticket.owner, StakeErrorCode::OwnerMismatch);
+            require!(rollup.gid == ticket.gid, StakeErrorCode::GidMismatch);
+            if ticket.enrollment()? <= 0 {
+                continue;
+            }
+
+            ctx.accounts
+                .stake
+                .set_entity_epoch(&mut ticket, &mut rollup, timestamp, now)?;
+            ticket.exit(ctx.program_id)?;
+            rollup.exit(ctx.program_id)?;
+            tickets.push(pair[0].key());
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(UpdateEntitiesEvent {
+            smart_wallet,
+            event_seq,
+            tickets,
+            timestamp,
+            updated_at: now,
+        });
+        Ok(())
+    }
+    pub fn withdraw_entity_by_program(
+        ctx: Context<WithdrawEntityByProgram>,
+        bump: u8,
+    ) -> ProgramResult {
+        let _owner_index = ctx.accounts.smart_wallet.active_owner_index(ctx.accounts.smart_wallet_owner.key())?;
+        // -1 is !false
+        let reset_epoch: i64 = -1;
+        let rollup_account = &mut ctx.accounts.rollup;
+        let ticket_account = &mut ctx.accounts.ticket;
+
+        require!(ticket_account.bump == bump, StakeErrorCode::InvalidBump);
+        require!(ticket_account.mint == ctx.accounts.mint.key(), StakeErrorCode::InvalidMint);
+        require!(!ticket_account.is_frozen(), StakeErrorCode::TicketFrozen);
+        require!(rollup_account.owner == ticket_account.owner, StakeErrorCode::OwnerMismatch);
+        require!(rollup_account.gid == ticket_account.gid, StakeErrorCode::GidMismatch);
+        // require!(!ctx.accounts.stake.protected_gids.contains(&ticket_account.gid), StakeErrorCode::ProtectedGid);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.stake.uncount_ticket(ticket_account, rollup_account, now)?;
+        ticket_account.enrollment_epoch = reset_epoch.to_le_bytes().to_vec();
+        ticket_account.pending_until = 0;
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(WithdrawEntityEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            mint: ctx.accounts.mint.key(),
+            ticket: ticket_account.key(),
+            stake: ctx.accounts.stake.key(),
+            owner: ctx.accounts.owner.key(),
+        });
+
+        Ok(())
+    }
+    pub fn withdraw_entity(
+        ctx: Context<WithdrawEntity>,
+        bump: u8,
+    ) -> ProgramResult {
+        let reset_epoch: i64 = 0;
+        let rollup_account = &mut ctx.accounts.rollup;
+        let ticket_account = &mut ctx.accounts.ticket;
+
+        require!(ticket_account.bump == bump, StakeErrorCode::InvalidBump);
+        require!(ticket_account.mint == ctx.accounts.mint.key(), StakeErrorCode::InvalidMint);
+        require!(!ctx.accounts.stake.is_protected(ticket_account.gid), StakeErrorCode::ProtectedGid);
+        require!(!ticket_account.is_frozen(), StakeErrorCode::TicketFrozen);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.stake.uncount_ticket(ticket_account, rollup_account, now)?;
+        ticket_account.enrollment_epoch = reset_epoch.to_le_bytes().to_vec();
+        ticket_account.pending_until = 0;
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(WithdrawEntityEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            mint: ctx.accounts.mint.key(),
+            ticket: ticket_account.key(),
+            stake: ctx.accounts.stake.key(),
+            owner: ctx.accounts.owner.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Withdraws all of an owner's [Ticket]s in a [Stake] at once, across gids. The
+    /// remaining accounts are the owner's [Rollup]s, followed by the tickets; each
+    /// ticket's rollup must be passed. Already withdrawn tickets are skipped.
+    pub fn withdraw_all_entities<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawAllEntities<'info>>,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        let owner = ctx.accounts.owner.key();
+        let num_rollups = ctx
+            .remaining_accounts
+            .iter()
+            .take_while(|a| Rollup::is_rollup(a))
+            .count();
+        let (rollup_infos, ticket_infos) = ctx.remaining_accounts.split_at(num_rollups);
+
+        let mut rollups: Vec<Account<Rollup>> = vec![];
+        for info in rollup_infos {
+            let rollup: Account<Rollup> = Account::try_from(info)?;
+            rollup.check_address(info.key())?;
+            require!(rollup.stake == ctx.accounts.stake.key(), StakeErrorCode::AccountMismatch);
+            require!(rollup.owner == owner, StakeErrorCode::OwnerMismatch);
+            rollups.push(rollup);
+        }
+        Rollup::check_distinct(rollups.iter().map(|r| &**r))?;
+
+        let stake = &mut ctx.accounts.stake;
+        let mut tickets: Vec<Pubkey> = vec![];
+        for info in ticket_infos {
+            let mut ticket: Account<Ticket> = Account::try_from(info)?;
+            ticket.check_address(smart_wallet, info.key())?;
+            require!(ticket.owner == owner, StakeErrorCode::OwnerMismatch);
+            if ticket.enrollment()? <= 0 {
+                continue;
+            }
+            require!(!stake.is_protected(ticket.gid), StakeErrorCode::ProtectedGid);
+            require!(!ticket.is_frozen(), StakeErrorCode::TicketFrozen);
+            let rollup = rollups
+                .iter_mut()
+                .find(|r| r.gid == ticket.gid)
+                .ok_or(StakeErrorCode::MissingRollup)?;
+
+            stake.uncount_ticket(&ticket, rollup, now)?;
+            ticket.enrollment_epoch = 0i64.to_le_bytes().to_vec();
+            ticket.pending_until = 0;
+            ticket.exit(ctx.program_id)?;
+            tickets.push(info.key());
+        }
+        for rollup in rollups.iter() {
+            rollup.exit(ctx.program_id)?;
+        }
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(WithdrawAllEntitiesEvent {
+            smart_wallet,
+            event_seq,
+            stake: ctx.accounts.stake.key(),
+            owner,
+            tickets,
+            rollups: rollups.iter().map(|r| r.key()).collect(),
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Records the [SmartWallet] and index of a subaccount, so its owner can be looked up.
+    pub fn create_subaccount_info(
+        ctx: Context<CreateSubaccountInfo>,
+        _bump: u8,
+        subaccount: Pubkey,
+        subaccount_type: SubaccountType,
+        index: u64,
+    ) -> ProgramResult {
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        let (address, _) = SmartWallet::subaccount_address(smart_wallet, subaccount_type, index);
+        invariant!(address == subaccount, "subaccount address");
+
+        let info = &mut ctx.accounts.subaccount_info;
+        info.smart_wallet = smart_wallet;
+        info.subaccount_type = subaccount_type;
+        info.index = index;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(SubaccountCreateEvent {
+            smart_wallet,
+            event_seq,
+            subaccount,
+            subaccount_type,
+            index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates the [InvokerWhitelist] of the owner invoker at `index`, restricting it
+    /// to the given instructions.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn create_invoker_whitelist(
+        ctx: Context<CreateInvokerWhitelist>,
+        bump: u8,
+        index: u64,
+        permissions: Vec<InvokerPermission>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        InvokerWhitelist::check_permissions(&permissions)?;
+
+        let whitelist = &mut ctx.accounts.invoker_whitelist;
+        whitelist.bump = bump;
+        whitelist.smart_wallet = ctx.accounts.smart_wallet.key();
+        whitelist.index = index;
+        whitelist.permissions = permissions.clone();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(WalletSetInvokerWhitelistEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            index,
+            permissions,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Replaces the permissions of an [InvokerWhitelist], reallocating it to fit
+    /// them with the payer funding any additional rent.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn set_invoker_whitelist(
+        ctx: Context<SetInvokerWhitelist>,
+        permissions: Vec<InvokerPermission>,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        InvokerWhitelist::check_permissions(&permissions)?;
+
+        account_utils::realloc(
+            &ctx.accounts.invoker_whitelist.to_account_info(),
+            InvokerWhitelist::space(permissions.len()),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let whitelist = &mut ctx.accounts.invoker_whitelist;
+        whitelist.permissions = permissions.clone();
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(WalletSetInvokerWhitelistEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            index: whitelist.index,
+            permissions,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Invokes an instruction signed by an owner invoker of the [SmartWallet].
+    /// Any single active owner may do this, without a [Transaction], within the
+    /// invoker's [InvokerWhitelist] if it has one.
+    #[access_control(ctx.accounts.validate())]
+    pub fn owner_invoke_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, OwnerInvokeInstruction<'info>>,
+        index: u64,
+        bump: u8,
+        ix: TXInstruction,
+    ) -> ProgramResult {
+        ProtocolConfig::load(ctx.remaining_accounts)?
+            .check_invoke(&ctx.accounts.smart_wallet, std::slice::from_ref(&ix))?;
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        InvokerWhitelist::check(smart_wallet, index, ctx.remaining_accounts, &ix)?;
+        let invoker_seeds: &[&[&[u8]]] = &[&[
+            OWNER_INVOKER_SEED,
+            &smart_wallet.to_bytes(),
+            &index.to_le_bytes(),
+            &[bump],
+        ]];
+
+        solana_program::program::invoke_signed(&(&ix).into(), ctx.remaining_accounts, invoker_seeds)?;
+        ctx.accounts.smart_wallet.reload()?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OwnerInvokeEvent {
+            smart_wallet,
+            event_seq,
+            index,
+            invoker: ctx.accounts.owner.key(),
+            program_id: ix.program_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Pre-approves `instruction` for a single execution by `key`, signed by the derived
+    /// wallet at `wallet_index`, between `valid_from` and `valid_until`.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_one_shot(
+        ctx: Context<CreateOneShot>,
+        bump: u8,
+        nonce: u64,
+        key: Pubkey,
+        wallet_index: u64,
+        instruction: TXInstruction,
+        valid_from: i64,
+        valid_until: i64,
+    ) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+        require!(valid_from < valid_until, InvalidOneShotWindow);
+
+        let one_shot = &mut ctx.accounts.one_shot;
+        one_shot.bump = bump;
+        one_shot.smart_wallet = ctx.accounts.smart_wallet.key();
+        one_shot.key = key;
+        one_shot.nonce = nonce;
+        one_shot.wallet_index = wallet_index;
+        one_shot.instruction = instruction;
+        one_shot.valid_from = valid_from;
+        one_shot.valid_until = valid_until;
+        one_shot.executed_at = -1;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OneShotEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            one_shot: ctx.accounts.one_shot.key(),
+            key,
+            created: true,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Executes the instruction of a [OneShot], signed by its derived wallet. Only
+    /// [OneShot::key] may do this, once, within the window. The instruction's accounts
+    /// and the [ProtocolConfig] are passed as remaining accounts.
+    pub fn execute_one_shot<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteOneShot<'info>>,
+        wallet_bump: u8,
+    ) -> ProgramResult {
+        let now = Clock::get()?.unix_timestamp;
+        let one_shot = &ctx.accounts.one_shot;
+        one_shot.check_executable(now)?;
+        ProtocolConfig::load(ctx.remaining_accounts)?.check_invoke(
+            &ctx.accounts.smart_wallet,
+            std::slice::from_ref(&one_shot.instruction),
+        )?;
+        let smart_wallet = ctx.accounts.smart_wallet.key();
+        let wallet_seeds: &[&[&[u8]]] = &[&[
+            DERIVED_WALLET_SEED,
+            &smart_wallet.to_bytes(),
+            &one_shot.wallet_index.to_le_bytes(),
+            &[wallet_bump],
+        ]];
+        solana_program::program::invoke_signed(
+            &(&one_shot.instruction).into(),
+            ctx.remaining_accounts,
+            wallet_seeds,
+        )?;
+        ctx.accounts.smart_wallet.reload()?;
+
+        let program_id = one_shot.instruction.program_id;
+        ctx.accounts.one_shot.executed_at = now;
+        ctx.accounts.smart_wallet.record_execution(now)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OneShotExecuteEvent {
+            smart_wallet,
+            event_seq,
+            one_shot: ctx.accounts.one_shot.key(),
+            key: ctx.accounts.key.key(),
+            program_id,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Revokes a [OneShot], or reclaims the rent of an executed one.
+    /// Requires [SmartWallet::threshold] owners to sign, passed as remaining accounts.
+    pub fn revoke_one_shot(ctx: Context<RevokeOneShot>) -> ProgramResult {
+        ctx.accounts.smart_wallet.check_owner_signers(ctx.remaining_accounts)?;
+
+        let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+        emit!(OneShotEvent {
+            smart_wallet: ctx.accounts.smart_wallet.key(),
+            event_seq,
+            one_shot: ctx.accounts.one_shot.key(),
+            key: ctx.accounts.one_shot.key,
+            created: false,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Executes ixs arg
+    #[access_control(ctx.accounts.validate())]
+    pub fn execute_ixs<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteInstructions<'info>>,
+        index: u64,
+        bump: u8,
+        ixs: Vec<TXInstruction>,
+    ) -> ProgramResult {
+        let smart_wallet = &ctx.accounts.smart_wallet;
+        let wallet_seeds: &[&[&[u8]]] = &[&[
+            DERIVED_WALLET_SEED,
+            &smart_wallet.key().to_bytes(),
+            &index.to_le_bytes(),
+            &[bump],
+        ]];
+
+        let config = ProtocolConfig::load(ctx.remaining_accounts)?;
+        require!(ixs.len() <= config.max_instructions as usize, TooManyInstructions);
+        config.check_invoke(smart_wallet, &ixs)?;
+
+        for ix in ixs.iter() {
+            solana_program::program::invoke_signed(&(ix).into(), ctx.remaining_accounts, wallet_seeds)?;
+        }
+        ctx.accounts.smart_wallet.reload()?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.smart_wallet.record_execution(now)?;
+        let smart_wallet = &ctx.accounts.smart_wallet;
+
+        let executor = ctx.accounts.authority_a.to_account_info();
+        let amount = smart_wallet.reimburse_executor(
+            smart_wallet.key(),
+            &executor,
+            ctx.remaining_accounts,
+        )?;
+        if amount > 0 {
+            let event_seq = ctx.accounts.smart_wallet.next_event_seq()?;
+            emit!(ExecutorReimbursedEvent {
+                smart_wallet: ctx.accounts.smart_wallet.key(),
+                event_seq,
+                executor: executor.key(),
+                amount,
+                timestamp: now
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Proposes `transaction` for the [smart_wallet] `create_*` instructions, emitting its
+/// [TransactionCreateEvent] and [TransactionEstimateEvent].
+///
+/// The [Transaction] takes the next index unless `reserved_index`, redeemed from a
+/// [TransactionReservation] whose proposer was rate limited when reserving it. A
+/// committed [Transaction] stores only `instructions_hash`, and its instructions are
+/// checked against the [ProtocolConfig] on execution instead. A
+/// [TransactionState::Draft] [Transaction] is left for its proposer to finalize; any
+/// other is activated with `eta`.
+#[allow(clippy::too_many_arguments)]
+fn init_transaction<'info>(
+    transaction: &mut Account<'info, Transaction>,
+    smart_wallet: &mut Account<'info, SmartWallet>,
+    proposer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    remaining_accounts: &[AccountInfo],
+    reserved_index: Option<u64>,
+    bump: u8,
+    instructions: Vec<TXInstruction>,
+    instructions_hash: Option<[u8; 32]>,
+    eta: i64,
+    state: TransactionState,
+) -> ProgramResult {
+    let config = ProtocolConfig::load(remaining_accounts)?;
+    if instructions_hash.is_none() {
+        require!(
+            instructions.len() <= config.max_instructions as usize,
+            TooManyInstructions
+        );
+        Transaction::check_instruction_limits(&instructions)?;
+    }
+    let wallet = smart_wallet.key();
+    let proposer_key = proposer.key();
+    let owner_index = smart_wallet.check_proposer(wallet, proposer_key, remaining_accounts)?;
+    let now = Clock::get()?.unix_timestamp;
+    let index = match reserved_index {
+        Some(index) => index,
+        None => {
+            smart_wallet.check_rate_limit(wallet, proposer_key, remaining_accounts, now)?;
+            smart_wallet.next_transaction_index()?
+        }
+    };
+    transaction.init_draft(
+        smart_wallet,
+        index,
+        proposer_key,
+        owner_index,
+        bump,
+        instructions.clone(),
+    )?;
+    transaction.instructions_hash = instructions_hash;
+    if state != TransactionState::Draft {
+        transaction.activate(smart_wallet, eta, now)?;
+        if owner_index.is_some() {
+            smart_wallet.record_approval(now);
+        }
+    }
+    let info = transaction.to_account_info();
+    transaction.lock_bond(
+        smart_wallet,
+        info,
+        proposer.to_account_info(),
+        system_program.to_account_info(),
+    )?;
+
+    let event_seq = smart_wallet.next_event_seq()?;
+    emit!(TransactionCreateEvent {
+        smart_wallet: wallet,
+        event_seq,
+        transaction: transaction.key(),
+        proposer: proposer_key,
+        instructions,
+        instructions_hash,
+        eta: transaction.eta,
+        notifications: OwnerNotification::collect(wallet, remaining_accounts),
+        timestamp: now
+    });
+
+    let event_seq = smart_wallet.next_event_seq()?;
+    emit!(TransactionEstimateEvent {
+        smart_wallet: wallet,
+        event_seq,
+        transaction: transaction.key(),
+        estimate: TransactionEstimate::new(&transaction.instructions),
+        timestamp: now,
+    });
+    Ok(())
+}
+
+/// Accounts for [smart_wallet::init_protocol_config].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct InitProtocolConfig<'info> {
+    /// The [ProtocolConfig] to create.
+    #[account(
+        init,
+        seeds = [PROTOCOL_CONFIG_SEED],
+        bump,
+        payer = authority,
+        space = ProtocolConfig::space(),
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// Program data of this program. Checked in the handler.
+    pub program_data: Account<'info, ProgramData>,
+    /// The upgrade authority of this program.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for the [ProtocolConfig] instructions signed by [ProtocolConfig::authority].
+#[derive(Accounts)]
+pub struct SetProtocolConfig<'info> {
+    /// The [ProtocolConfig].
+    #[account(mut, has_one = authority)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// [ProtocolConfig::authority].
+    pub authority: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::set_paused].
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// The [ProtocolConfig].
+    #[account(mut, has_one = security_council)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// [ProtocolConfig::security_council].
+    pub security_council: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::govern_protocol_config].
+#[derive(Accounts)]
+pub struct GovernProtocolConfig<'info> {
+    /// The [ProtocolConfig].
+    #[account(mut)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// The [SmartWallet] governing the update, signing as its PDA. Checked in the handler.
+    #[account(signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::create_smart_wallet].
+#[derive(Accounts)]
+#[instruction(bump: u8, max_owners: u8)]
+pub struct CreateSmartWallet<'info> {
+    /// Base key of the SmartWallet.
+    pub base: Signer<'info>,
+
+    /// The [SmartWallet] to create.
+    #[account(
+        init,
+        seeds = [
+            SMART_WALLET_SEED,
+            base.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = SmartWallet::space(max_owners),
+    )]
+    pub smart_wallet: Account<'info, SmartWallet>,
+
+    /// Payer to create the smart_wallet.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::import_smart_wallet].
+#[derive(Accounts)]
+#[instruction(bump: u8, max_owners: u8)]
+pub struct ImportSmartWallet<'info> {
+    /// The upstream Goki smart wallet. Checked in the handler.
+    pub legacy_smart_wallet: UncheckedAccount<'info>,
+    /// The [SmartWallet] to create.
+    #[account(
+        init,
+        seeds = [
+            SMART_WALLET_SEED,
+            LegacySmartWallet::load(&legacy_smart_wallet)?.base.to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = SmartWallet::space(max_owners),
+    )]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::import_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct ImportTransaction<'info> {
+    /// The [SmartWallet] imported from the upstream wallet.
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The upstream Goki smart wallet. Checked in the handler.
+    pub legacy_smart_wallet: UncheckedAccount<'info>,
+    /// The pending upstream Goki transaction. Checked in the handler.
+    pub legacy_transaction: UncheckedAccount<'info>,
+    /// The [Transaction] to create, derived from the upstream one so it is imported once.
+    #[account(
+        init,
+        seeds = [
+            TRANSACTION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            legacy_transaction.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Transaction::space(LegacyTransaction::load(&legacy_transaction)?.instructions),
+    )]
+    pub transaction: Account<'info, Transaction>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::set_executor_reimbursement].
+#[derive(Accounts)]
+pub struct SetExecutorReimbursement<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_executor_allowlist].
+#[derive(Accounts)]
+pub struct SetExecutorAllowlist<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_audited_programs_only].
+#[derive(Accounts)]
+pub struct SetAuditedProgramsOnly<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_grace_period].
+#[derive(Accounts)]
+pub struct SetGracePeriod<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_proposer_gate].
+#[derive(Accounts)]
+pub struct SetProposerGate<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_proposal_bond].
+#[derive(Accounts)]
+pub struct SetProposalBond<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_proposal_rate_limit].
+#[derive(Accounts)]
+pub struct SetProposalRateLimit<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_execution_receipts].
+#[derive(Accounts)]
+pub struct SetExecutionReceipts<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::set_veto_authority].
+#[derive(Accounts)]
+pub struct SetVetoAuthority<'info> {
+    /// The [SmartWallet], signing as its PDA.
+    #[account(mut, signer)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::init_proposer_activity].
+#[derive(Accounts)]
+#[instruction(bump: u8, proposer: Pubkey)]
+pub struct InitProposerActivity<'info> {
+    /// The [SmartWallet].
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [ProposerActivity] to create.
+    #[account(
+        init,
+        seeds = [
+            PROPOSER_ACTIVITY_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            proposer.to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = ProposerActivity::space(),
+    )]
+    pub proposer_activity: Account<'info, ProposerActivity>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::resize_smart_wallet].
+#[derive(Accounts)]
+pub struct ResizeSmartWallet<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// Payer of the additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::suspend_owner].
+#[derive(Accounts)]
+pub struct SuspendOwner<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// An active owner of the [SmartWallet].
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::reinstate_owner].
+#[derive(Accounts)]
+pub struct ReinstateOwner<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::create_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8, instructions: Vec<TXInstruction>)]
+pub struct CreateTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to create.
+    #[account(
+        init,
+        seeds = [
+            TRANSACTION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            smart_wallet.num_transactions.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Transaction::space(instructions.clone()),
+    )]
+    pub transaction: Account<'info, Transaction>,
+    /// An owner, an [AllowedProposer], or a holder meeting the [ProposerGate].
+    /// Checked in the handler. Pays the [SmartWallet::proposal_bond].
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// Payer to create the [Transaction].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::create_draft_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8, instructions: Vec<TXInstruction>)]
+pub struct CreateDraftTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to create.
+    #[account(
+        init,
+        seeds = [
+            TRANSACTION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            smart_wallet.num_transactions.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Transaction::space(instructions.clone()),
+    )]
+    pub transaction: Account<'info, Transaction>,
+    /// An owner, an [AllowedProposer], or a holder meeting the [ProposerGate].
+    /// Checked in the handler. Pays the [SmartWallet::proposal_bond].
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// Payer to create the [Transaction].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::create_proposer_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8, proposer_nonce: u64, instructions: Vec<TXInstruction>)]
+pub struct CreateProposerTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to create.
+    #[account(
+        init,
+        seeds = [
+            TRANSACTION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            proposer.key().to_bytes().as_ref(),
+            proposer_nonce.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Transaction::space(instructions.clone()),
+    )]
+    pub transaction: Account<'info, Transaction>,
+    /// An owner, an [AllowedProposer], or a holder meeting the [ProposerGate].
+    /// Checked in the handler. Pays the [SmartWallet::proposal_bond].
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// Payer to create the [Transaction].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::register_owner_notification].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct RegisterOwnerNotification<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OwnerNotification] to create.
+    #[account(
+        init,
+        seeds = [
+            OWNER_NOTIFICATION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = owner,
+        space = OwnerNotification::space(),
+    )]
+    pub notification: Account<'info, OwnerNotification>,
+    /// An owner of the [SmartWallet], paying for the [OwnerNotification].
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::update_owner_notification].
+#[derive(Accounts)]
+pub struct UpdateOwnerNotification<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OwnerNotification].
+    #[account(mut, has_one = smart_wallet, has_one = owner)]
+    pub notification: Account<'info, OwnerNotification>,
+    /// The owner who registered the [OwnerNotification].
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::remove_owner_notification].
+#[derive(Accounts)]
+pub struct RemoveOwnerNotification<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OwnerNotification] to close.
+    #[account(mut, has_one = smart_wallet, has_one = owner, close = owner)]
+    pub notification: Account<'info, OwnerNotification>,
+    /// The owner who registered the [OwnerNotification], receiving its rent.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::init_owner_heartbeat].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct InitOwnerHeartbeat<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OwnerHeartbeat] to create.
+    #[account(
+        init,
+        seeds = [
+            OWNER_HEARTBEAT_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = owner,
+        space = OwnerHeartbeat::space(),
+    )]
+    pub heartbeat: Account<'info, OwnerHeartbeat>,
+    /// An owner of the [SmartWallet], paying for the [OwnerHeartbeat].
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::owner_heartbeat].
+#[derive(Accounts)]
+pub struct OwnerHeartbeatUpdate<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OwnerHeartbeat].
+    #[account(mut, has_one = smart_wallet, has_one = owner)]
+    pub heartbeat: Account<'info, OwnerHeartbeat>,
+    /// The owner.
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::add_allowed_proposer].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct AddAllowedProposer<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [AllowedProposer] to create.
+    #[account(
+        init,
+        seeds = [
+            ALLOWED_PROPOSER_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            proposer.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = AllowedProposer::space(),
+    )]
+    pub allowed_proposer: Account<'info, AllowedProposer>,
+    /// The proposer to allow.
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::remove_allowed_proposer].
+#[derive(Accounts)]
+pub struct RemoveAllowedProposer<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [AllowedProposer] to close.
+    #[account(mut, has_one = smart_wallet, close = receiver)]
+    pub allowed_proposer: Account<'info, AllowedProposer>,
+    /// Receives the rent of the [AllowedProposer].
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::create_committed_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8, proposer_nonce: u64)]
+pub struct CreateCommittedTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to create.
+    #[account(
+        init,
+        seeds = [
+            TRANSACTION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            proposer.key().to_bytes().as_ref(),
+            proposer_nonce.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Transaction::space(vec![]),
+    )]
+    pub transaction: Account<'info, Transaction>,
+    /// An owner, an [AllowedProposer], or a holder meeting the [ProposerGate].
+    /// Checked in the handler. Pays the [SmartWallet::proposal_bond].
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// Payer to create the [Transaction].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::reserve_transaction_index].
+#[derive(Accounts)]
+pub struct ReserveTransactionIndex<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [TransactionReservation] to create.
+    #[account(
+        init,
+        seeds = [
+            TRANSACTION_RESERVATION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            smart_wallet.num_transactions.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = TransactionReservation::space(),
+    )]
+    pub reservation: Account<'info, TransactionReservation>,
+    /// An owner, an [AllowedProposer], or a holder meeting the [ProposerGate].
+    /// Checked in the handler. Pays the [SmartWallet::proposal_bond].
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// Payer to create the [TransactionReservation].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::create_reserved_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8, instructions: Vec<TXInstruction>)]
+pub struct CreateReservedTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [TransactionReservation] being redeemed.
+    #[account(
+        mut,
+        has_one = smart_wallet,
+        has_one = proposer,
+        close = payer,
+    )]
+    pub reservation: Account<'info, TransactionReservation>,
+    /// The [Transaction] to create, at the reserved index.
+    #[account(
+        init,
+        seeds = [
+            TRANSACTION_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            reservation.index.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Transaction::space(instructions.clone()),
+    )]
+    pub transaction: Account<'info, Transaction>,
+    /// The owner who reserved the index. Pays the [SmartWallet::proposal_bond].
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// Payer to create the [Transaction]. Receives the rent of the reservation.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::execute_transaction].
+#[derive(Accounts)]
+pub struct ExecuteTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to execute.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// The executor, receiving any [ExecutorReimbursement]. Checked in the handler.
+    #[account(mut)]
+    pub executor: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::check_transaction].
+#[derive(Accounts)]
+pub struct CheckTransaction<'info> {
+    /// The [SmartWallet].
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to check.
+    #[account(has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+}
+
+/// Accounts for [smart_wallet::attest_configuration].
+#[derive(Accounts)]
+pub struct AttestConfiguration<'info> {
+    /// The [SmartWallet] to attest.
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+}
+
+/// Accounts for [smart_wallet::mark_expired].
+#[derive(Accounts)]
+pub struct MarkExpired<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to mark.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// The treasury receiving the slashed bond: the derived wallet at index 0.
+    #[account(
+        mut,
+        seeds = [
+            DERIVED_WALLET_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            0u64.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::reap_transaction].
+#[derive(Accounts)]
+pub struct ReapTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to reap.
+    #[account(mut, has_one = smart_wallet, has_one = proposer, close = proposer)]
+    pub transaction: Account<'info, Transaction>,
+    /// The treasury receiving the slashed bond: the derived wallet at index 0.
+    #[account(
+        mut,
+        seeds = [
+            DERIVED_WALLET_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            0u64.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    /// The proposer of the [Transaction], refunded its rent.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::release_proposal_bond].
+#[derive(Accounts)]
+pub struct ReleaseProposalBond<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The executed [Transaction].
+    #[account(mut, has_one = smart_wallet, has_one = proposer)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction], refunded the bond.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::close_transaction].
+#[derive(Accounts)]
+pub struct CloseTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to close.
+    #[account(mut, has_one = smart_wallet, has_one = proposer, close = receiver)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction] or the [SmartWallet]. Checked in the handler.
+    pub authority: Signer<'info>,
+    /// The proposer of the [Transaction], refunded any remaining bond.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+    /// Receives the rent of the [Transaction].
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::approve].
+#[derive(Accounts)]
+pub struct Approve<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to approve.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// One of the smart_wallet owners. Checked in the handler.
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::unapprove].
+#[derive(Accounts)]
+pub struct Unapprove<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to unapprove.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// One of the smart_wallet owners, paying for the nonce. Checked in the handler.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::approve_with_memo].
+#[derive(Accounts)]
+pub struct ApproveWithMemo<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to approve.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// One of the smart_wallet owners, paying for the memo. Checked in the handler.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::approve_many].
+#[derive(Accounts)]
+pub struct ApproveMany<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// One of the smart_wallet owners. Checked in the handler.
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::approve_with_message].
+#[derive(Accounts)]
+pub struct ApproveWithMessage<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to approve.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// The owner who signed the approval message.
+    pub owner: UncheckedAccount<'info>,
+    /// The [solana_program::sysvar::instructions] sysvar.
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::approve_with_signature].
+#[derive(Accounts)]
+pub struct ApproveWithSignature<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to approve.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// The owner who signed the approval payload.
+    pub owner: UncheckedAccount<'info>,
+    /// The [solana_program::sysvar::instructions] sysvar.
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::approve_aggregate].
+#[derive(Accounts)]
+pub struct ApproveAggregate<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to approve.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// The [solana_program::sysvar::instructions] sysvar.
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::approve_instructions].
+#[derive(Accounts)]
+pub struct ApproveInstructions<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to approve.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// One of the smart_wallet owners, paying for any reallocation.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::declare_writable_scope].
+#[derive(Accounts)]
+pub struct DeclareWritableScope<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction].
+    #[account(mut, has_one = smart_wallet, has_one = proposer)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction], paying for the reallocation.
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::finalize_transaction].
+#[derive(Accounts)]
+pub struct FinalizeTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to finalize.
+    #[account(mut, has_one = smart_wallet, has_one = proposer)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction].
+    pub proposer: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::declare_dependencies].
+#[derive(Accounts)]
+pub struct DeclareDependencies<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction].
+    #[account(mut, has_one = smart_wallet, has_one = proposer)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction], paying for the reallocation.
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::set_proposer_executes].
+#[derive(Accounts)]
+pub struct SetProposerExecutes<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction].
+    #[account(mut, has_one = smart_wallet, has_one = proposer)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction].
+    pub proposer: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::cancel_transaction].
+#[derive(Accounts)]
+pub struct CancelTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to cancel.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction] or the [SmartWallet]. Checked in the handler.
+    pub authority: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::veto_transaction].
+#[derive(Accounts)]
+pub struct VetoTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction] to veto.
+    #[account(mut, has_one = smart_wallet)]
+    pub transaction: Account<'info, Transaction>,
+    /// The [SmartWallet::veto_authority].
+    pub veto_authority: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::append_transaction].
+#[derive(Accounts)]
+pub struct AppendTransaction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Transaction].
+    #[account(mut, has_one = smart_wallet, has_one = proposer)]
+    pub transaction: Account<'info, Transaction>,
+    /// The proposer of the [Transaction], paying for the reallocation.
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet:append_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8, abs_index: u64, stake_data: StakeData)]
+pub struct CreateStake<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Ticket].
+    #[account(
+        init,
+        seeds = [
+            STAKE_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            abs_index.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Stake::space(
+            stake_data.protected_gids.len(),
+            stake_data.protected_gid_ranges.len()
+        ),
+    )]
+    pub stake: Account<'info, Stake>,
+    /// Mint of the reward token.
+    pub reward_mint: Account<'info, Mint>,
+    /// Token account holding the rewards. Must be owned by the [Stake].
+    #[account(
+        constraint = reward_vault.mint == reward_mint.key(),
+        constraint = reward_vault.owner == stake.key(),
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// Payer to create the [Transaction].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// An owner of the [SmartWallet]. Checked in the handler.
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+/// Accounts for [smart_wallet::extend_stake].
+#[derive(Accounts)]
+pub struct ExtendStake<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] to extend.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
+/// Accounts for [smart_wallet::set_stake_metadata].
+#[derive(Accounts)]
+pub struct SetStakeMetadata<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] to describe.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
+/// Accounts for [smart_wallet::set_reward_burn].
+#[derive(Accounts)]
+pub struct SetRewardBurn<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
+/// Accounts for [smart_wallet::set_reward_emission].
+#[derive(Accounts)]
+pub struct SetRewardEmission<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
+
+/// Accounts for [smart_wallet::set_claim_window].
+#[derive(Accounts)]
+pub struct SetClaimWindow<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::set_reward_rounding].
+#[derive(Accounts)]
+pub struct SetRewardRounding<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::set_owner_ata_claims].
+#[derive(Accounts)]
+pub struct SetOwnerAtaClaims<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::set_stake_viewers].
+#[derive(Accounts)]
+pub struct SetStakeViewers<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::seal_stake_params].
+#[derive(Accounts)]
+pub struct SealStakeParams<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::reveal_stake_params].
+#[derive(Accounts)]
+pub struct RevealStakeParams<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
+
+/// Accounts for [smart_wallet::add_stake_sponsor].
+#[derive(Accounts)]
+pub struct AddStakeSponsor<'info> {
+    /// The [SmartWallet] of the [Stake].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [SmartWallet] becoming a sponsor.
+    pub sponsor: Account<'info, SmartWallet>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::remove_stake_sponsor].
+#[derive(Accounts)]
+pub struct RemoveStakeSponsor<'info> {
+    /// The [SmartWallet] of the [Stake], or the sponsor.
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut)]
+    pub stake: Account<'info, Stake>,
+}
+
+/// Accounts for [smart_wallet::fund_stake].
+#[derive(Accounts)]
+pub struct FundStake<'info> {
+    /// The funding [SmartWallet]: the [Stake]'s or a sponsor.
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(
+        mut,
+        has_one = reward_vault,
+        constraint = stake.is_admin(smart_wallet.key())
+    )]
+    pub stake: Account<'info, Stake>,
+    /// The [Stake::reward_vault].
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// Token account the rewards are transferred from.
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+    /// Owner of the source account, e.g. an account derived from the [SmartWallet].
+    /// Only transfers by the [SmartWallet] or its derived wallets credit a sponsor.
+    pub authority: Signer<'info>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for [smart_wallet::set_protected_gids].
+#[derive(Accounts)]
+pub struct SetProtectedGids<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+/// Accounts for [smart_wallet::remap_gid].
+#[derive(Accounts)]
+pub struct RemapGid<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] whose gids are remapped.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
+
+/// Accounts for [smart_wallet::create_bonus_budget].
+#[derive(Accounts)]
+pub struct CreateBonusBudget<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Token account of the reward mint, owned by the [Stake], holding the budget.
+    #[account(
+        constraint = bonus_vault.mint == stake.reward_mint,
+        constraint = bonus_vault.owner == stake.key(),
+        constraint = bonus_vault.key() != stake.reward_vault,
+    )]
+    pub bonus_vault: Account<'info, TokenAccount>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::fund_bonus_budget].
+#[derive(Accounts)]
+pub struct FundBonusBudget<'info> {
+    /// The funding [SmartWallet]: the [Stake]'s or a sponsor.
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, constraint = stake.is_admin(smart_wallet.key()))]
+    pub stake: Account<'info, Stake>,
+    /// The vault of the [Stake::bonus_budget]. Checked in the handler.
+    #[account(mut)]
+    pub bonus_vault: Account<'info, TokenAccount>,
+    /// Token account the tokens are transferred from.
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+    /// Owner of the source account, e.g. an account derived from the [SmartWallet].
+    pub authority: Signer<'info>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for [smart_wallet::create_quest].
+#[derive(Accounts)]
+#[instruction(bump: u8, index: u64)]
+pub struct CreateQuest<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] paying the bonus.
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [Quest] to create.
+    #[account(
+        init,
+        seeds = [
+            QUEST_SEED,
+            stake.key().to_bytes().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Quest::space(),
+    )]
+    pub quest: Account<'info, Quest>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::claim_quest].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct ClaimQuest<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] paying the bonus.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [Quest] being claimed.
+    #[account(mut, has_one = stake)]
+    pub quest: Account<'info, Quest>,
+    /// Record preventing the [Rollup] from claiming twice.
+    #[account(
+        init,
+        seeds = [
+            QUEST_CLAIM_SEED,
+            quest.key().to_bytes().as_ref(),
+            rollup.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = QuestClaim::space(),
+    )]
+    pub quest_claim: Account<'info, QuestClaim>,
+    #[account(
+        seeds = [
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref(),
+            rollup.gid.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub rollup: Account<'info, Rollup>,
+    /// A [Ticket] of the owner in the [Rollup]'s gid. Checked in the handler.
+    pub ticket: Account<'info, Ticket>,
+    /// Token account the bonus is paid from: the vault of the [Stake::bonus_budget],
+    /// or the [Stake::reward_vault] if there is none. Checked in the handler.
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// Token account of the owner receiving the bonus.
+    #[account(
+        mut,
+        constraint = owner_tokens.mint == stake.reward_mint,
+        constraint = owner_tokens.owner == owner.key(),
+    )]
+    pub owner_tokens: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The owner of the [Rollup].
+    pub owner: Signer<'info>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+/// Accounts for [smart_wallet::create_milestone].
+#[derive(Accounts)]
+#[instruction(bump: u8, index: u64)]
+pub struct CreateMilestone<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] whose claims are tracked.
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [Milestone] to create.
+    #[account(
+        init,
+        seeds = [
+            MILESTONE_SEED,
+            stake.key().to_bytes().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Milestone::space(),
+    )]
+    pub milestone: Account<'info, Milestone>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::claim_milestone].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct ClaimMilestone<'info> {
+    /// The [SmartWallet], authority of the badge.
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [Milestone] being claimed.
+    #[account(mut, has_one = stake)]
+    pub milestone: Account<'info, Milestone>,
+    /// Record preventing the owner from claiming twice.
+    #[account(
+        init,
+        seeds = [
+            MILESTONE_CLAIM_SEED,
+            milestone.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = owner,
+        space = MilestoneClaim::space(),
+    )]
+    pub milestone_claim: Account<'info, MilestoneClaim>,
+    /// Rewards claimed by the owner.
+    #[account(has_one = stake, has_one = owner)]
+    pub owner_rewards: Account<'info, OwnerRewards>,
+    /// Mint of the badge.
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = smart_wallet,
+    )]
+    pub mint: Account<'info, Mint>,
+    /// Token account of the owner receiving the badge.
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_tokens: Account<'info, TokenAccount>,
+    /// Metadata account of the badge. Checked in the handler.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The Token Metadata program.
+    #[account(address = token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+    /// The [AssociatedToken] program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+    /// The [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Accounts for [smart_wallet:append_transaction].
+#[derive(Accounts)]
+#[instruction(bump: u8, gid: u16)]
+pub struct RollupEntityInit<'info> {
+    /// Payer to create the [Transaction].
+    // pub mint: UncheckedAccount<'info>,
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] the [Rollup] accrues rewards in.
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [Ticket].
+    #[account(
+        init,
+        seeds = [
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref(),
+            gid.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Rollup::space(),
+    )]
+    pub rollup: Account<'info, Rollup>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The mint owner. Checked in the handler.
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+/// Accounts for [smart_wallet::migrate_rollup].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct MigrateRollup<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] the [Rollup] accrues rewards in.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The legacy [Rollup], derived per [SmartWallet]. Checked in the handler.
+    #[account(mut)]
+    pub legacy_rollup: UncheckedAccount<'info>,
+    /// The [Rollup] to create.
+    #[account(
+        init,
+        seeds = [
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref(),
+            LegacyRollup::load(&legacy_rollup)?.gid.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = Rollup::space(),
+    )]
+    pub rollup: Account<'info, Rollup>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The owner of the [Rollup].
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+/// Accounts for [smart_wallet::init_owner_rewards].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct InitOwnerRewards<'info> {
+    /// The [Stake].
+    pub stake: Account<'info, Stake>,
+    /// The [OwnerRewards].
+    #[account(
+        init,
+        seeds = [
+            OWNER_REWARDS_SEED,
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = OwnerRewards::space(),
+    )]
+    pub owner_rewards: Account<'info, OwnerRewards>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The owner claiming rewards.
+    pub owner: UncheckedAccount<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+/// Accounts for [smart_wallet::authorize_crank].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct AuthorizeCrank<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [CrankPermission] to create.
+    #[account(
+        init,
+        seeds = [
+            CRANK_PERMISSION_SEED,
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = owner,
+        space = CrankPermission::space(),
+    )]
+    pub crank_permission: Account<'info, CrankPermission>,
+    /// Token account receiving the claimed rewards.
+    #[account(constraint = destination.mint == stake.reward_mint)]
+    pub destination: Account<'info, TokenAccount>,
+    /// The owner authorizing the crank.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::revoke_crank].
+#[derive(Accounts)]
+pub struct RevokeCrank<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [CrankPermission] to close.
+    #[account(mut, has_one = stake, has_one = owner, close = owner)]
+    pub crank_permission: Account<'info, CrankPermission>,
+    /// The owner revoking the crank.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::crank_claim].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct CrankClaim<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    #[account(
+        mut,
+        seeds = [
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref(),
+            rollup.gid.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub rollup: Account<'info, Rollup>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet, has_one = reward_vault)]
+    pub stake: Account<'info, Stake>,
+    /// Rewards claimed by the owner so far.
+    #[account(mut, has_one = stake, has_one = owner)]
+    pub owner_rewards: Account<'info, OwnerRewards>,
+    /// The owner's permission for the crank.
+    #[account(mut, has_one = stake, has_one = owner, has_one = crank, has_one = destination)]
+    pub crank_permission: Account<'info, CrankPermission>,
+    /// Token account holding the rewards.
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// Token account the owner chose to receive the rewards.
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    /// The owner of the [Rollup].
+    pub owner: UncheckedAccount<'info>,
+    /// The crank claiming on behalf of the owner.
+    pub crank: Signer<'info>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for [smart_wallet::add_allowed_mint].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct AddAllowedMint<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [AllowedMint] to create.
+    #[account(
+        init,
+        seeds = [
+            ALLOWED_MINT_SEED,
+            stake.key().to_bytes().as_ref(),
+            mint.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = AllowedMint::space(),
+    )]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    /// The mint to allow.
+    pub mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+/// Accounts for [smart_wallet::remove_allowed_mint].
+#[derive(Accounts)]
+pub struct RemoveAllowedMint<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [AllowedMint] to close.
+    #[account(mut, has_one = stake, close = receiver)]
+    pub allowed_mint: Account<'info, AllowedMint>,
+    /// Receives the rent of the [AllowedMint].
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::set_participant_allowlist].
+#[derive(Accounts)]
+pub struct SetParticipantAllowlist<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::add_allowed_participant].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct AddAllowedParticipant<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [AllowedParticipant] to create.
+    #[account(
+        init,
+        seeds = [
+            ALLOWED_PARTICIPANT_SEED,
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = AllowedParticipant::space(),
+    )]
+    pub allowed_participant: Account<'info, AllowedParticipant>,
+    /// The owner to allow.
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::prove_participant].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct ProveParticipant<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [AllowedParticipant] to create.
+    #[account(
+        init,
+        seeds = [
+            ALLOWED_PARTICIPANT_SEED,
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = owner,
+        space = AllowedParticipant::space(),
+    )]
+    pub allowed_participant: Account<'info, AllowedParticipant>,
+    /// The owner proving their membership, who pays the rent.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::remove_allowed_participant].
+#[derive(Accounts)]
+pub struct RemoveAllowedParticipant<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [AllowedParticipant] to close.
+    #[account(mut, has_one = stake, close = receiver)]
+    pub allowed_participant: Account<'info, AllowedParticipant>,
+    /// Receives the rent of the [AllowedParticipant].
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::add_fee_exemption].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct AddFeeExemption<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [FeeExemption] to create.
+    #[account(
+        init,
+        seeds = [
+            FEE_EXEMPTION_SEED,
+            stake.key().to_bytes().as_ref(),
+            account.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = FeeExemption::space(),
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    /// The account to exempt.
+    pub account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::remove_fee_exemption].
+#[derive(Accounts)]
+pub struct RemoveFeeExemption<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [FeeExemption] to close.
+    #[account(mut, has_one = stake, close = receiver)]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    /// Receives the rent of the [FeeExemption].
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+/// Accounts for [smart_wallet::set_yield_venue].
+#[derive(Accounts)]
+pub struct SetYieldVenue<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
 }
 
-/// Accounts for [smart_wallet::create_smart_wallet].
+/// Accounts for [smart_wallet::deposit_reward_vault] and
+/// [smart_wallet::recall_reward_vault].
 #[derive(Accounts)]
-#[instruction(bump: u8, max_owners: u8)]
-pub struct CreateSmartWallet<'info> {
-    /// Base key of the SmartWallet.
-    pub base: Signer<'info>,
+pub struct RouteRewardVault<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet, has_one = reward_vault)]
+    pub stake: Account<'info, Stake>,
+    /// The reward vault of the [Stake].
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// The [YieldVenue] program. Checked in the handler.
+    pub venue_program: UncheckedAccount<'info>,
+}
 
-    /// The [SmartWallet] to create.
+/// Accounts for [smart_wallet::create_rent_pool].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct CreateRentPool<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [RentPool] to create.
     #[account(
         init,
         seeds = [
-            b"GokiSmartWallet".as_ref(),
-            base.key().to_bytes().as_ref()
+            RENT_POOL_SEED,
+            smart_wallet.key().to_bytes().as_ref()
         ],
         bump,
         payer = payer,
-        space = SmartWallet::space(max_owners),
+        space = RentPool::space(),
     )]
-    pub smart_wallet: Account<'info, SmartWallet>,
-
-    /// Payer to create the smart_wallet.
+    pub rent_pool: Account<'info, RentPool>,
     #[account(mut)]
     pub payer: Signer<'info>,
-
     /// The [System] program.
     pub system_program: Program<'info, System>,
 }
 
-/// Accounts for [smart_wallet:append_transaction].
+/// Accounts for [smart_wallet::sponsor_rent].
 #[derive(Accounts)]
-#[instruction(bump: u8, instructions: TXInstruction)]
-pub struct AppendTransaction<'info> {
+#[instruction(gid: u16)]
+pub struct SponsorRent<'info> {
     /// The [SmartWallet].
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
-    /// The [Transaction].
-    #[account(mut)]
-    pub transaction: Account<'info, Transaction>,
-    /// One of the smart_wallet owners. Checked in the handler.
+    /// The [Stake] the mint is to be registered in.
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [RentPool] paying the rent.
+    #[account(mut, has_one = smart_wallet)]
+    pub rent_pool: Account<'info, RentPool>,
+    /// The [Ticket] of the mint, not yet created.
+    #[account(
+        mut,
+        seeds = [
+            system_program.key().to_bytes().as_ref(),
+            smart_wallet.key().to_bytes().as_ref(),
+            mint.key().to_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub ticket: UncheckedAccount<'info>,
+    /// The [Rollup] of the owner's gid, funded only if not yet created.
+    #[account(
+        mut,
+        seeds = [
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref(),
+            gid.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub rollup: UncheckedAccount<'info>,
+    /// The holder of the mint.
     pub owner: Signer<'info>,
+    /// Token account of the owner holding the mint.
+    #[account(
+        constraint = owner_tokens.owner == owner.key(),
+        constraint = owner_tokens.mint == mint.key(),
+        constraint = owner_tokens.amount == 1,
+    )]
+    pub owner_tokens: Account<'info, TokenAccount>,
+    /// The NFT mint. Checked in the handler.
+    pub mint: Account<'info, Mint>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
 }
 
-/// Accounts for [smart_wallet:append_transaction].
+/// Accounts for [smart_wallet::close_ticket].
 #[derive(Accounts)]
-#[instruction(bump: u8, abs_index: u64, stake_data: StakeData)]
-pub struct CreateStake<'info> {
+pub struct CloseTicket<'info> {
     /// The [SmartWallet].
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
-    /// The [Ticket].
+    /// The [Ticket] to close.
     #[account(
-        init,
+        mut,
+        has_one = owner,
+        close = rent_receiver,
         seeds = [
-            b"Stake".as_ref(),
+            system_program.key().to_bytes().as_ref(),
             smart_wallet.key().to_bytes().as_ref(),
-            abs_index.to_le_bytes().as_ref()
+            ticket.mint.to_bytes().as_ref()
+        ],
+        bump = ticket.bump,
+    )]
+    pub ticket: Account<'info, Ticket>,
+    /// The [RentPool] address of the wallet. Checked in the handler, as it may not exist.
+    #[account(
+        mut,
+        seeds = [
+            RENT_POOL_SEED,
+            smart_wallet.key().to_bytes().as_ref()
         ],
         bump,
-        payer = payer,
-        space = Stake::space(stake_data.protected_gids.len()),
     )]
-    pub stake: Account<'info, Stake>,
-    /// Payer to create the [Transaction].
+    pub rent_pool: UncheckedAccount<'info>,
+    /// Receives the rent: the [RentPool] if it exists, otherwise the owner.
     #[account(mut)]
-    pub payer: Signer<'info>,
-    /// The mint owner. Checked in the handler.
+    pub rent_receiver: UncheckedAccount<'info>,
+    /// Owner of the [Ticket].
     pub owner: Signer<'info>,
     /// The [System] program.
     pub system_program: Program<'info, System>,
 }
-/// Accounts for [smart_wallet:append_transaction].
+
+/// Accounts for [smart_wallet::close_rollup].
 #[derive(Accounts)]
-#[instruction(bump: u8, gid: u16)]
-pub struct RollupEntityInit<'info> {
-    /// Payer to create the [Transaction].
-    // pub mint: UncheckedAccount<'info>,
+pub struct CloseRollup<'info> {
     /// The [SmartWallet].
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
-    /// The [Ticket].
+    /// The [Stake] of the [Rollup].
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [Rollup] to close.
+    #[account(mut, has_one = stake, has_one = owner, close = rent_receiver)]
+    pub rollup: Account<'info, Rollup>,
+    /// The [RentPool] address of the wallet. Checked in the handler, as it may not exist.
     #[account(
-        init,
+        mut,
         seeds = [
-            smart_wallet.key().to_bytes().as_ref(),
-            owner.key().to_bytes().as_ref(),
-            gid.to_le_bytes().as_ref()
+            RENT_POOL_SEED,
+            smart_wallet.key().to_bytes().as_ref()
         ],
         bump,
-        payer = payer,
-        space = Rollup::space(),
     )]
-    pub rollup: Account<'info, Rollup>,
+    pub rent_pool: UncheckedAccount<'info>,
+    /// Receives the rent: the [RentPool] if it exists, otherwise the owner.
     #[account(mut)]
-    pub payer: Signer<'info>,
-    /// The mint owner. Checked in the handler.
+    pub rent_receiver: UncheckedAccount<'info>,
+    /// Owner of the [Rollup].
     pub owner: Signer<'info>,
-    /// The [System] program.
-    pub system_program: Program<'info, System>,
 }
 /// Accounts for [smart_wallet:append_transaction].
 #[derive(Accounts)]
@@ -407,7 +6153,10 @@ pub struct RegisterEntity<'info> {
     /// The [SmartWallet].
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
-    #[account(mut)]
+    /// The [Stake] the mint is registered in.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    #[account(mut, has_one = stake)]
     pub rollup: Account<'info, Rollup>,
     /// The [Ticket].
     #[account(
@@ -426,7 +6175,8 @@ pub struct RegisterEntity<'info> {
     pub payer: Signer<'info>,
     /// The mint owner. Checked in the handler.
     pub owner: Signer<'info>,
-    pub mint: UncheckedAccount<'info>,
+    /// The NFT mint. Checked in the handler.
+    pub mint: Account<'info, Mint>,
     /// The [System] program.
     pub system_program: Program<'info, System>,
 }
@@ -438,26 +6188,97 @@ pub struct ClaimEntities<'info> {
     /// The [SmartWallet].
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref(),
+            rollup.gid.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
     pub rollup: Account<'info, Rollup>,
     /// The [Ticket].
-    #[account(mut)]
+    #[account(mut, has_one = smart_wallet, has_one = reward_vault)]
     pub stake: Account<'info, Stake>,
+    /// Rewards claimed by the owner so far.
+    #[account(mut, has_one = stake, has_one = owner)]
+    pub owner_rewards: Account<'info, OwnerRewards>,
+    /// Token account holding the rewards.
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// Token account of the owner receiving the rewards.
+    #[account(
+        mut,
+        constraint = owner_tokens.mint == stake.reward_mint,
+        constraint = owner_tokens.owner == owner.key(),
+    )]
+    pub owner_tokens: Account<'info, TokenAccount>,
     #[account(mut)]
     pub payer: Signer<'info>,
     /// The mint owner. Checked in the handler.
     pub owner: Signer<'info>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
     /// The [System] program.
     pub system_program: Program<'info, System>,
 }
+
+/// Accounts for [smart_wallet::claim_entities_to].
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct ClaimEntitiesTo<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    #[account(
+        mut,
+        seeds = [
+            stake.key().to_bytes().as_ref(),
+            owner.key().to_bytes().as_ref(),
+            rollup.gid.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub rollup: Account<'info, Rollup>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet, has_one = reward_vault)]
+    pub stake: Account<'info, Stake>,
+    /// Rewards claimed by the owner so far.
+    #[account(mut, has_one = stake, has_one = owner)]
+    pub owner_rewards: Account<'info, OwnerRewards>,
+    /// Token account holding the rewards.
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    /// Token account receiving the rewards. Its owner is checked in the handler.
+    #[account(mut, constraint = destination.mint == stake.reward_mint)]
+    pub destination: Account<'info, TokenAccount>,
+    /// The owner of the [Rollup].
+    pub owner: Signer<'info>,
+    /// The [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts for [smart_wallet::distribute_rewards].
+#[derive(Accounts)]
+pub struct DistributeRewards<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] rewards are distributed from.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
 #[derive(Accounts)]
 #[instruction(bump: u8)]
 pub struct UpdateEntityByOwner<'info> {
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
     #[account(mut)]
     pub ticket: Account<'info, Ticket>,
-    #[account(mut)]
+    #[account(mut, has_one = stake)]
     pub rollup: Account<'info, Rollup>,
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -469,6 +6290,51 @@ pub struct UpdateEntityByOwner<'info> {
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
+/// Accounts for [smart_wallet::activate_entity].
+#[derive(Accounts)]
+pub struct ActivateEntity<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The pending [Ticket]. Checked in the handler.
+    #[account(mut)]
+    pub ticket: Account<'info, Ticket>,
+    /// The [Rollup] of the ticket's owner and gid.
+    #[account(mut, has_one = stake)]
+    pub rollup: Account<'info, Rollup>,
+    /// Token account of the owner holding the mint.
+    pub mint_ata: Account<'info, TokenAccount>,
+}
+/// Accounts for [smart_wallet::report_rollups].
+#[derive(Accounts)]
+pub struct ReportRollups<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] the [Rollup]s accrue rewards in.
+    #[account(has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
+
+/// Accounts for [smart_wallet::freeze_ticket] and [smart_wallet::unfreeze_ticket].
+#[derive(Accounts)]
+pub struct FreezeTicket<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake].
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// The [Ticket]. Checked in the handler.
+    #[account(mut)]
+    pub ticket: Account<'info, Ticket>,
+    /// The [Rollup] of the ticket's owner and gid.
+    #[account(mut, has_one = stake)]
+    pub rollup: Account<'info, Rollup>,
+}
 #[derive(Accounts)]
 #[instruction(bump: u8, timestamp: Vec<u8>)]
 pub struct UpdateEntity<'info> {
@@ -485,17 +6351,27 @@ pub struct UpdateEntity<'info> {
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
+/// Accounts for [smart_wallet::update_entities].
+#[derive(Accounts)]
+pub struct UpdateEntities<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] the [Rollup]s accrue rewards in.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+}
 /// Accounts for [smart_wallet:append_transaction].
 #[derive(Accounts)]
 #[instruction(bump: u8)]
 pub struct WithdrawEntityByProgram<'info> {
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
-    #[account(mut)]
+    #[account(mut, has_one = smart_wallet)]
     pub stake: Account<'info, Stake>,
     #[account(mut)]
     pub ticket: Account<'info, Ticket>,
-    #[account(mut)]
+    #[account(mut, has_one = stake)]
     pub rollup: Account<'info, Rollup>,
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -509,11 +6385,11 @@ pub struct WithdrawEntityByProgram<'info> {
 pub struct WithdrawEntity<'info> {
     #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
-    #[account(mut)]
+    #[account(mut, has_one = smart_wallet)]
     pub stake: Account<'info, Stake>,
     #[account(mut)]
     pub ticket: Account<'info, Ticket>,
-    #[account(mut)]
+    #[account(mut, has_one = stake)]
     pub rollup: Account<'info, Rollup>,
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -522,10 +6398,24 @@ pub struct WithdrawEntity<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Accounts for [smart_wallet::withdraw_all_entities].
+#[derive(Accounts)]
+pub struct WithdrawAllEntities<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [Stake] the tickets are withdrawn from.
+    #[account(mut, has_one = smart_wallet)]
+    pub stake: Account<'info, Stake>,
+    /// Owner of the tickets.
+    pub owner: Signer<'info>,
+}
+
 /// Accounts for [smart_wallet::execute_transaction].
 #[derive(Accounts)]
 pub struct ExecuteInstructions<'info> {
     /// The [SmartWallet].
+    #[account(mut)]
     pub smart_wallet: Account<'info, SmartWallet>,
     /// The [Transaction] to execute.
     #[account(mut)]
@@ -535,6 +6425,138 @@ pub struct ExecuteInstructions<'info> {
     pub authority_b: Signer<'info>,
 }
 
+/// Accounts for [smart_wallet::create_subaccount_info].
+#[derive(Accounts)]
+#[instruction(bump: u8, subaccount: Pubkey)]
+pub struct CreateSubaccountInfo<'info> {
+    /// The [SmartWallet] owning the subaccount.
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [SubaccountInfo] to create.
+    #[account(
+        init,
+        seeds = [
+            SUBACCOUNT_INFO_SEED,
+            subaccount.to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = SubaccountInfo::space(),
+    )]
+    pub subaccount_info: Account<'info, SubaccountInfo>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::create_invoker_whitelist].
+#[derive(Accounts)]
+#[instruction(bump: u8, index: u64, permissions: Vec<InvokerPermission>)]
+pub struct CreateInvokerWhitelist<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [InvokerWhitelist] to create.
+    #[account(
+        init,
+        seeds = [
+            INVOKER_WHITELIST_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = InvokerWhitelist::space(permissions.len()),
+    )]
+    pub invoker_whitelist: Account<'info, InvokerWhitelist>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::set_invoker_whitelist].
+#[derive(Accounts)]
+pub struct SetInvokerWhitelist<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [InvokerWhitelist].
+    #[account(mut, has_one = smart_wallet)]
+    pub invoker_whitelist: Account<'info, InvokerWhitelist>,
+    /// Payer of any additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::owner_invoke_instruction].
+#[derive(Accounts)]
+pub struct OwnerInvokeInstruction<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// An owner of the [SmartWallet].
+    pub owner: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::create_one_shot].
+#[derive(Accounts)]
+#[instruction(bump: u8, nonce: u64, key: Pubkey, wallet_index: u64, instruction: TXInstruction)]
+pub struct CreateOneShot<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OneShot] to create.
+    #[account(
+        init,
+        seeds = [
+            ONE_SHOT_SEED,
+            smart_wallet.key().to_bytes().as_ref(),
+            key.to_bytes().as_ref(),
+            nonce.to_le_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = OneShot::space(&instruction),
+    )]
+    pub one_shot: Account<'info, OneShot>,
+    /// Payer to create the [OneShot].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for [smart_wallet::execute_one_shot].
+#[derive(Accounts)]
+pub struct ExecuteOneShot<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OneShot] to execute.
+    #[account(mut, has_one = smart_wallet, has_one = key)]
+    pub one_shot: Account<'info, OneShot>,
+    /// [OneShot::key].
+    pub key: Signer<'info>,
+}
+
+/// Accounts for [smart_wallet::revoke_one_shot].
+#[derive(Accounts)]
+pub struct RevokeOneShot<'info> {
+    /// The [SmartWallet].
+    #[account(mut)]
+    pub smart_wallet: Account<'info, SmartWallet>,
+    /// The [OneShot] to close.
+    #[account(mut, has_one = smart_wallet, close = receiver)]
+    pub one_shot: Account<'info, OneShot>,
+    /// Receives the rent of the [OneShot].
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
 #[error]
 pub enum ErrorCode {
     #[msg("The given owner is not part of this smart wallet.")]
@@ -557,16 +6579,123 @@ pub enum ErrorCode {
     OwnerSetChanged,
     #[msg("Invalid bump seed.")]
     InvalidBump,
-    #[msg("Invalid Mint.")]
-    InvalidMint,
-    #[msg("Protected GID.")]
-    ProtectedGid,
-    #[msg("Qualified Hijack.")]
-    NoJack,
-    #[msg("Qualified GID Hijack.")]
-    NoGIDJack,
-    #[msg("Inconsistent Timestamp.")]
-    DisingenuousUpdate,
-    #[msg("Invalid Mint ATA.")]
-    InvalidATA,
+    #[msg("Executor reimbursement exceeds the maximum.")]
+    ReimbursementTooHigh,
+    #[msg("Executor reimbursement vault or System program not provided.")]
+    MissingReimbursementVault,
+    #[msg("The given owner is suspended.")]
+    OwnerSuspended,
+    #[msg("The given owner is not suspended.")]
+    OwnerNotSuspended,
+    #[msg("The executor is not on the executor allowlist.")]
+    ExecutorNotAllowed,
+    #[msg("Too many executors on the allowlist.")]
+    TooManyExecutors,
+    #[msg("The protocol config account is missing.")]
+    MissingProtocolConfig,
+    #[msg("Protocol parameters are out of range.")]
+    InvalidProtocolParams,
+    #[msg("Signer is not the program's upgrade authority.")]
+    NotUpgradeAuthority,
+    #[msg("Too many owners.")]
+    TooManyOwners,
+    #[msg("Too many instructions.")]
+    TooManyInstructions,
+    #[msg("The protocol fee account is missing.")]
+    MissingProtocolFeeAccount,
+    #[msg("The protocol is paused.")]
+    ProtocolPaused,
+    #[msg("Too many audited programs.")]
+    TooManyAuditedPrograms,
+    #[msg("The program is not audited.")]
+    ProgramNotAudited,
+    #[msg("The transaction is not past its grace period.")]
+    TransactionNotExpired,
+    #[msg("The transaction is already marked as expired.")]
+    TransactionAlreadyExpired,
+    #[msg("The proposer does not hold enough of the proposer gate token.")]
+    ProposerGateNotMet,
+    #[msg("The transaction has not been executed.")]
+    TransactionNotExecuted,
+    #[msg("The proposer activity account is missing.")]
+    MissingProposerActivity,
+    #[msg("The proposer has reached the proposal rate limit.")]
+    ProposalRateLimited,
+    #[msg("The invoker whitelist was not passed.")]
+    MissingInvokerWhitelist,
+    #[msg("The instruction is not in the invoker whitelist.")]
+    InstructionNotWhitelisted,
+    #[msg("Discriminators must be between 1 and 8 bytes.")]
+    InvalidDiscriminator,
+    #[msg("Grace period must be positive or NO_GRACE_PERIOD.")]
+    InvalidGracePeriod,
+    #[msg("The instructions do not match the committed hash.")]
+    InstructionsHashMismatch,
+    #[msg("The account is not a valid upstream Goki account.")]
+    InvalidLegacyAccount,
+    #[msg("The transaction already declares its writable scope.")]
+    WritableScopeAlreadyDeclared,
+    #[msg("An instruction writes to an account outside the writable scope.")]
+    AccountOutsideWritableScope,
+    #[msg("Execution checkpoints must move forward within the instructions.")]
+    InvalidCheckpoint,
+    #[msg("The signed approval message has expired.")]
+    ApprovalMessageExpired,
+    #[msg("The approval message domain is too long.")]
+    ApprovalDomainTooLong,
+    #[msg("No ed25519 instruction verifies the owner's signature.")]
+    MissingSignatureVerification,
+    #[msg("Only the proposer may execute this transaction.")]
+    ExecutorNotProposer,
+    #[msg("An owner other than the proposer has already approved the transaction.")]
+    TransactionAlreadyApproved,
+    #[msg("Instructions can only be approved individually once they are stored.")]
+    InstructionsNotStored,
+    #[msg("Approvals must be given for each of the transaction's instructions.")]
+    InstructionApprovalsMismatch,
+    #[msg("A one-shot window must end after it starts.")]
+    InvalidOneShotWindow,
+    #[msg("The one-shot instruction has already been executed.")]
+    OneShotAlreadyExecuted,
+    #[msg("The one-shot instruction cannot be executed outside its window.")]
+    OneShotWindowClosed,
+    #[msg("The transaction has been cancelled.")]
+    TransactionCancelled,
+    #[msg("Only the proposer or the smart wallet may cancel the transaction.")]
+    InvalidCancelAuthority,
+    #[msg("Only the veto authority may veto the transaction.")]
+    InvalidVetoAuthority,
+    #[msg("Only queued transactions can be vetoed, before their ETA.")]
+    VetoWindowClosed,
+    #[msg("Only the proposer or the smart wallet may close the transaction.")]
+    InvalidCloseAuthority,
+    #[msg("The transaction is pending: it must be executed, cancelled or expired to be closed.")]
+    TransactionPending,
+    #[msg("The transaction belongs to a different smart wallet.")]
+    TransactionWalletMismatch,
+    #[msg("The execution receipt or the system program was not passed.")]
+    MissingExecutionReceipt,
+    #[msg("Approval memo is too long.")]
+    ApprovalMemoTooLong,
+    #[msg("The transaction already declares its dependencies.")]
+    DependenciesAlreadyDeclared,
+    #[msg("Too many dependencies.")]
+    TooManyDependencies,
+    #[msg("A transaction cannot depend on itself.")]
+    InvalidDependency,
+    #[msg("A dependency of the transaction has not executed.")]
+    DependencyNotExecuted,
+    #[msg("The transaction is not a draft.")]
+    TransactionNotDraft,
+    #[msg("An instruction has too many keys.")]
+    TooManyInstructionKeys,
+    #[msg("An instruction's data is too long.")]
+    InstructionDataTooLong,
+    #[msg("The transaction cannot move to that state.")]
+    InvalidStateTransition,
+    #[msg("The transaction is a draft that has not been finalized.")]
+    TransactionIsDraft,
+    #[msg("The smart wallet does not govern this protocol config update.")]
+    ProtocolConfigNotGoverned,
 }
+
@@ -0,0 +1,135 @@
+use crate::*;
+
+impl ProtocolConfig {
+    /// Derives the address of the [ProtocolConfig].
+    pub fn address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PROTOCOL_CONFIG_SEED], &crate::ID)
+    }
+
+    /// Reads the [ProtocolConfig] from `accounts`, which must include its address.
+    /// Returns the defaults if it has not been created yet.
+    pub fn load(accounts: &[AccountInfo]) -> Result<ProtocolConfig> {
+        let (address, _) = ProtocolConfig::address();
+        let info = unwrap_or_err!(
+            accounts.iter().find(|a| a.key() == address),
+            MissingProtocolConfig
+        );
+        if info.data_is_empty() {
+            return Ok(ProtocolConfig::default());
+        }
+        require!(*info.owner == crate::ID, MissingProtocolConfig);
+        Ok(ProtocolConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?)
+    }
+
+    /// Reads the [ProtocolConfig] from `accounts` and checks the protocol is not paused.
+    pub fn check_not_paused(accounts: &[AccountInfo]) -> ProgramResult {
+        require!(!ProtocolConfig::load(accounts)?.paused, ProtocolPaused);
+        Ok(())
+    }
+
+    /// Checks that `smart_wallet` may invoke `ixs`: the protocol is not paused and, in
+    /// audited-programs mode, every program is in [ProtocolConfig::audited_programs].
+    /// This program is always allowed, so the wallet can still change its own settings.
+    /// [ProtocolConfig::is_governance] instructions may run while paused.
+    pub fn check_invoke(&self, smart_wallet: &SmartWallet, ixs: &[TXInstruction]) -> ProgramResult {
+        require!(
+            !self.paused || ixs.iter().all(ProtocolConfig::is_governance),
+            ProtocolPaused
+        );
+        if smart_wallet.audited_programs_only {
+            for ix in ixs.iter() {
+                require!(
+                    ix.program_id == crate::ID || self.audited_programs.contains(&ix.program_id),
+                    ProgramNotAudited
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether `ix` calls [crate::smart_wallet::govern_protocol_config], so a
+    /// [SmartWallet] holding the pause can lift it from an executed [Transaction].
+    pub fn is_governance(ix: &TXInstruction) -> bool {
+        let sighash = solana_program::hash::hash(b"global:govern_protocol_config").to_bytes();
+        ix.program_id == crate::ID && ix.data.get(..8) == Some(&sighash[..8])
+    }
+
+    /// Applies `update` signed by `smart_wallet`, which must govern it: the
+    /// [ProtocolConfig::security_council] for pausing, otherwise the
+    /// [ProtocolConfig::authority].
+    pub fn apply_update(
+        &mut self,
+        smart_wallet: Pubkey,
+        update: ProtocolConfigUpdate,
+    ) -> ProgramResult {
+        let governor = match update {
+            ProtocolConfigUpdate::SetPaused { .. } => self.security_council,
+            _ => self.authority,
+        };
+        require!(smart_wallet == governor, ProtocolConfigNotGoverned);
+        match update {
+            ProtocolConfigUpdate::SetProtocolFee {
+                protocol_fee_bps,
+                fee_recipient,
+            } => {
+                require!(
+                    protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+                    InvalidProtocolParams
+                );
+                self.protocol_fee_bps = protocol_fee_bps;
+                self.fee_recipient = fee_recipient;
+            }
+            ProtocolConfigUpdate::SetPaused { paused } => self.paused = paused,
+            ProtocolConfigUpdate::SetSecurityCouncil { security_council } => {
+                self.security_council = security_council
+            }
+            ProtocolConfigUpdate::AddAuditedProgram { program } => {
+                self.add_audited_program(program)?
+            }
+            ProtocolConfigUpdate::RemoveAuditedProgram { program } => {
+                self.remove_audited_program(program)
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds `program` to [ProtocolConfig::audited_programs] if it is not already there.
+    pub fn add_audited_program(&mut self, program: Pubkey) -> ProgramResult {
+        if !self.audited_programs.contains(&program) {
+            require!(
+                self.audited_programs.len() < MAX_AUDITED_PROGRAMS,
+                TooManyAuditedPrograms
+            );
+            self.audited_programs.push(program);
+        }
+        Ok(())
+    }
+
+    /// Removes `program` from [ProtocolConfig::audited_programs].
+    pub fn remove_audited_program(&mut self, program: Pubkey) {
+        self.audited_programs.retain(|id| *id != program);
+    }
+
+    /// Sets the tunable parameters, checking they are in range.
+    pub fn set_params(&mut self, params: ProtocolParams) -> ProgramResult {
+        require!(
+            params.max_owners > 0
+                && params.max_instructions > 0
+                && params.default_grace_period > 0
+                && params.protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            InvalidProtocolParams
+        );
+        self.max_owners = params.max_owners;
+        self.max_instructions = params.max_instructions;
+        self.default_grace_period = params.default_grace_period;
+        self.protocol_fee_bps = params.protocol_fee_bps;
+        self.fee_recipient = params.fee_recipient;
+        Ok(())
+    }
+
+    /// The protocol's share of a fee of `amount`.
+    pub fn protocol_fee(&self, amount: u64) -> Result<u64> {
+        let fee = unwrap_int!((amount as u128).checked_mul(self.protocol_fee_bps as u128)) / 10_000;
+        Ok(unwrap_int!(u64::try_from(fee).ok()))
+    }
+}
@@ -0,0 +1,73 @@
+use crate::*;
+use solana_program::{ed25519_program, sysvar::instructions::load_instruction_at_checked};
+
+/// Size of the offsets of each signature in an ed25519 program instruction.
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+
+/// Checks that the transaction verifies `signer`'s ed25519 signature over `message`,
+/// with an ed25519 program instruction found through the `instructions` sysvar.
+pub fn check_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> ProgramResult {
+    find_verification(instructions, signer, None, message)
+}
+
+/// Like [check_ed25519_signature] for the owner of `sig`, also checking that the
+/// verified signature is the one in `sig`.
+pub fn check_ed25519_owner_sig(
+    instructions: &AccountInfo,
+    sig: &OwnerSig,
+    message: &[u8],
+) -> ProgramResult {
+    find_verification(instructions, &sig.owner, Some(&sig.signature), message)
+}
+
+/// Finds an ed25519 program instruction verifying `signer`'s signature over `message`.
+fn find_verification(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    signature: Option<&[u8; 64]>,
+    message: &[u8],
+) -> ProgramResult {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == ed25519_program::ID && verifies(&ix.data, signer, signature, message) {
+            return Ok(());
+        }
+        index += 1;
+    }
+    Err(ErrorCode::MissingSignatureVerification.into())
+}
+
+/// Returns true if the ed25519 program instruction `data` verifies a signature by
+/// `signer` over `message`, both stored in the instruction itself. If given, the
+/// signature must also be `signature`.
+fn verifies(data: &[u8], signer: &Pubkey, signature: Option<&[u8; 64]>, message: &[u8]) -> bool {
+    let num_signatures = match data.first() {
+        Some(n) => *n as usize,
+        None => return false,
+    };
+    (0..num_signatures).any(|i| {
+        let start = 2 + i * SIGNATURE_OFFSETS_LEN;
+        let offsets = match data.get(start..start + SIGNATURE_OFFSETS_LEN) {
+            Some(offsets) => offsets,
+            None => return false,
+        };
+        let read = |field: usize| {
+            u16::from_le_bytes([offsets[field * 2], offsets[field * 2 + 1]]) as usize
+        };
+        // Instruction indices of the signature, public key and message.
+        if [read(1), read(3), read(6)].iter().any(|&ix| ix != u16::MAX as usize) {
+            return false;
+        }
+        let signature_matches = match signature {
+            Some(signature) => data.get(read(0)..read(0) + 64) == Some(&signature[..]),
+            None => true,
+        };
+        signature_matches
+            && data.get(read(2)..read(2) + 32) == Some(signer.as_ref())
+            && data.get(read(4)..read(4) + read(5)) == Some(message)
+    })
+}
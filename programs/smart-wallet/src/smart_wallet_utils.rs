@@ -1,6 +1,11 @@
 use crate::*;
 
 impl SmartWallet {
+    /// Derives the address of the [SmartWallet] created with `base`.
+    pub fn address(base: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SMART_WALLET_SEED, &base.to_bytes()], &crate::ID)
+    }
+
     /// Gets the index of the key in the owners Vec, or error
     pub fn owner_index(&self, key: Pubkey) -> Result<usize> {
         Ok(unwrap_or_err!(
@@ -8,4 +13,453 @@ impl SmartWallet {
             InvalidOwner
         ))
     }
+
+    /// Gets the index of the key in the owners Vec, erroring if the owner is suspended.
+    pub fn active_owner_index(&self, key: Pubkey) -> Result<usize> {
+        let index = self.owner_index(key)?;
+        require!(!self.is_suspended(key), OwnerSuspended);
+        Ok(index)
+    }
+
+    /// Checks that the key may propose a [Transaction], returning its owner index if it
+    /// is an owner. Other accounts must have their [AllowedProposer] or meet the
+    /// [SmartWallet::proposer_gate] with a token account, passed in `accounts`.
+    pub fn check_proposer(
+        &self,
+        smart_wallet: Pubkey,
+        key: Pubkey,
+        accounts: &[AccountInfo],
+    ) -> Result<Option<usize>> {
+        if self.owners.contains(&key) {
+            return Ok(Some(self.active_owner_index(key)?));
+        }
+        let (allowed_proposer, _) = AllowedProposer::address(smart_wallet, key);
+        if accounts
+            .iter()
+            .any(|a| a.key() == allowed_proposer && *a.owner == crate::ID && !a.data_is_empty())
+        {
+            return Ok(None);
+        }
+        let gate = unwrap_or_err!(self.proposer_gate, InvalidOwner);
+        require!(
+            accounts.iter().any(|a| {
+                Account::<TokenAccount>::try_from(a)
+                    .map(|t| t.mint == gate.mint && t.owner == key && t.amount >= gate.min_amount)
+                    .unwrap_or(false)
+            }),
+            ProposerGateNotMet
+        );
+        Ok(None)
+    }
+
+    /// Counts a proposal by the key against the [SmartWallet::proposal_rate_limit],
+    /// updating its [ProposerActivity] passed in `accounts`.
+    pub fn check_rate_limit(
+        &self,
+        smart_wallet: Pubkey,
+        key: Pubkey,
+        accounts: &[AccountInfo],
+        now: i64,
+    ) -> ProgramResult {
+        let limit = match self.proposal_rate_limit {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let address = ProposerActivity::address(smart_wallet, key).0;
+        let info = unwrap_or_err!(
+            accounts.iter().find(|a| a.key() == address),
+            MissingProposerActivity
+        );
+        let mut activity: Account<ProposerActivity> = Account::try_from(info)?;
+        if now >= unwrap_int!(activity.window_start.checked_add(limit.window)) {
+            activity.window_start = now;
+            activity.num_proposals = 0;
+        }
+        require!(
+            activity.num_proposals < limit.max_proposals,
+            ProposalRateLimited
+        );
+        activity.num_proposals = unwrap_int!(activity.num_proposals.checked_add(1));
+        activity.exit(&crate::ID)
+    }
+
+    /// Returns true if the owner is suspended.
+    pub fn is_suspended(&self, key: Pubkey) -> bool {
+        self.suspended_owners.contains(&key)
+    }
+
+    /// Derives the address of a subaccount of this wallet.
+    pub fn subaccount_address(
+        smart_wallet: Pubkey,
+        subaccount_type: SubaccountType,
+        index: u64,
+    ) -> (Pubkey, u8) {
+        let prefix: &[u8] = match subaccount_type {
+            SubaccountType::Derived => DERIVED_WALLET_SEED,
+            SubaccountType::OwnerInvoker => OWNER_INVOKER_SEED,
+        };
+        Pubkey::find_program_address(
+            &[prefix, &smart_wallet.to_bytes(), &index.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Increments and returns [SmartWallet::event_seq], to be included in the next event.
+    pub fn next_event_seq(&mut self) -> Result<u64> {
+        self.event_seq = unwrap_int!(self.event_seq.checked_add(1));
+        Ok(self.event_seq)
+    }
+
+    /// Returns the next [Transaction] index and increments [SmartWallet::num_transactions].
+    pub fn next_transaction_index(&mut self) -> Result<u64> {
+        let index = self.num_transactions;
+        self.num_transactions = unwrap_int!(self.num_transactions.checked_add(1));
+        Ok(index)
+    }
+
+    /// Records an execution at `now` in the activity statistics.
+    pub fn record_execution(&mut self, now: i64) -> ProgramResult {
+        self.num_executed = unwrap_int!(self.num_executed.checked_add(1));
+        self.last_executed_at = now;
+        Ok(())
+    }
+
+    /// Moves the [SmartWallet::expiry_cursor] up to the [Transaction] at `index`.
+    pub fn advance_expiry_cursor(&mut self, index: u64) {
+        self.expiry_cursor = self.expiry_cursor.max(index);
+    }
+
+    /// Records an approval at `now` in the activity statistics.
+    pub fn record_approval(&mut self, now: i64) {
+        self.last_approved_at = now;
+    }
+
+    /// Canonical hash of the governance configuration: the SHA-256 hash of the Borsh
+    /// serialization of the owners, threshold, delays, owner set sequence number,
+    /// suspended owners, executor allowlist and reimbursement, audited program
+    /// restriction, proposer gate, proposal bond and rate limit, and veto authority, in
+    /// that order.
+    pub fn config_digest(&self) -> Result<[u8; 32]> {
+        let data = (
+            &self.owners,
+            self.threshold,
+            self.minimum_delay,
+            self.grace_period,
+            self.owner_set_seqno,
+            &self.suspended_owners,
+            &self.executor_allowlist,
+            self.executor_reimbursement,
+            self.audited_programs_only,
+            self.proposer_gate,
+            self.proposal_bond,
+            self.proposal_rate_limit,
+            self.veto_authority,
+        )
+            .try_to_vec()
+            .map_err(ProgramError::from)?;
+        Ok(solana_program::hash::hash(&data).to_bytes())
+    }
+
+    /// Checks that the key may execute transactions on this [SmartWallet].
+    pub fn check_executor(&self, key: Pubkey) -> ProgramResult {
+        require!(
+            self.executor_allowlist.is_empty() || self.executor_allowlist.contains(&key),
+            ExecutorNotAllowed
+        );
+        Ok(())
+    }
+
+    /// Checks that at least [SmartWallet::threshold] distinct, non-suspended owners
+    /// are signers among the given accounts.
+    pub fn check_owner_signers(&self, accounts: &[AccountInfo]) -> ProgramResult {
+        self.check_signers(accounts, false)
+    }
+
+    /// Checks that at least [SmartWallet::threshold] distinct owners are signers
+    /// among the given accounts, counting suspended owners.
+    pub fn check_all_owner_signers(&self, accounts: &[AccountInfo]) -> ProgramResult {
+        self.check_signers(accounts, true)
+    }
+
+    fn check_signers(&self, accounts: &[AccountInfo], include_suspended: bool) -> ProgramResult {
+        let mut signed = vec![false; self.owners.len()];
+        for account in accounts.iter().filter(|a| a.is_signer) {
+            if !include_suspended && self.is_suspended(account.key()) {
+                continue;
+            }
+            if let Some(index) = self.owners.iter().position(|a| *a == account.key()) {
+                signed[index] = true;
+            }
+        }
+        let num_signed = signed.iter().filter(|&did_sign| *did_sign).count() as u64;
+        require!(num_signed >= self.threshold, NotEnoughSigners);
+        Ok(())
+    }
+
+    /// Pays the [ExecutorReimbursement] to the executor, if one is configured.
+    ///
+    /// The vault and the [System] program are looked up in `accounts`.
+    /// Returns the number of lamports paid.
+    pub fn reimburse_executor<'info>(
+        &self,
+        smart_wallet: Pubkey,
+        executor: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<u64> {
+        let reimbursement = match self.executor_reimbursement {
+            Some(reimbursement) => reimbursement,
+            None => return Ok(0),
+        };
+        let amount = unwrap_int!(reimbursement.total_lamports());
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        let seeds: &[&[u8]] = &[
+            DERIVED_WALLET_SEED,
+            &smart_wallet.to_bytes(),
+            &reimbursement.vault_index.to_le_bytes(),
+            &[reimbursement.vault_bump],
+        ];
+        let vault_key = Pubkey::create_program_address(seeds, &crate::ID)
+            .map_err(|_| ErrorCode::InvalidBump)?;
+        let vault = unwrap_or_err!(
+            accounts.iter().find(|a| a.key() == vault_key),
+            MissingReimbursementVault
+        );
+        let system_program = unwrap_or_err!(
+            accounts.iter().find(|a| a.key() == System::id()),
+            MissingReimbursementVault
+        );
+        if vault.lamports() < amount {
+            msg!("reimbursement vault is underfunded");
+            return Ok(0);
+        }
+
+        solana_program::program::invoke_signed(
+            &solana_program::system_instruction::transfer(&vault_key, &executor.key(), amount),
+            &[vault.clone(), executor.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+        Ok(amount)
+    }
+}
+
+impl SubaccountInfo {
+    /// Derives the address of the [SubaccountInfo] of a subaccount.
+    pub fn address(subaccount: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SUBACCOUNT_INFO_SEED, &subaccount.to_bytes()], &crate::ID)
+    }
+
+    /// Returns true if `authority` is the [SmartWallet] `smart_wallet` itself or one of
+    /// its derived wallets, whose [SubaccountInfo] is passed in `accounts`.
+    pub fn is_wallet_authority<'info>(
+        smart_wallet: Pubkey,
+        authority: Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> bool {
+        if authority == smart_wallet {
+            return true;
+        }
+        let (address, _) = SubaccountInfo::address(authority);
+        accounts
+            .iter()
+            .filter(|info| info.key() == address)
+            .filter_map(|info| Account::<SubaccountInfo>::try_from(info).ok())
+            .any(|info| {
+                info.smart_wallet == smart_wallet
+                    && info.subaccount_type == SubaccountType::Derived
+            })
+    }
+}
+
+impl AllowedProposer {
+    /// Derives the address of the [AllowedProposer] of a proposer.
+    pub fn address(smart_wallet: Pubkey, proposer: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                ALLOWED_PROPOSER_SEED,
+                &smart_wallet.to_bytes(),
+                &proposer.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+}
+
+impl OwnerNotification {
+    /// Derives the address of the [OwnerNotification] of an owner.
+    pub fn address(smart_wallet: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                OWNER_NOTIFICATION_SEED,
+                &smart_wallet.to_bytes(),
+                &owner.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Returns the addresses of the [OwnerNotification]s of the [SmartWallet] in `accounts`.
+    pub fn collect(smart_wallet: Pubkey, accounts: &[AccountInfo]) -> Vec<Pubkey> {
+        accounts
+            .iter()
+            .filter(|a| {
+                Account::<OwnerNotification>::try_from(a)
+                    .map(|n| n.smart_wallet == smart_wallet)
+                    .unwrap_or(false)
+            })
+            .map(|a| a.key())
+            .collect()
+    }
+}
+
+impl OwnerHeartbeat {
+    /// Derives the address of the [OwnerHeartbeat] of an owner.
+    pub fn address(smart_wallet: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                OWNER_HEARTBEAT_SEED,
+                &smart_wallet.to_bytes(),
+                &owner.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Records a heartbeat at `now`.
+    pub fn record(&mut self, now: i64) -> ProgramResult {
+        self.last_seen = now;
+        self.num_heartbeats = unwrap_int!(self.num_heartbeats.checked_add(1));
+        Ok(())
+    }
+
+    /// Seconds since the owner was last seen at `now`.
+    pub fn silence(&self, now: i64) -> i64 {
+        now.saturating_sub(self.last_seen)
+    }
+}
+
+impl OneShot {
+    /// Derives the address of the [OneShot] of a key at `nonce`.
+    pub fn address(smart_wallet: Pubkey, key: Pubkey, nonce: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                ONE_SHOT_SEED,
+                &smart_wallet.to_bytes(),
+                &key.to_bytes(),
+                &nonce.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Checks that the instruction may be executed at `now`: it has not been, and its
+    /// window is open.
+    pub fn check_executable(&self, now: i64) -> ProgramResult {
+        require!(self.executed_at == -1, OneShotAlreadyExecuted);
+        require!(
+            self.valid_from <= now && now < self.valid_until,
+            OneShotWindowClosed
+        );
+        Ok(())
+    }
+}
+
+impl ProposerActivity {
+    /// Derives the address of the [ProposerActivity] of a proposer.
+    pub fn address(smart_wallet: Pubkey, proposer: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                PROPOSER_ACTIVITY_SEED,
+                &smart_wallet.to_bytes(),
+                &proposer.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+}
+
+impl InvokerWhitelist {
+    /// Derives the address of the [InvokerWhitelist] of an owner invoker.
+    pub fn address(smart_wallet: Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                INVOKER_WHITELIST_SEED,
+                &smart_wallet.to_bytes(),
+                &index.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Checks that the owner invoker at `index` may invoke `ix`, reading its
+    /// [InvokerWhitelist] from `accounts`, which must include its address.
+    /// Invokers without a whitelist are unrestricted.
+    pub fn check(
+        smart_wallet: Pubkey,
+        index: u64,
+        accounts: &[AccountInfo],
+        ix: &TXInstruction,
+    ) -> ProgramResult {
+        let (address, _) = InvokerWhitelist::address(smart_wallet, index);
+        let info = unwrap_or_err!(
+            accounts.iter().find(|a| a.key() == address),
+            MissingInvokerWhitelist
+        );
+        if info.data_is_empty() {
+            return Ok(());
+        }
+        let whitelist: Account<InvokerWhitelist> = Account::try_from(info)?;
+        require!(whitelist.allows(ix), InstructionNotWhitelisted);
+        Ok(())
+    }
+
+    /// Returns true if `ix` matches one of the permissions.
+    pub fn allows(&self, ix: &TXInstruction) -> bool {
+        self.permissions.iter().any(|permission| {
+            permission.program_id == ix.program_id && ix.data.starts_with(&permission.discriminator)
+        })
+    }
+
+    /// Checks that every discriminator is non-empty and at most
+    /// [MAX_DISCRIMINATOR_LEN] bytes.
+    pub fn check_permissions(permissions: &[InvokerPermission]) -> ProgramResult {
+        require!(
+            permissions.iter().all(|permission| {
+                !permission.discriminator.is_empty()
+                    && permission.discriminator.len() <= MAX_DISCRIMINATOR_LEN
+            }),
+            InvalidDiscriminator
+        );
+        Ok(())
+    }
+}
+
+impl LegacySmartWallet {
+    /// Deserializes a [LegacySmartWallet] from an account owned by the upstream program.
+    pub fn load(info: &AccountInfo) -> Result<LegacySmartWallet> {
+        require!(*info.owner == upstream_goki::ID, InvalidLegacyAccount);
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == SmartWallet::discriminator(),
+            InvalidLegacyAccount
+        );
+        LegacySmartWallet::deserialize(&mut &data[8..])
+            .map_err(|_| ErrorCode::InvalidLegacyAccount.into())
+    }
+
+    /// Checks that at least [LegacySmartWallet::threshold] distinct owners are signers
+    /// among the given accounts.
+    pub fn check_owner_signers(&self, accounts: &[AccountInfo]) -> ProgramResult {
+        let mut signed = vec![false; self.owners.len()];
+        for account in accounts.iter().filter(|a| a.is_signer) {
+            if let Some(index) = self.owners.iter().position(|a| *a == account.key()) {
+                signed[index] = true;
+            }
+        }
+        let num_signed = signed.iter().filter(|&did_sign| *did_sign).count() as u64;
+        require!(num_signed >= self.threshold, NotEnoughSigners);
+        Ok(())
+    }
 }
@@ -0,0 +1,138 @@
+//! Errors of the staking subsystem.
+//!
+//! Their codes start at 7000, apart from the smart wallet's [crate::ErrorCode]s
+//! starting at 6000, so clients can tell which subsystem a failure comes from.
+
+use anchor_lang::prelude::*;
+
+#[error(offset = 7000)]
+pub enum StakeErrorCode {
+    #[msg("Invalid bump seed.")]
+    InvalidBump,
+    #[msg("The ticket or rollup belongs to a different owner.")]
+    OwnerMismatch,
+    #[msg("The mint does not match the ticket or milestone.")]
+    InvalidMint,
+    #[msg("The gid is protected, so its tickets cannot be withdrawn or its rewards claimed.")]
+    ProtectedGid,
+    #[msg("The account is not the expected account of this stake, owner or gid.")]
+    AccountMismatch,
+    #[msg("The rollup's gid does not match the ticket's gid.")]
+    GidMismatch,
+    #[msg("A stored timestamp is malformed or the ticket is already enrolled.")]
+    InvalidTimestamp,
+    #[msg("The token account is not the owner's account holding the mint.")]
+    InvalidTokenAccount,
+    #[msg("The stake has ended.")]
+    StakeEnded,
+    #[msg("The reward pot is exhausted.")]
+    RewardPotExhausted,
+    #[msg("This ticket does not satisfy the quest requirement.")]
+    QuestNotEligible,
+    #[msg("Too many gid remappings.")]
+    TooManyGidRemaps,
+    #[msg("The mint is not on the stake's allowlist.")]
+    MintNotAllowed,
+    #[msg("Milestone metadata exceeds the Token Metadata limits.")]
+    MilestoneMetadataTooLong,
+    #[msg("The owner has not claimed enough rewards for this milestone.")]
+    MilestoneNotReached,
+    #[msg("The destination token account is not owned by the given owner or can be moved by others.")]
+    InvalidDestination,
+    #[msg("Crank interval must not be negative.")]
+    InvalidCrankInterval,
+    #[msg("The crank claimed too recently.")]
+    CrankTooEarly,
+    #[msg("The ticket is not pending activation.")]
+    TicketNotPending,
+    #[msg("The mint has not been held for the minimum hold time.")]
+    HoldTimeNotReached,
+    #[msg("An account needed to pay the enrollment fee was not provided.")]
+    MissingEnrollmentFeeAccount,
+    #[msg("Stake metadata uri is too long.")]
+    StakeMetadataUriTooLong,
+    #[msg("The mint is not an NFT.")]
+    NotAnNFT,
+    #[msg("The rent pool cannot cover the rent.")]
+    RentPoolDepleted,
+    #[msg("Rent must be returned to the rent pool if it exists, otherwise to the owner.")]
+    InvalidRentReceiver,
+    #[msg("The ticket's rent has already been paid.")]
+    RentAlreadySponsored,
+    #[msg("The ticket has not been withdrawn.")]
+    TicketNotWithdrawn,
+    #[msg("The rollup still has mints or unclaimed rewards.")]
+    RollupNotEmpty,
+    #[msg("The reward mint account is missing.")]
+    MissingRewardMint,
+    #[msg("Burn basis points must not exceed 10000.")]
+    InvalidBurnBps,
+    #[msg("A gid range must not end before it starts.")]
+    InvalidGidRange,
+    #[msg("The stake has minted its rewards cap for this epoch.")]
+    RewardEmissionCapReached,
+    #[msg("Invalid claim window.")]
+    InvalidClaimWindow,
+    #[msg("Rewards cannot be claimed outside the stake's claim window.")]
+    ClaimWindowClosed,
+    #[msg("The ticket is frozen.")]
+    TicketFrozen,
+    #[msg("The ticket is not frozen.")]
+    TicketNotFrozen,
+    #[msg("The ticket is not enrolled.")]
+    TicketNotEnrolled,
+    #[msg("The owner is not on the stake's participant allowlist.")]
+    ParticipantNotAllowed,
+    #[msg("The stake has no Merkle participant allowlist.")]
+    NoMerkleAllowlist,
+    #[msg("Invalid Merkle proof.")]
+    InvalidMerkleProof,
+    #[msg("The stake has no yield venue.")]
+    YieldVenueNotSet,
+    #[msg("The program is not the stake's yield venue.")]
+    YieldVenueMismatch,
+    #[msg("Rewards are deposited in the yield venue and must be recalled first.")]
+    RewardsDeposited,
+    #[msg("The yield venue moved more rewards than deposited.")]
+    YieldDepositExceeded,
+    #[msg("The yield venue moved rewards the wrong way.")]
+    InvalidYieldTransfer,
+    #[msg("Rewards of this stake may only be paid to the owner's associated token account.")]
+    DestinationNotOwnerAta,
+    #[msg("The smart wallet is already an admin of the stake.")]
+    SponsorAlreadyAdded,
+    #[msg("The stake has too many sponsors.")]
+    TooManySponsors,
+    #[msg("The smart wallet is not a sponsor of the stake.")]
+    SponsorNotFound,
+    #[msg("The smart wallet may not administer the stake.")]
+    NotStakeAdmin,
+    #[msg("The rollup of the ticket's gid was not passed.")]
+    MissingRollup,
+    #[msg("Too many viewers.")]
+    TooManyViewers,
+    #[msg("The stake already has sealed parameters.")]
+    SealedParamsPending,
+    #[msg("Sealed parameters are too long.")]
+    SealedParamsTooLong,
+    #[msg("The stake has no sealed parameters.")]
+    NoSealedParams,
+    #[msg("The parameters do not match the commitment.")]
+    InvalidReveal,
+    #[msg("The stake already has a bonus budget.")]
+    BonusBudgetAlreadyCreated,
+    #[msg("The stake has no bonus budget.")]
+    NoBonusBudget,
+    #[msg("The account is not the vault of the bonus budget.")]
+    InvalidBonusVault,
+    #[msg("The bonus budget is exhausted.")]
+    BonusBudgetExhausted,
+    #[msg("The same rollup was passed more than once.")]
+    DuplicateRollup,
+}
+
+impl From<StakeErrorCode> for crate::Error {
+    fn from(e: StakeErrorCode) -> Self {
+        ProgramError::from(e).into()
+    }
+}
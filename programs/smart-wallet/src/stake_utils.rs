@@ -0,0 +1,1023 @@
+use crate::*;
+use solana_program::keccak;
+
+impl Stake {
+    /// Derives the address of the [Stake] of a [SmartWallet] at `index`.
+    pub fn address(smart_wallet: Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[STAKE_SEED, &smart_wallet.to_bytes(), &index.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Epoch the [Stake] started at.
+    pub fn genesis(&self) -> Result<i64> {
+        let bytes: [u8; 8] = self.genesis_epoch.clone().try_into().map_err(|_| StakeErrorCode::InvalidTimestamp)?;
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    /// Epoch the [Stake] ends at.
+    pub fn ends_at(&self) -> Result<i64> {
+        Ok(unwrap_int!(self.genesis()?.checked_add(self.duration.into())))
+    }
+
+    /// Gid the given gid is treated as, after applying [Stake::gid_remaps].
+    pub fn resolve_gid(&self, gid: u16) -> u16 {
+        self.gid_remaps
+            .iter()
+            .find(|remap| remap.from == gid)
+            .map_or(gid, |remap| remap.to)
+    }
+
+    /// Returns true if the gid, after remapping, is protected, either listed in
+    /// [Stake::protected_gids] or within one of [Stake::protected_gid_ranges].
+    pub fn is_protected(&self, gid: u16) -> bool {
+        let gid = self.resolve_gid(gid);
+        self.protected_gids.contains(&gid)
+            || self.protected_gid_ranges.iter().any(|range| range.contains(gid))
+    }
+
+    /// Checks that the mint is an NFT: a supply of one and no decimals. If
+    /// [Stake::require_master_edition] is set, its master edition is looked up in `accounts`.
+    pub fn check_nft(&self, mint: &Account<Mint>, accounts: &[AccountInfo]) -> ProgramResult {
+        require!(mint.supply == 1 && mint.decimals == 0, StakeErrorCode::NotAnNFT);
+        if self.require_master_edition {
+            require!(
+                accounts
+                    .iter()
+                    .any(|info| token_metadata::is_master_edition(info, mint.key())),
+                StakeErrorCode::NotAnNFT
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that the mint may be registered, looking up its [AllowedMint]
+    /// in `accounts` if [Stake::mint_allowlist] is set.
+    pub fn check_mint_allowed(&self, stake: Pubkey, mint: Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        if !self.mint_allowlist {
+            return Ok(());
+        }
+        let allowed = accounts.iter().any(|info| {
+            Account::<AllowedMint>::try_from(info)
+                .map(|allowed| allowed.stake == stake && allowed.mint == mint)
+                .unwrap_or(false)
+        });
+        require!(allowed, StakeErrorCode::MintNotAllowed);
+        Ok(())
+    }
+
+    /// Checks that the owner may register, looking up their [AllowedParticipant]
+    /// in `accounts` if [Stake::participant_allowlist] is set.
+    pub fn check_participant_allowed(
+        &self,
+        stake: Pubkey,
+        owner: Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        if self.participant_allowlist.is_none() {
+            return Ok(());
+        }
+        let allowed = accounts.iter().any(|info| {
+            Account::<AllowedParticipant>::try_from(info)
+                .map(|allowed| allowed.stake == stake && allowed.owner == owner)
+                .unwrap_or(false)
+        });
+        require!(allowed, StakeErrorCode::ParticipantNotAllowed);
+        Ok(())
+    }
+
+    /// Checks that `owner` may register `mint` in the [Stake] at `stake`: that it is an
+    /// NFT, allowed by [Stake::mint_allowlist], and that the owner is allowed by
+    /// [Stake::participant_allowlist], looking up the accounts proving it in `accounts`.
+    pub fn check_registrable(
+        &self,
+        stake: Pubkey,
+        mint: &Account<Mint>,
+        owner: Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        self.check_nft(mint, accounts)?;
+        self.check_mint_allowed(stake, mint.key(), accounts)?;
+        self.check_participant_allowed(stake, owner, accounts)
+    }
+
+    /// Reward earned per ticket per second under the [RewardCurve], as a Q64.64
+    /// fixed-point number so rates below one unit per second do not round to zero.
+    pub fn reward_rate(&self) -> Result<u128> {
+        match self.reward_curve {
+            RewardCurve::Fixed { rate } => Ok(u128::from(rate) << REWARD_RATE_FRACTIONAL_BITS),
+            RewardCurve::InverseSupply { max_rate } => {
+                if self.duration <= 0 || self.reward_pot <= 0 {
+                    return Ok(0);
+                }
+                let pot = (self.reward_pot as u128) << REWARD_RATE_FRACTIONAL_BITS;
+                let per_second = unwrap_int!(pot.checked_div(self.duration as u128));
+                let rate = unwrap_int!(per_second.checked_div(self.total_mints.max(1).into()));
+                if max_rate == 0 {
+                    Ok(rate)
+                } else {
+                    Ok(rate.min(u128::from(max_rate) << REWARD_RATE_FRACTIONAL_BITS))
+                }
+            }
+        }
+    }
+
+    /// Rewards accrued by `mints` tickets over `elapsed` seconds at the current rate,
+    /// on top of the fractional `dust` carried over from earlier credits, or less it
+    /// if `dust_owed`. Returns the whole rewards, rounded by [Stake::reward_rounding],
+    /// and the new dust and whether it is owed.
+    pub fn accrued_rewards(
+        &self,
+        mints: u32,
+        elapsed: i64,
+        dust: u64,
+        dust_owed: bool,
+    ) -> Result<(u64, u64, bool)> {
+        if elapsed <= 0 {
+            return Ok((0, dust, dust_owed));
+        }
+        let rate = self.reward_rate()?;
+        let ticket_seconds = unwrap_int!((elapsed as u64).checked_mul(mints.into()));
+        let whole = unwrap_int!(((rate >> REWARD_RATE_FRACTIONAL_BITS) as u64).checked_mul(ticket_seconds));
+        let carried = if dust_owed { 0 } else { dust };
+        // Both factors are below 2^64, so this cannot overflow.
+        let fraction = u128::from(rate as u64) * u128::from(ticket_seconds) + u128::from(carried);
+        let mut amount = unwrap_int!(whole.checked_add((fraction >> REWARD_RATE_FRACTIONAL_BITS) as u64));
+        let mut remainder = fraction as u64;
+        if dust_owed {
+            if remainder >= dust {
+                remainder -= dust;
+            } else if amount > 0 {
+                amount -= 1;
+                remainder = remainder.wrapping_sub(dust);
+            } else {
+                return Ok((0, dust - remainder, true));
+            }
+        }
+        Ok(self.reward_rounding.round(amount, remainder))
+    }
+
+    /// Credits the rewards accrued by the [Rollup] since its last epoch and moves
+    /// its epoch to `now`. Credits never exceed the remaining reward pot; dust is
+    /// dropped once they do. Returns the amount credited.
+    pub fn credit_rollup(&mut self, rollup: &mut Rollup, now: i64) -> Result<u64> {
+        let elapsed = unwrap_int!(now.checked_sub(rollup.last_epoch()?));
+        let remaining = self.remaining_pot();
+        let (accrued, dust, dust_owed) = self.accrued_rewards(
+            rollup.mints,
+            elapsed,
+            rollup.accrued_dust,
+            rollup.dust_owed,
+        )?;
+        let (amount, dust, dust_owed) = if accrued > remaining {
+            (remaining, 0, false)
+        } else {
+            (accrued, dust, dust_owed)
+        };
+
+        rollup.accrued = unwrap_int!(rollup.accrued.checked_add(amount));
+        rollup.accrued_dust = dust;
+        rollup.dust_owed = dust_owed;
+        rollup.timestamp = now.to_le_bytes().to_vec();
+        self.rewards_credited = unwrap_int!(self.rewards_credited.checked_add(amount));
+        Ok(amount)
+    }
+
+    /// Moves the epochs of `ticket` and its [Rollup] to `timestamp`, crediting the
+    /// rollup up to it first. `timestamp` must be positive, so the ticket stays
+    /// enrolled, and between the rollup's epoch and `now`, so no rewards are credited
+    /// twice or ahead of time.
+    pub fn set_entity_epoch(
+        &mut self,
+        ticket: &mut Ticket,
+        rollup: &mut Rollup,
+        timestamp: i64,
+        now: i64,
+    ) -> ProgramResult {
+        require!(
+            timestamp > 0 && timestamp <= now && timestamp >= rollup.last_epoch()?,
+            StakeErrorCode::InvalidTimestamp
+        );
+        self.credit_rollup(rollup, timestamp)?;
+        ticket.enrollment_epoch = timestamp.to_le_bytes().to_vec();
+        Ok(())
+    }
+
+    /// Rewards that may still be credited: unbounded if rewards are minted.
+    pub fn remaining_pot(&self) -> u64 {
+        if self.reward_emission.is_some() {
+            return u64::MAX;
+        }
+        (self.reward_pot.max(0) as u64).saturating_sub(self.rewards_credited)
+    }
+
+    /// Adds `amount` to the [Stake::bonus_budget]. Returns the updated budget.
+    pub fn fund_bonus_budget(&mut self, amount: u64) -> Result<BonusBudget> {
+        let budget = self
+            .bonus_budget
+            .as_mut()
+            .ok_or(StakeErrorCode::NoBonusBudget)?;
+        budget.funded = unwrap_int!(budget.funded.checked_add(amount));
+        Ok(*budget)
+    }
+
+    /// Spends `amount` of the [Stake::bonus_budget] on a bonus paid from `vault`.
+    pub fn spend_bonus_budget(&mut self, vault: Pubkey, amount: u64) -> ProgramResult {
+        let budget = self
+            .bonus_budget
+            .as_mut()
+            .ok_or(StakeErrorCode::NoBonusBudget)?;
+        require!(budget.vault == vault, StakeErrorCode::InvalidBonusVault);
+        let spent = unwrap_int!(budget.spent.checked_add(amount));
+        require!(spent <= budget.funded, StakeErrorCode::BonusBudgetExhausted);
+        budget.spent = spent;
+        Ok(())
+    }
+
+    /// Reserves `amount` of the remaining reward pot for a bonus.
+    pub fn reserve_bonus(&mut self, amount: u64) -> ProgramResult {
+        let remaining = self.remaining_pot();
+        require!(amount <= remaining, StakeErrorCode::RewardPotExhausted);
+        self.rewards_credited = unwrap_int!(self.rewards_credited.checked_add(amount));
+        Ok(())
+    }
+
+    /// Limits a claim of `amount` to what the owner may still claim under
+    /// [Stake::max_rewards_per_owner]. Rewards above the cap are returned to the pot.
+    pub fn cap_claim(&mut self, owner_rewards: &OwnerRewards, amount: u64) -> Result<u64> {
+        if self.max_rewards_per_owner == 0 {
+            return Ok(amount);
+        }
+        let allowed = self
+            .max_rewards_per_owner
+            .saturating_sub(owner_rewards.claimed)
+            .min(amount);
+        let excess = unwrap_int!(amount.checked_sub(allowed));
+        self.rewards_credited = unwrap_int!(self.rewards_credited.checked_sub(excess));
+        Ok(allowed)
+    }
+
+    /// Charges the [Stake::enrollment_fee] to the payer, if one is set. The
+    /// [ProtocolConfig::protocol_fee_bps] share of it goes to the protocol.
+    ///
+    /// The destinations, the program moving the fee and, for SPL fees, the payer's
+    /// token account are looked up in `accounts`.
+    pub fn charge_enrollment_fee<'info>(
+        &self,
+        config: &ProtocolConfig,
+        payer: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+    ) -> ProgramResult {
+        let fee = match self.enrollment_fee {
+            Some(fee) if fee.amount > 0 => fee,
+            _ => return Ok(()),
+        };
+        let protocol_fee = config.protocol_fee(fee.amount)?;
+        let project_fee = unwrap_int!(fee.amount.checked_sub(protocol_fee));
+        let destination = accounts
+            .iter()
+            .find(|a| a.key() == fee.destination)
+            .ok_or(StakeErrorCode::MissingEnrollmentFeeAccount)?;
+        match fee.mint {
+            None => {
+                let system_program = accounts
+                    .iter()
+                    .find(|a| a.key() == System::id())
+                    .ok_or(StakeErrorCode::MissingEnrollmentFeeAccount)?;
+                let pay = |to: &AccountInfo<'info>, amount: u64| {
+                    solana_program::program::invoke(
+                        &solana_program::system_instruction::transfer(&payer.key(), &to.key(), amount),
+                        &[payer.clone(), to.clone(), system_program.clone()],
+                    )
+                };
+                pay(destination, project_fee)?;
+                if protocol_fee > 0 {
+                    let recipient = unwrap_or_err!(
+                        accounts.iter().find(|a| a.key() == config.fee_recipient),
+                        MissingProtocolFeeAccount
+                    );
+                    pay(recipient, protocol_fee)?;
+                }
+                Ok(())
+            }
+            Some(mint) => {
+                let token_program = accounts
+                    .iter()
+                    .find(|a| a.key() == token::ID)
+                    .ok_or(StakeErrorCode::MissingEnrollmentFeeAccount)?;
+                let token_account = |a: &AccountInfo<'info>, owner: Pubkey| {
+                    Account::<TokenAccount>::try_from(a)
+                        .map(|t| t.mint == mint && t.owner == owner)
+                        .unwrap_or(false)
+                };
+                let source = accounts
+                    .iter()
+                    .find(|a| a.key() != fee.destination && token_account(a, payer.key()))
+                    .ok_or(StakeErrorCode::MissingEnrollmentFeeAccount)?;
+                let pay = |to: &AccountInfo<'info>, amount: u64| {
+                    token::transfer(
+                        CpiContext::new(
+                            token_program.clone(),
+                            token::Transfer {
+                                from: source.clone(),
+                                to: to.clone(),
+                                authority: payer.clone(),
+                            },
+                        ),
+                        amount,
+                    )
+                };
+                pay(destination, project_fee)?;
+                if protocol_fee > 0 {
+                    let recipient = unwrap_or_err!(
+                        accounts
+                            .iter()
+                            .find(|a| a.key() != source.key() && token_account(a, config.fee_recipient)),
+                        MissingProtocolFeeAccount
+                    );
+                    pay(recipient, protocol_fee)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Enrolls the [Ticket] at `now`. It is counted in the [Rollup] immediately, or
+    /// left pending for [Stake::min_hold_seconds] if set.
+    pub fn enroll(&mut self, ticket: &mut Ticket, rollup: &mut Rollup, now: i64) -> ProgramResult {
+        ticket.enrollment_epoch = now.to_le_bytes().to_vec();
+        if self.min_hold_seconds > 0 {
+            ticket.pending_until = unwrap_int!(now.checked_add(self.min_hold_seconds));
+            return Ok(());
+        }
+        ticket.pending_until = 0;
+        self.count_ticket(rollup, now)
+    }
+
+    /// Settles the [Rollup] and counts one more mint in it.
+    pub fn count_ticket(&mut self, rollup: &mut Rollup, now: i64) -> ProgramResult {
+        self.credit_rollup(rollup, now)?;
+        rollup.mints = unwrap_int!(rollup.mints.checked_add(1));
+        self.total_mints = unwrap_int!(self.total_mints.checked_add(1));
+        Ok(())
+    }
+
+    /// Settles the [Rollup] and stops counting `ticket` in it. Does nothing if the
+    /// ticket is withdrawn, pending or frozen, as it was not counted.
+    pub fn uncount_ticket(
+        &mut self,
+        ticket: &Ticket,
+        rollup: &mut Rollup,
+        now: i64,
+    ) -> ProgramResult {
+        if ticket.enrollment()? <= 0 || ticket.pending_until != 0 {
+            return Ok(());
+        }
+        self.credit_rollup(rollup, now)?;
+        rollup.mints = unwrap_int!(rollup.mints.checked_sub(1));
+        self.total_mints = unwrap_int!(self.total_mints.checked_sub(1));
+        Ok(())
+    }
+
+    /// Settles the [Rollup] and takes its accrued rewards, capped by
+    /// [Stake::max_rewards_per_owner]. Returns the rewards to pay out.
+    pub fn claim_rollup(
+        &mut self,
+        rollup: &mut Rollup,
+        owner_rewards: &mut OwnerRewards,
+        now: i64,
+    ) -> Result<u64> {
+        self.credit_rollup(rollup, now)?;
+        let rewards = self.cap_claim(owner_rewards, rollup.accrued)?;
+        rollup.accrued = 0;
+        owner_rewards.claimed = unwrap_int!(owner_rewards.claimed.checked_add(rewards));
+        Ok(rewards)
+    }
+
+    /// Pays a bonus of `amount` from the vault of the [Stake::bonus_budget]. Nothing is
+    /// burned or minted. Fails outside the [Stake::claim_window].
+    pub fn pay_bonus<'info>(
+        &self,
+        stake: AccountInfo<'info>,
+        bonus_vault: AccountInfo<'info>,
+        destination: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        amount: u64,
+        now: i64,
+    ) -> ProgramResult {
+        if let Some(window) = self.claim_window {
+            require!(window.is_open(now), StakeErrorCode::ClaimWindowClosed);
+        }
+        let smart_wallet = self.smart_wallet.to_bytes();
+        let index = self.index.to_le_bytes();
+        let bump = [self.bump];
+        let seeds: &[&[&[u8]]] = &[&[STAKE_SEED, &smart_wallet, &index, &bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program,
+                token::Transfer {
+                    from: bonus_vault,
+                    to: destination,
+                    authority: stake,
+                },
+                seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Pays out a claimed reward of `amount` to `owner`, burning [Stake::burn_bps] of it
+    /// unless their [FeeExemption] is in `accounts`. The rest is minted if
+    /// [Stake::reward_emission] is set, otherwise transferred from the vault. The reward
+    /// mint must be passed in `accounts` if anything is burned or minted. Fails outside
+    /// the [Stake::claim_window]. Returns the amount burned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_rewards<'info>(
+        &mut self,
+        stake: AccountInfo<'info>,
+        reward_vault: AccountInfo<'info>,
+        destination: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        owner: Pubkey,
+        amount: u64,
+        now: i64,
+    ) -> Result<u64> {
+        if let Some(window) = self.claim_window {
+            require!(window.is_open(now), StakeErrorCode::ClaimWindowClosed);
+        }
+        let burn_bps = if FeeExemption::is_exempt(stake.key(), owner, accounts) {
+            0
+        } else {
+            self.burn_bps
+        };
+        let burned = unwrap_int!((amount as u128)
+            .checked_mul(burn_bps as u128)
+            .and_then(|v| v.checked_div(10_000))
+            .and_then(|v| u64::try_from(v).ok()));
+        let paid = unwrap_int!(amount.checked_sub(burned));
+        let smart_wallet = self.smart_wallet.to_bytes();
+        let index = self.index.to_le_bytes();
+        let bump = [self.bump];
+        let seeds: &[&[&[u8]]] = &[&[STAKE_SEED, &smart_wallet, &index, &bump]];
+
+        if let Some(emission) = self.reward_emission.as_mut() {
+            // The burned share is simply never minted.
+            emission.record(paid, now)?;
+            if paid > 0 {
+                let mint = accounts
+                    .iter()
+                    .find(|a| a.key() == self.reward_mint)
+                    .ok_or(StakeErrorCode::MissingRewardMint)?;
+                token::mint_to(
+                    CpiContext::new_with_signer(
+                        token_program,
+                        token::MintTo {
+                            mint: mint.clone(),
+                            to: destination,
+                            authority: stake,
+                        },
+                        seeds,
+                    ),
+                    paid,
+                )?;
+            }
+            return Ok(burned);
+        }
+
+        require!(
+            !self.has_yield_deposit(),
+            StakeErrorCode::RewardsDeposited
+        );
+        if burned > 0 {
+            let mint = accounts
+                .iter()
+                .find(|a| a.key() == self.reward_mint)
+                .ok_or(StakeErrorCode::MissingRewardMint)?;
+            token::burn(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    token::Burn {
+                        mint: mint.clone(),
+                        to: reward_vault.clone(),
+                        authority: stake.clone(),
+                    },
+                    seeds,
+                ),
+                burned,
+            )?;
+        }
+        if paid > 0 {
+            self.transfer_rewards(stake, reward_vault, destination, token_program, paid)?;
+        }
+        Ok(burned)
+    }
+
+    /// Returns true if `smart_wallet` may fund the [Stake]: its own [SmartWallet] or
+    /// one of its [Stake::sponsors]. Only its own [SmartWallet] may change its
+    /// parameters.
+    pub fn is_admin(&self, smart_wallet: Pubkey) -> bool {
+        self.smart_wallet == smart_wallet
+            || self.sponsors.iter().any(|s| s.smart_wallet == smart_wallet)
+    }
+
+    /// Checks that rewards of `owner` may be paid to `destination`: with
+    /// [Stake::owner_ata_claims] set, only to their associated token account.
+    pub fn check_claim_destination(&self, owner: Pubkey, destination: Pubkey) -> ProgramResult {
+        if self.owner_ata_claims {
+            require!(
+                destination
+                    == anchor_spl::associated_token::get_associated_token_address(
+                        &owner,
+                        &self.reward_mint
+                    ),
+                StakeErrorCode::DestinationNotOwnerAta
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns true if rewards are deposited in the [Stake::yield_venue].
+    pub fn has_yield_deposit(&self) -> bool {
+        matches!(self.yield_venue, Some(venue) if venue.deposited > 0)
+    }
+
+    /// Invokes the [Stake::yield_venue] program with `data` and `accounts`, signed by
+    /// the [Stake]. No other account is passed on as a signer.
+    pub fn invoke_yield_venue<'info>(
+        &self,
+        stake: &AccountInfo<'info>,
+        venue_program: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        data: Vec<u8>,
+    ) -> ProgramResult {
+        let venue = self.yield_venue.ok_or(StakeErrorCode::YieldVenueNotSet)?;
+        require!(
+            venue_program.key() == venue.program_id,
+            StakeErrorCode::YieldVenueMismatch
+        );
+        let ix = solana_program::instruction::Instruction {
+            program_id: venue.program_id,
+            accounts: accounts
+                .iter()
+                .map(|account| solana_program::instruction::AccountMeta {
+                    pubkey: account.key(),
+                    is_signer: account.key() == stake.key(),
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data,
+        };
+        let mut account_infos = accounts.to_vec();
+        account_infos.push(venue_program.clone());
+        solana_program::program::invoke_signed(
+            &ix,
+            &account_infos,
+            &[&[
+                STAKE_SEED,
+                &self.smart_wallet.to_bytes(),
+                &self.index.to_le_bytes(),
+                &[self.bump],
+            ]],
+        )
+    }
+
+    /// Transfers `amount` of the reward token from the vault, signed by the [Stake].
+    pub fn transfer_rewards<'info>(
+        &self,
+        stake: AccountInfo<'info>,
+        reward_vault: AccountInfo<'info>,
+        destination: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        amount: u64,
+    ) -> ProgramResult {
+        let seeds: &[&[&[u8]]] = &[&[
+            STAKE_SEED,
+            &self.smart_wallet.to_bytes(),
+            &self.index.to_le_bytes(),
+            &[self.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program,
+                token::Transfer {
+                    from: reward_vault,
+                    to: destination,
+                    authority: stake,
+                },
+                seeds,
+            ),
+            amount,
+        )
+    }
+}
+
+impl Ticket {
+    /// Derives the address of the [Ticket] of a mint.
+    pub fn address(smart_wallet: Pubkey, mint: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[&System::id().to_bytes(), &smart_wallet.to_bytes(), &mint.to_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Epoch the ticket was enrolled at. Not positive if withdrawn.
+    pub fn enrollment(&self) -> Result<i64> {
+        let bytes: [u8; 8] = self.enrollment_epoch.clone().try_into().map_err(|_| StakeErrorCode::InvalidTimestamp)?;
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    /// Returns true if governance froze the ticket with
+    /// [crate::smart_wallet::freeze_ticket].
+    pub fn is_frozen(&self) -> bool {
+        self.pending_until == FROZEN_TICKET
+    }
+
+    /// Checks that this account is the [Ticket] PDA of its mint.
+    pub fn check_address(&self, smart_wallet: Pubkey, key: Pubkey) -> ProgramResult {
+        let address = Pubkey::create_program_address(
+            &[
+                &System::id().to_bytes(),
+                &smart_wallet.to_bytes(),
+                &self.mint.to_bytes(),
+                &[self.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| StakeErrorCode::InvalidBump)?;
+        require!(address == key, StakeErrorCode::AccountMismatch);
+        Ok(())
+    }
+}
+
+impl LegacyRollup {
+    /// Deserializes a [LegacyRollup] from an account owned by this program.
+    pub fn load(info: &AccountInfo) -> Result<LegacyRollup> {
+        require!(*info.owner == crate::ID, StakeErrorCode::AccountMismatch);
+        let data = info.try_borrow_data()?;
+        require!(data.len() >= 8 && data[..8] == Rollup::discriminator(), StakeErrorCode::AccountMismatch);
+        LegacyRollup::deserialize(&mut &data[8..]).map_err(|_| StakeErrorCode::AccountMismatch.into())
+    }
+
+    /// Checks that this account is the legacy [Rollup] PDA of the owner.
+    pub fn check_address(&self, smart_wallet: Pubkey, owner: Pubkey, key: Pubkey) -> ProgramResult {
+        let address = Pubkey::create_program_address(
+            &[
+                &smart_wallet.to_bytes(),
+                &owner.to_bytes(),
+                &self.gid.to_le_bytes(),
+                &[self.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| StakeErrorCode::InvalidBump)?;
+        require!(address == key, StakeErrorCode::AccountMismatch);
+        Ok(())
+    }
+}
+
+impl Quest {
+    /// Derives the address of the [Quest] of a [Stake] at `index`.
+    pub fn address(stake: Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[QUEST_SEED, &stake.to_bytes(), &index.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Checks that the [Ticket] and its [Rollup] satisfy the [QuestRequirement] at `now`.
+    pub fn check_eligible(
+        &self,
+        stake: &Stake,
+        ticket: &Ticket,
+        rollup: &Rollup,
+        now: i64,
+    ) -> ProgramResult {
+        let enrollment = ticket.enrollment()?;
+        require!(enrollment > 0 && ticket.pending_until == 0, StakeErrorCode::QuestNotEligible);
+        match self.requirement {
+            QuestRequirement::StakedThrough { start, end } => {
+                require!(enrollment <= start && now >= end, StakeErrorCode::QuestNotEligible);
+            }
+            QuestRequirement::GidStaked { gid, min_mints } => {
+                require!(
+                    stake.resolve_gid(ticket.gid) == gid && rollup.mints >= min_mints,
+                    StakeErrorCode::QuestNotEligible
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl QuestClaim {
+    /// Derives the address of the [QuestClaim] of a [Rollup] on a [Quest].
+    pub fn address(quest: Pubkey, rollup: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[QUEST_CLAIM_SEED, &quest.to_bytes(), &rollup.to_bytes()],
+            &crate::ID,
+        )
+    }
+}
+
+impl Milestone {
+    /// Derives the address of the [Milestone] of a [Stake] at `index`.
+    pub fn address(stake: Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[MILESTONE_SEED, &stake.to_bytes(), &index.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Checks that the badge metadata fits the Token Metadata limits.
+    pub fn check_metadata(name: &str, symbol: &str, uri: &str) -> ProgramResult {
+        require!(
+            name.len() <= token_metadata::MAX_NAME_LENGTH
+                && symbol.len() <= token_metadata::MAX_SYMBOL_LENGTH
+                && uri.len() <= token_metadata::MAX_URI_LENGTH,
+            StakeErrorCode::MilestoneMetadataTooLong
+        );
+        Ok(())
+    }
+}
+
+impl MilestoneClaim {
+    /// Derives the address of the [MilestoneClaim] of an owner on a [Milestone].
+    pub fn address(milestone: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                MILESTONE_CLAIM_SEED,
+                &milestone.to_bytes(),
+                &owner.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+}
+
+impl Rollup {
+    /// Derives the address of the [Rollup] of an owner's gid in a [Stake].
+    pub fn address(stake: Pubkey, owner: Pubkey, gid: u16) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[&stake.to_bytes(), &owner.to_bytes(), &gid.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Returns true if the account holds a [Rollup], judging by its discriminator.
+    pub fn is_rollup(info: &AccountInfo) -> bool {
+        match info.try_borrow_data() {
+            Ok(data) => data.starts_with(&Rollup::discriminator()),
+            Err(_) => false,
+        }
+    }
+
+    /// Epoch rewards were last credited at.
+    pub fn last_epoch(&self) -> Result<i64> {
+        let bytes: [u8; 8] = self.timestamp.clone().try_into().map_err(|_| StakeErrorCode::InvalidTimestamp)?;
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    /// Checks that this account is the [Rollup] PDA of its stake, owner and gid.
+    pub fn check_address(&self, key: Pubkey) -> ProgramResult {
+        let address = Pubkey::create_program_address(
+            &[
+                &self.stake.to_bytes(),
+                &self.owner.to_bytes(),
+                &self.gid.to_le_bytes(),
+                &[self.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| StakeErrorCode::InvalidBump)?;
+        require!(address == key, StakeErrorCode::AccountMismatch);
+        Ok(())
+    }
+
+    /// Checks that no two [Rollup]s of an owner in a [Stake] share a gid, i.e. that
+    /// no rollup was passed twice. Each copy is written back on exit, so a duplicate
+    /// would overwrite the mints taken out of the other.
+    pub fn check_distinct<'a>(rollups: impl IntoIterator<Item = &'a Rollup>) -> ProgramResult {
+        let mut gids: Vec<u16> = vec![];
+        for rollup in rollups {
+            require!(!gids.contains(&rollup.gid), StakeErrorCode::DuplicateRollup);
+            gids.push(rollup.gid);
+        }
+        Ok(())
+    }
+}
+
+impl OwnerRewards {
+    /// Derives the address of the [OwnerRewards] of an owner in a [Stake].
+    pub fn address(stake: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[OWNER_REWARDS_SEED, &stake.to_bytes(), &owner.to_bytes()],
+            &crate::ID,
+        )
+    }
+}
+
+impl CrankPermission {
+    /// Derives the address of the [CrankPermission] of an owner in a [Stake].
+    pub fn address(stake: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[CRANK_PERMISSION_SEED, &stake.to_bytes(), &owner.to_bytes()],
+            &crate::ID,
+        )
+    }
+}
+
+impl AllowedMint {
+    /// Derives the address of the [AllowedMint] of a mint in a [Stake].
+    pub fn address(stake: Pubkey, mint: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[ALLOWED_MINT_SEED, &stake.to_bytes(), &mint.to_bytes()],
+            &crate::ID,
+        )
+    }
+}
+
+impl RentPool {
+    /// Derives the address of the [RentPool] of a [SmartWallet].
+    pub fn address(smart_wallet: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RENT_POOL_SEED, &smart_wallet.to_bytes()], &crate::ID)
+    }
+
+    /// Moves the rent of an account of `space` bytes out of the pool into `account`,
+    /// keeping the pool itself rent exempt. Returns the lamports moved.
+    pub fn sponsor(&mut self, pool: &AccountInfo, account: &AccountInfo, space: usize) -> Result<u64> {
+        let rent = Rent::get()?;
+        let lamports = rent.minimum_balance(space);
+        let available = pool
+            .lamports()
+            .saturating_sub(rent.minimum_balance(RentPool::space()));
+        require!(available >= lamports, StakeErrorCode::RentPoolDepleted);
+
+        **pool.try_borrow_mut_lamports()? = unwrap_int!(pool.lamports().checked_sub(lamports));
+        **account.try_borrow_mut_lamports()? = unwrap_int!(account.lamports().checked_add(lamports));
+        self.outstanding = unwrap_int!(self.outstanding.checked_add(lamports));
+        Ok(lamports)
+    }
+
+    /// Checks where the rent of a closed account goes: to the [RentPool] if the wallet
+    /// has one, otherwise to the owner. Records the lamports returned to the pool.
+    pub fn reclaim(rent_pool: &AccountInfo, receiver: Pubkey, owner: Pubkey, lamports: u64) -> ProgramResult {
+        if *rent_pool.owner != crate::ID || rent_pool.data_is_empty() {
+            require!(receiver == owner, StakeErrorCode::InvalidRentReceiver);
+            return Ok(());
+        }
+        require!(receiver == rent_pool.key(), StakeErrorCode::InvalidRentReceiver);
+        let mut data = rent_pool.try_borrow_mut_data()?;
+        let mut pool = RentPool::try_deserialize(&mut &data[..])?;
+        pool.outstanding = pool.outstanding.saturating_sub(lamports);
+        pool.try_serialize(&mut &mut data[..])
+    }
+}
+
+impl AllowedParticipant {
+    /// Derives the address of the [AllowedParticipant] of an owner in a [Stake].
+    pub fn address(stake: Pubkey, owner: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                ALLOWED_PARTICIPANT_SEED,
+                &stake.to_bytes(),
+                &owner.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+}
+
+impl FeeExemption {
+    /// Derives the address of the [FeeExemption] of an account in a [Stake].
+    pub fn address(stake: Pubkey, account: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[FEE_EXEMPTION_SEED, &stake.to_bytes(), &account.to_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Returns true if the [FeeExemption] of `account` in the [Stake] is in `accounts`.
+    pub fn is_exempt(stake: Pubkey, account: Pubkey, accounts: &[AccountInfo]) -> bool {
+        accounts.iter().any(|info| {
+            Account::<FeeExemption>::try_from(info)
+                .map(|exemption| exemption.stake == stake && exemption.account == account)
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// Returns true if `proof` shows that `owner` is a leaf of the Merkle tree of
+/// `root`. Pairs of nodes are hashed in sorted order.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], owner: Pubkey) -> bool {
+    let leaf = keccak::hash(&owner.to_bytes()).to_bytes();
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == root
+}
+
+impl SealedParams {
+    /// Checks that `params` hash to the [SealedParams::commitment].
+    pub fn check_reveal(&self, params: &[u8]) -> ProgramResult {
+        require!(
+            keccak::hash(params).to_bytes() == self.commitment,
+            StakeErrorCode::InvalidReveal
+        );
+        Ok(())
+    }
+}
+
+impl RewardRounding {
+    /// Rounds `amount` plus `fraction`, in units of 2^-64, to a whole amount. Returns it
+    /// with the fraction left over, and whether that fraction is owed because the
+    /// amount was rounded up.
+    pub fn round(&self, amount: u64, fraction: u64) -> (u64, u64, bool) {
+        let round_up = match self {
+            RewardRounding::Floor => false,
+            RewardRounding::Round => fraction >= 1 << 63,
+            RewardRounding::Ceil => fraction > 0,
+        };
+        match amount.checked_add(1) {
+            Some(rounded) if round_up => (rounded, fraction.wrapping_neg(), true),
+            _ => (amount, fraction, false),
+        }
+    }
+}
+
+impl ClaimWindow {
+    /// Checks that the window is well formed.
+    pub fn validate(&self) -> ProgramResult {
+        let valid = match *self {
+            ClaimWindow::Periodic { period, length, .. } => 0 < length && length <= period,
+            ClaimWindow::MonthEnd { days } => (1..=28).contains(&days),
+        };
+        require!(valid, StakeErrorCode::InvalidClaimWindow);
+        Ok(())
+    }
+
+    /// Returns true if claims are open at `now`.
+    pub fn is_open(&self, now: i64) -> bool {
+        match *self {
+            ClaimWindow::Periodic {
+                start,
+                period,
+                length,
+            } => now >= start && (now - start) % period < length,
+            ClaimWindow::MonthEnd { days } => {
+                let (year, month, day) = civil_date(now);
+                day > days_in_month(year, month) - i64::from(days)
+            }
+        }
+    }
+}
+
+/// Converts a unix timestamp to a UTC (year, month, day) date.
+fn civil_date(timestamp: i64) -> (i64, i64, i64) {
+    // Days since 0000-03-01, so leap days fall at the end of each year.
+    let z = timestamp.div_euclid(SECONDS_PER_DAY) + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Number of days in a month of the Gregorian calendar.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+impl RewardEmission {
+    /// Counts `amount` minted at `now` against the cap, starting a new epoch if the
+    /// current one has ended.
+    pub fn record(&mut self, amount: u64, now: i64) -> ProgramResult {
+        if now >= unwrap_int!(self.epoch_start.checked_add(self.epoch_duration)) {
+            self.epoch_start = now;
+            self.minted_in_epoch = 0;
+        }
+        let minted = unwrap_int!(self.minted_in_epoch.checked_add(amount));
+        require!(minted <= self.max_per_epoch, StakeErrorCode::RewardEmissionCapReached);
+        self.minted_in_epoch = minted;
+        Ok(())
+    }
+}
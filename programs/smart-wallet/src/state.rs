@@ -1,5 +1,6 @@
 //! State structs.
 
+use crate::MAX_EXECUTORS;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
 
@@ -15,7 +16,8 @@ pub struct SmartWallet {
     pub threshold: u64,
     /// Minimum delay between approval and execution.
     pub minimum_delay: i64,
-    /// Time after the ETA until a transaction expires.
+    /// Time after the ETA until a transaction expires. [crate::NO_GRACE_PERIOD]
+    /// if transactions never expire.
     pub grace_period: i64,
 
     /// Sequence of the ownership set.
@@ -26,8 +28,40 @@ pub struct SmartWallet {
     /// Owners of the [SmartWallet].
     pub owners: Vec<Pubkey>,
 
+    /// Lamports paid to the executor of a transaction, if enabled.
+    pub executor_reimbursement: Option<ExecutorReimbursement>,
+    /// Owners whose approvals do not count and who cannot execute.
+    pub suspended_owners: Vec<Pubkey>,
+    /// Accounts allowed to execute transactions. Anyone may execute if empty.
+    pub executor_allowlist: Vec<Pubkey>,
+    /// Sequence number of the last event emitted for this wallet.
+    pub event_seq: u64,
+    /// If true, only programs in [ProtocolConfig::audited_programs] may be invoked.
+    pub audited_programs_only: bool,
+    /// Number of executions by this [SmartWallet].
+    pub num_executed: u64,
+    /// When this [SmartWallet] last executed. 0 if never.
+    pub last_executed_at: i64,
+    /// When an owner last approved a [Transaction]. 0 if never.
+    pub last_approved_at: i64,
+    /// If set, accounts other than the owners may propose by holding enough of a token.
+    pub proposer_gate: Option<ProposerGate>,
+    /// Lamports locked by the proposer of each [Transaction], refunded once it is
+    /// executed and slashed to the treasury if it expires. 0 if disabled.
+    pub proposal_bond: u64,
+    /// If set, caps how many [Transaction]s each proposer may propose per window.
+    pub proposal_rate_limit: Option<ProposalRateLimit>,
+    /// If set, may veto [Transaction]s queued behind an ETA before it is reached.
+    pub veto_authority: Option<Pubkey>,
+    /// If true, every execution of a [Transaction] writes an [ExecutionReceipt].
+    pub execution_receipts: bool,
+    /// Highest [Transaction] index processed by the expiry crank, through
+    /// [crate::smart_wallet::mark_expired] or [crate::smart_wallet::reap_transaction],
+    /// so cranks can resume after it.
+    pub expiry_cursor: u64,
+
     /// Extra space for program upgrades.
-    pub reserved: [u64; 16],
+    pub reserved: [u64; 2],
 }
 
 impl SmartWallet {
@@ -37,9 +71,147 @@ impl SmartWallet {
             + std::mem::size_of::<SmartWallet>()
             + 4 // 4 = the Vec discriminator
             + std::mem::size_of::<Pubkey>() * (max_owners as usize)
+            + 4 // suspended_owners Vec discriminator
+            + std::mem::size_of::<Pubkey>() * (max_owners as usize)
+            + 4 // executor_allowlist Vec discriminator
+            + std::mem::size_of::<Pubkey>() * MAX_EXECUTORS
     }
 }
 
+/// Reimbursement of the executor's fees out of a wallet-derived lamport vault.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExecutorReimbursement {
+    /// Index of the wallet-derived address holding the reimbursement lamports.
+    pub vault_index: u64,
+    /// Bump seed of the vault address.
+    pub vault_bump: u8,
+    /// Lamports paid to cover the executor's transaction fees.
+    pub fee_lamports: u64,
+    /// Additional lamports tipped to the executor.
+    pub tip_lamports: u64,
+}
+
+impl ExecutorReimbursement {
+    /// Total lamports paid to the executor per execution.
+    pub fn total_lamports(&self) -> Option<u64> {
+        self.fee_lamports.checked_add(self.tip_lamports)
+    }
+}
+
+/// Token balance allowing an account that is not an owner to propose [Transaction]s.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProposerGate {
+    /// Mint of the governance token.
+    pub mint: Pubkey,
+    /// Minimum balance the proposer must hold when proposing.
+    pub min_amount: u64,
+}
+
+/// Cap on the [Transaction]s a proposer may propose within a window.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProposalRateLimit {
+    /// Maximum proposals per proposer per window.
+    pub max_proposals: u32,
+    /// Length of the window, in seconds.
+    pub window: i64,
+}
+
+/// Protocol-wide defaults and caps consulted by handlers, so they can be tuned
+/// without a program upgrade. Created by the program's upgrade authority.
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct ProtocolConfig {
+    /// Bump seed.
+    pub bump: u8,
+    /// Account allowed to change the config.
+    pub authority: Pubkey,
+    /// Maximum `max_owners` of a new [SmartWallet].
+    pub max_owners: u8,
+    /// Maximum number of instructions executed at once.
+    pub max_instructions: u8,
+    /// Grace period of a new [SmartWallet].
+    pub default_grace_period: i64,
+    /// Share of enrollment fees paid to [ProtocolConfig::fee_recipient], in basis points.
+    pub protocol_fee_bps: u16,
+    /// Receives protocol fees, directly for lamports or via its token accounts.
+    pub fee_recipient: Pubkey,
+    /// Multisig allowed to pause the protocol.
+    pub security_council: Pubkey,
+    /// If true, execution and reward claims are halted. Withdrawals still work.
+    pub paused: bool,
+    /// Programs that wallets in audited-programs mode may invoke.
+    pub audited_programs: Vec<Pubkey>,
+
+    /// Extra space for new parameters.
+    pub reserved: [u64; 8],
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ProtocolConfig {
+    /// Parameters used until a [ProtocolConfig] is created.
+    fn default() -> Self {
+        ProtocolConfig {
+            bump: 0,
+            authority: Pubkey::default(),
+            max_owners: u8::MAX,
+            max_instructions: u8::MAX,
+            default_grace_period: crate::DEFAULT_GRACE_PERIOD,
+            protocol_fee_bps: 0,
+            fee_recipient: Pubkey::default(),
+            security_council: Pubkey::default(),
+            paused: false,
+            audited_programs: vec![],
+            reserved: [0; 8],
+        }
+    }
+}
+
+impl ProtocolConfig {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // authority
+            1 + // max_owners
+            1 + // max_instructions
+            8 + // default_grace_period
+            2 + // protocol_fee_bps
+            32 + // fee_recipient
+            32 + // security_council
+            1 + // paused
+            4 + 32 * crate::MAX_AUDITED_PROGRAMS + // audited_programs
+            8 * 8 // reserved
+    }
+}
+
+/// Tunable parameters of the [ProtocolConfig].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProtocolParams {
+    pub max_owners: u8,
+    pub max_instructions: u8,
+    pub default_grace_period: i64,
+    pub protocol_fee_bps: u16,
+    pub fee_recipient: Pubkey,
+}
+
+/// A change to the [ProtocolConfig] made by its governing [SmartWallet] with
+/// [crate::smart_wallet::govern_protocol_config].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProtocolConfigUpdate {
+    /// Sets [ProtocolConfig::protocol_fee_bps] and [ProtocolConfig::fee_recipient].
+    SetProtocolFee {
+        protocol_fee_bps: u16,
+        fee_recipient: Pubkey,
+    },
+    /// Sets [ProtocolConfig::paused]. Governed by the [ProtocolConfig::security_council].
+    SetPaused { paused: bool },
+    /// Designates the [ProtocolConfig::security_council].
+    SetSecurityCouncil { security_council: Pubkey },
+    /// Adds a program to [ProtocolConfig::audited_programs].
+    AddAuditedProgram { program: Pubkey },
+    /// Removes a program from [ProtocolConfig::audited_programs].
+    RemoveAuditedProgram { program: Pubkey },
+}
+
 #[account]
 #[derive(Debug, Default, PartialEq)]
 pub struct Transaction {
@@ -67,13 +239,66 @@ pub struct Transaction {
     pub executor: Pubkey,
     /// When the transaction was executed. -1 if not executed.
     pub executed_at: i64,
+    /// Whether the [Transaction] was marked as expired by [crate::smart_wallet::mark_expired].
+    pub expired: bool,
+    /// Lamports of [SmartWallet::proposal_bond] locked in this account by the proposer.
+    pub bond: u64,
+    /// If set, [Transaction::instructions] is empty and the instructions, whose hash
+    /// this is, are revealed on execution.
+    pub instructions_hash: Option<[u8; 32]>,
+    /// If set, the only accounts the instructions may reference as writable.
+    pub writable_scope: Option<Vec<Pubkey>>,
+    /// Number of instructions that have run, when execution is split across several
+    /// calls. Execution resumes from here, so no instruction runs twice.
+    pub instructions_executed: u16,
+    /// If true, only the proposer may execute the [Transaction].
+    pub proposer_executes: bool,
+    /// `instruction_signers[index][i]` is true iff `[SmartWallet]::owners[index]` approved
+    /// instruction `i` alone. Empty until an owner approves a subset of the instructions.
+    pub instruction_signers: Vec<Vec<bool>>,
+    /// Whether the [Transaction] was cancelled by [crate::smart_wallet::cancel_transaction]
+    /// or vetoed by [crate::smart_wallet::veto_transaction].
+    pub cancelled: bool,
+    /// `approval_memos[index]` is the rationale `[SmartWallet]::owners[index]` gave when
+    /// approving. Empty until an owner approves with a memo.
+    pub approval_memos: Vec<Vec<u8>>,
+    /// Indexes of the [Transaction]s that must execute before this one, declared with
+    /// [crate::smart_wallet::declare_dependencies].
+    pub dependencies: Vec<u64>,
+    /// Where the [Transaction] is in its lifecycle, kept in sync with the other fields
+    /// by every handler. Approvals are counted as of the last approval change.
+    pub state: TransactionState,
+    /// `approval_nonces[index]` counts the times `[SmartWallet]::owners[index]` withdrew
+    /// their approval, so a signed approval cannot be replayed after it. Empty until an
+    /// owner unapproves.
+    pub approval_nonces: Vec<u64>,
 }
 
-impl Transaction {
-    /// Computes the space a [Transaction] uses.
-    pub fn new(size: usize) -> usize {
-        std::mem::size_of::<Transaction>() * size
+/// Lifecycle state of a [Transaction].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionState {
+    /// Instructions are still being uploaded. It cannot be approved yet.
+    Draft,
+    /// Awaiting approvals.
+    Active,
+    /// [SmartWallet::threshold] active owners approved it.
+    Approved,
+    /// Executed.
+    Executed,
+    /// Cancelled by its proposer or the wallet, or vetoed.
+    Cancelled,
+    /// Marked expired after its grace period.
+    Expired,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for TransactionState {
+    fn default() -> Self {
+        TransactionState::Active
     }
+}
+
+impl Transaction {
     /// Computes the space a [Transaction] uses.
     pub fn space(blank_xacts: Vec<TXInstruction>) -> usize {
         4  // Anchor discriminato
@@ -84,6 +309,80 @@ impl Transaction {
     }
 }
 
+/// A [Transaction] index reserved by an owner, redeemed by creating the [Transaction]
+/// at that index with [crate::smart_wallet::create_reserved_transaction].
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct TransactionReservation {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// The owner who reserved the index.
+    pub proposer: Pubkey,
+    /// The reserved [Transaction] index.
+    pub index: u64,
+}
+
+impl TransactionReservation {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            32 + // proposer
+            8 // index
+    }
+}
+
+/// Whether a [Transaction] can be executed, and if not, why.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionReadiness {
+    /// The [Transaction] can be executed.
+    Ready,
+    /// The [Transaction] has already been executed.
+    AlreadyExecuted,
+    /// The owner set has changed since the [Transaction] was created.
+    OwnerSetChanged,
+    /// Not enough active owners have approved the [Transaction].
+    BelowThreshold,
+    /// The ETA of the [Transaction] has not been reached.
+    EtaNotReached,
+    /// The [Transaction] is past its grace period.
+    Stale,
+    /// The [Transaction] was cancelled.
+    Cancelled,
+}
+
+/// Size of a [Transaction]'s instructions, for pre-sizing compute budget requests
+/// and address lookup tables.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TransactionEstimate {
+    /// Number of instructions.
+    pub num_instructions: u32,
+    /// Number of distinct accounts referenced, including programs.
+    pub num_accounts: u32,
+    /// Number of distinct accounts referenced as writable.
+    pub num_writable: u32,
+    /// Number of distinct accounts referenced as signers.
+    pub num_signers: u32,
+    /// Total bytes of instruction data.
+    pub data_len: u32,
+}
+
+/// Result of [crate::smart_wallet::check_transaction], written to the return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TransactionCheck {
+    /// Readiness of the [Transaction].
+    pub readiness: TransactionReadiness,
+    /// Number of active owners that approved the [Transaction].
+    pub num_signers: u64,
+    /// Threshold of the [SmartWallet].
+    pub threshold: u64,
+    /// ETA of the [Transaction].
+    pub eta: i64,
+    /// When the [Transaction] becomes stale. [crate::NO_ETA] if it never does.
+    pub expires_at: i64,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
 pub struct StakeData {
     pub duration: i32,
@@ -92,6 +391,31 @@ pub struct StakeData {
     pub reward_pot: i64,
     pub protected_gids: Vec<u16>,
     pub uuid: Vec<u8>,
+    pub reward_curve: RewardCurve,
+    pub distribution_interval: i64,
+    pub max_rewards_per_owner: u64,
+    pub mint_allowlist: bool,
+    pub min_hold_seconds: i64,
+    pub enrollment_fee: Option<EnrollmentFee>,
+    pub require_master_edition: bool,
+    pub protected_gid_ranges: Vec<GidRange>,
+}
+
+/// How the per-ticket reward rate of a [Stake] is computed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardCurve {
+    /// Every ticket earns `rate` per second.
+    Fixed { rate: u64 },
+    /// The reward pot is spread across all registered mints over the duration,
+    /// so the rate per ticket falls as more mints are staked.
+    /// `max_rate` caps the rate per ticket; 0 means uncapped.
+    InverseSupply { max_rate: u64 },
+}
+
+impl Default for RewardCurve {
+    fn default() -> Self {
+        RewardCurve::Fixed { rate: 0 }
+    }
 }
 
 /// Instruction.
@@ -105,19 +429,250 @@ pub struct Stake {
     pub reward_pot: i64,
     pub protected_gids: Vec<u16>,
     pub uuid: Vec<u8>,
+    /// How the per-ticket reward rate is computed.
+    pub reward_curve: RewardCurve,
+    /// Number of mints currently registered.
+    pub total_mints: u64,
+    /// The [SmartWallet] this [Stake] belongs to.
+    pub smart_wallet: Pubkey,
+    /// Index used to derive the [Stake] address.
+    pub index: u64,
+    /// Mint of the reward token.
+    pub reward_mint: Pubkey,
+    /// Token account holding the rewards, owned by the [Stake].
+    pub reward_vault: Pubkey,
+    /// Minimum seconds between reward distributions to a [Rollup].
+    pub distribution_interval: i64,
+    /// Total rewards credited to [Rollup]s so far.
+    pub rewards_credited: u64,
+    /// Maximum rewards a single owner may claim over the life of the [Stake]. 0 if uncapped.
+    pub max_rewards_per_owner: u64,
+    /// Gids that are treated as another gid.
+    pub gid_remaps: Vec<GidRemap>,
+    /// If true, only mints with an [AllowedMint] may be registered.
+    pub mint_allowlist: bool,
+    /// Seconds a [Ticket] is held before it starts accruing. 0 to accrue immediately.
+    pub min_hold_seconds: i64,
+    /// Fee charged to the payer of [crate::smart_wallet::register_entity], if any.
+    pub enrollment_fee: Option<EnrollmentFee>,
+    /// Uri of off-chain JSON describing the [Stake] for front-ends.
+    pub metadata_uri: String,
+    /// If true, registered mints must have a Metaplex master edition.
+    pub require_master_edition: bool,
+    /// Basis points of each claimed reward burned instead of paid out.
+    pub burn_bps: u16,
+    /// Ranges of protected gids, in addition to [Stake::protected_gids].
+    pub protected_gid_ranges: Vec<GidRange>,
+    /// If set, rewards are minted on claim instead of paid out of the vault, and
+    /// are not bounded by [Stake::reward_pot].
+    pub reward_emission: Option<RewardEmission>,
+    /// If set, rewards may only be claimed while the window is open.
+    pub claim_window: Option<ClaimWindow>,
+    /// If set, only owners with an [AllowedParticipant] may register.
+    pub participant_allowlist: Option<ParticipantAllowlist>,
+    /// If set, the program idle rewards may be deposited into.
+    pub yield_venue: Option<YieldVenue>,
+    /// If true, rewards are only paid to the owner's associated token account.
+    pub owner_ata_claims: bool,
+    /// Other [SmartWallet]s co-sponsoring the [Stake], sharing its admin rights.
+    pub sponsors: Vec<StakeSponsor>,
+    /// How credited rewards are rounded to whole units.
+    pub reward_rounding: RewardRounding,
+    /// Private parameters committed to before they are announced.
+    pub sealed_params: Option<SealedParams>,
+    /// Encryption keys of the delegates allowed to read [Stake::sealed_params].
+    pub viewers: Vec<Pubkey>,
+    /// Last [Rollup] passed to [crate::smart_wallet::distribute_rewards], so cranks
+    /// paging through rollups by address can resume after it.
+    pub distribution_cursor: Pubkey,
+    /// If set, [Quest] bonuses are paid from this budget instead of the reward pot.
+    pub bonus_budget: Option<BonusBudget>,
+}
+
+/// Budget of a [Stake] for promotional bonuses, held in its own vault.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct BonusBudget {
+    /// Token account of the reward mint, owned by the [Stake], holding the budget.
+    pub vault: Pubkey,
+    /// Total transferred in with [crate::smart_wallet::fund_bonus_budget].
+    pub funded: u64,
+    /// Total paid out as bonuses.
+    pub spent: u64,
+}
+
+/// How a [Rollup]'s accrued rewards are rounded to whole units when credited. The
+/// remainder is carried in [Rollup::accrued_dust] either way.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardRounding {
+    /// Round down, carrying the fraction forward.
+    Floor,
+    /// Round to the nearest unit, halves up.
+    Round,
+    /// Round up, carrying the fraction paid ahead as owed.
+    Ceil,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for RewardRounding {
+    fn default() -> Self {
+        RewardRounding::Floor
+    }
+}
+
+/// A [SmartWallet] co-sponsoring a [Stake].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct StakeSponsor {
+    /// The sponsor.
+    pub smart_wallet: Pubkey,
+    /// Rewards the sponsor contributed to the reward pot with [crate::smart_wallet::fund_stake].
+    pub funded: u64,
+}
+
+/// Parameters of a [Stake] committed to on-chain and revealed later with
+/// [crate::smart_wallet::reveal_stake_params].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct SealedParams {
+    /// Keccak hash of the parameters.
+    pub commitment: [u8; 32],
+    /// The parameters encrypted to the [Stake::viewers], opaque to the program.
+    pub ciphertext: Vec<u8>,
+}
+
+/// Fee charged per registration, in SOL or an SPL token.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnrollmentFee {
+    /// Mint of the fee token, or [None] to charge lamports.
+    pub mint: Option<Pubkey>,
+    /// Amount charged.
+    pub amount: u64,
+    /// Account receiving the fee: a token account of `mint`, or any account for lamports.
+    pub destination: Pubkey,
+}
+
+/// Maps a gid onto another gid.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GidRemap {
+    /// The gid tickets and rollups were registered with.
+    pub from: u16,
+    /// The gid they are treated as.
+    pub to: u16,
+}
+
+/// Parameters of a [RewardEmission].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RewardEmissionParams {
+    /// Length of an emission epoch, in seconds.
+    pub epoch_duration: i64,
+    /// Maximum rewards minted per epoch.
+    pub max_per_epoch: u64,
+}
+
+/// Rewards minted on claim by a [Stake], capped per epoch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RewardEmission {
+    /// Length of an emission epoch, in seconds.
+    pub epoch_duration: i64,
+    /// Maximum rewards minted per epoch.
+    pub max_per_epoch: u64,
+    /// Start of the current epoch.
+    pub epoch_start: i64,
+    /// Rewards minted in the current epoch.
+    pub minted_in_epoch: u64,
+}
+
+/// Times at which rewards of a [Stake] may be claimed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClaimWindow {
+    /// Open for `length` seconds of every `period` seconds, starting at `start`.
+    Periodic { start: i64, period: i64, length: i64 },
+    /// Open during the last `days` days of each calendar month, in UTC.
+    MonthEnd { days: u8 },
+}
+
+/// A whitelisted program a [Stake] lends its idle reward vault to.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct YieldVenue {
+    /// The venue program, e.g. a lending program.
+    pub program_id: Pubkey,
+    /// Rewards deposited into the venue and not yet recalled.
+    pub deposited: u64,
+}
+
+/// How owners get the [AllowedParticipant] needed to register in a [Stake].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticipantAllowlist {
+    /// Only governance adds participants.
+    Explicit,
+    /// Governance adds participants, and owners in the Merkle tree of `root` may add
+    /// themselves. Leaves are the keccak hash of the owner's address.
+    Merkle { root: [u8; 32] },
+}
+
+/// An inclusive range of gids.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GidRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl GidRange {
+    /// Returns true if the gid is within the range.
+    pub fn contains(&self, gid: u16) -> bool {
+        self.start <= gid && gid <= self.end
+    }
 }
 
 impl Stake {
-    pub fn space(protected_gids: usize) -> usize {
+    pub fn space(protected_gids: usize, protected_gid_ranges: usize) -> usize {
         8 +
             1 + // bump
             4 + // reward_tender
-            4 + (8 * 1) + // gen epoch
-            4 + (32 * 1) + // 32 char name utf-8
+            4 + 8 + // gen epoch
+            4 + 32 + // 32 char name utf-8
             8 + // reward_pot
             4 + (protected_gids * 2) + // protected_gids
-            4 + 36 // 36 char bytes of uuid string
+            4 + 36 + // 36 char bytes of uuid string
+            1 + 8 + // reward_curve
+            8 + // total_mints
+            32 + // smart_wallet
+            8 + // index
+            32 + // reward_mint
+            32 + // reward_vault
+            8 + // distribution_interval
+            8 + // rewards_credited
+            8 + // max_rewards_per_owner
+            4 + (crate::MAX_GID_REMAPS * 4) + // gid_remaps
+            1 + // mint_allowlist
+            8 + // min_hold_seconds
+            1 + 1 + 32 + 8 + 32 + // enrollment_fee
+            4 + crate::MAX_STAKE_URI_LENGTH + // metadata_uri
+            1 + // require_master_edition
+            2 + // burn_bps
+            4 + (protected_gid_ranges * 4) + // protected_gid_ranges
+            1 + 8 + 8 + 8 + 8 + // reward_emission
+            1 + 1 + 8 + 8 + 8 + // claim_window
+            1 + 1 + 32 + // participant_allowlist
+            1 + 32 + 8 + // yield_venue
+            1 + // owner_ata_claims
+            4 + (crate::MAX_STAKE_SPONSORS * (32 + 8)) + // sponsors
+            1 + // reward_rounding
+            1 + 32 + 4 + crate::MAX_SEALED_PARAMS_LEN + // sealed_params
+            4 + (crate::MAX_STAKE_VIEWERS * 32) + // viewers
+            32 + // distribution_cursor
+            1 + 32 + 8 + 8 // bonus_budget
+
+    }
 
+    /// Reallocates `stake` to [Stake::space] for its protected gids and gid ranges,
+    /// with `payer` topping up its rent exemption, e.g. before setting a field added
+    /// since it was created.
+    pub fn realloc_to_fit<'info>(
+        stake: &Account<'info, Stake>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> ProgramResult {
+        let space = Stake::space(stake.protected_gids.len(), stake.protected_gid_ranges.len());
+        crate::account_utils::realloc(&stake.to_account_info(), space, payer, system_program)
     }
 }
 /// Instruction.
@@ -129,23 +684,21 @@ pub struct Ticket {
     pub gid: u16,
     pub mint: Pubkey,
     pub owner: Pubkey,
+    /// If non-zero, the ticket does not accrue until this epoch, once activated
+    /// with [crate::smart_wallet::activate_entity]. [crate::FROZEN_TICKET] if frozen.
+    pub pending_until: i64,
 }
 
 impl Ticket {
-    // 95 bytes.
+    // 95 bytes, the same as before pending_until was added.
     pub fn space() -> usize {
         8 +
-        // 8
-            4 + (8 * 1) +
-            // 20
-            1 +
-            // 21
-            2 +
-            // 23
-            4 + 32 +
-            // 41
-            4 + 32
-
+            4 + 8 + // enrollment_epoch
+            1 + // bump
+            2 + // gid
+            32 + // mint
+            32 + // owner
+            8 // pending_until
     }
 }
 
@@ -157,15 +710,594 @@ pub struct Rollup {
     pub timestamp: Vec<u8>,
     pub gid: u16,
     pub mints: u32,
+    /// Owner of the mints in this [Rollup].
+    pub owner: Pubkey,
+    /// Rewards credited but not yet claimed.
+    pub accrued: u64,
+    /// The [Stake] this [Rollup] accrues rewards in.
+    pub stake: Pubkey,
+    /// Fraction of a reward credited but not yet whole, in units of 2^-64.
+    pub accrued_dust: u64,
+    /// If true, [Rollup::accrued_dust] was paid ahead by rounding up, and is taken
+    /// out of the next accrual instead.
+    pub dust_owed: bool,
+}
+
+/// The upstream Goki smart wallet program, whose accounts can be imported.
+pub mod upstream_goki {
+    anchor_lang::declare_id!("GokivDYuQXPZCWRkwMhdH2h91KpDQXBEmpgBgs55bnpH");
+}
+
+/// Layout of a [SmartWallet] of the upstream Goki program.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct LegacySmartWallet {
+    pub base: Pubkey,
+    pub bump: u8,
+    pub threshold: u64,
+    pub minimum_delay: i64,
+    pub grace_period: i64,
+    pub owner_set_seqno: u32,
+    pub num_transactions: u64,
+    pub owners: Vec<Pubkey>,
+    pub reserved: [u64; 16],
+}
+
+/// Layout of a [Transaction] of the upstream Goki program.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct LegacyTransaction {
+    pub smart_wallet: Pubkey,
+    pub index: u64,
+    pub bump: u8,
+    pub proposer: Pubkey,
+    pub instructions: Vec<TXInstruction>,
+    pub signers: Vec<bool>,
+    pub owner_set_seqno: u32,
+    pub eta: i64,
+    pub executor: Pubkey,
+    pub executed_at: i64,
+}
+
+/// Layout of [Rollup]s derived per [SmartWallet], before they were scoped per [Stake].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct LegacyRollup {
+    pub bump: u8,
+    pub timestamp: Vec<u8>,
+    pub gid: u16,
+    pub mints: u32,
 }
 
 impl Rollup {
     pub fn space() -> usize {
         8 +
             1 +
-            4 + (8 * 1) +
+            4 + 8 +
             2 +
-            4
+            4 +
+            32 + // owner
+            8 + // accrued
+            32 + // stake
+            8 + // accrued_dust
+            1 // dust_owed
+    }
+}
+
+/// A mint allowed to be registered in a [Stake] with [Stake::mint_allowlist] set.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct AllowedMint {
+    pub bump: u8,
+    /// The [Stake].
+    pub stake: Pubkey,
+    /// The allowed mint.
+    pub mint: Pubkey,
+}
+
+/// An owner allowed to register in a [Stake] with [Stake::participant_allowlist] set.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct AllowedParticipant {
+    pub bump: u8,
+    /// The [Stake].
+    pub stake: Pubkey,
+    /// The allowed owner.
+    pub owner: Pubkey,
+}
+
+impl AllowedParticipant {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // stake
+            32 // owner
+    }
+}
+
+/// An account exempt from the enrollment fee and the reward burn of a [Stake].
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct FeeExemption {
+    pub bump: u8,
+    /// The [Stake].
+    pub stake: Pubkey,
+    /// The exempt owner.
+    pub account: Pubkey,
+}
+
+impl FeeExemption {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // stake
+            32 // account
+    }
+}
+
+impl AllowedMint {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // stake
+            32 // mint
+    }
+}
+
+/// Lamports set aside by a [SmartWallet] to pay the rent of its stakers' [Ticket]s
+/// and [Rollup]s. Funded by transferring lamports to it.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct RentPool {
+    pub bump: u8,
+    /// The [SmartWallet] whose stakers are sponsored.
+    pub smart_wallet: Pubkey,
+    /// Rent paid out of the pool and not yet returned.
+    pub outstanding: u64,
+}
+
+impl RentPool {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            8 // outstanding
+    }
+}
+
+/// Rewards claimed by an owner from a [Stake].
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct OwnerRewards {
+    pub bump: u8,
+    /// The [Stake].
+    pub stake: Pubkey,
+    /// The owner claiming rewards.
+    pub owner: Pubkey,
+    /// Total rewards claimed.
+    pub claimed: u64,
+}
+
+impl OwnerRewards {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // stake
+            32 + // owner
+            8 // claimed
+    }
+}
+
+/// Permission for a crank to claim an owner's rewards from a [Stake] on a schedule.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct CrankPermission {
+    pub bump: u8,
+    /// The [Stake].
+    pub stake: Pubkey,
+    /// The owner whose rewards are claimed.
+    pub owner: Pubkey,
+    /// The key allowed to claim.
+    pub crank: Pubkey,
+    /// Token account the owner chose to receive the rewards.
+    pub destination: Pubkey,
+    /// Minimum seconds between claims by the crank.
+    pub interval: i64,
+    /// When the crank last claimed.
+    pub last_claimed_at: i64,
+}
+
+impl CrankPermission {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // stake
+            32 + // owner
+            32 + // crank
+            32 + // destination
+            8 + // interval
+            8 // last_claimed_at
+    }
+}
+
+/// A badge NFT minted to owners whose [OwnerRewards::claimed] reaches a threshold.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct Milestone {
+    pub bump: u8,
+    /// The [Stake].
+    pub stake: Pubkey,
+    /// Index used to derive the [Milestone] address.
+    pub index: u64,
+    /// Rewards an owner must have claimed to mint the badge.
+    pub min_claimed: u64,
+    /// Metadata name of the badge.
+    pub name: String,
+    /// Metadata symbol of the badge.
+    pub symbol: String,
+    /// Metadata uri of the badge.
+    pub uri: String,
+    /// Number of badges minted.
+    pub num_minted: u64,
+}
+
+impl Milestone {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // stake
+            8 + // index
+            8 + // min_claimed
+            4 + crate::token_metadata::MAX_NAME_LENGTH + // name
+            4 + crate::token_metadata::MAX_SYMBOL_LENGTH + // symbol
+            4 + crate::token_metadata::MAX_URI_LENGTH + // uri
+            8 // num_minted
+    }
+}
+
+/// Record of an owner minting the badge of a [Milestone].
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct MilestoneClaim {
+    pub bump: u8,
+    /// The [Milestone].
+    pub milestone: Pubkey,
+    /// The owner that claimed.
+    pub owner: Pubkey,
+    /// Mint of the badge.
+    pub mint: Pubkey,
+    /// When the badge was minted.
+    pub claimed_at: i64,
+}
+
+impl MilestoneClaim {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // milestone
+            32 + // owner
+            32 + // mint
+            8 // claimed_at
+    }
+}
+
+/// What a [Ticket] and its [Rollup] must satisfy to claim a [Quest] bonus.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuestRequirement {
+    /// The ticket was enrolled no later than `start` and is still enrolled at `end`.
+    StakedThrough { start: i64, end: i64 },
+    /// The ticket is enrolled in `gid` and its rollup holds at least `min_mints`.
+    GidStaked { gid: u16, min_mints: u32 },
+}
+
+impl Default for QuestRequirement {
+    fn default() -> Self {
+        QuestRequirement::GidStaked { gid: 0, min_mints: 0 }
+    }
+}
+
+/// A one-time bonus governance offers to [Rollup]s meeting a [QuestRequirement].
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct Quest {
+    pub bump: u8,
+    /// The [Stake] paying the bonus.
+    pub stake: Pubkey,
+    /// Index used to derive the [Quest] address.
+    pub index: u64,
+    /// What must be satisfied to claim the bonus.
+    pub requirement: QuestRequirement,
+    /// Bonus paid per claim.
+    pub bonus: u64,
+    /// Number of times the bonus was claimed.
+    pub num_claims: u64,
+}
+
+impl Quest {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // stake
+            8 + // index
+            1 + 16 + // requirement
+            8 + // bonus
+            8 // num_claims
+    }
+}
+
+/// Record of a [Rollup] claiming a [Quest] bonus.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct QuestClaim {
+    pub bump: u8,
+    /// The [Quest].
+    pub quest: Pubkey,
+    /// The [Rollup] that claimed.
+    pub rollup: Pubkey,
+    /// Bonus paid.
+    pub amount: u64,
+    /// When the bonus was claimed.
+    pub claimed_at: i64,
+}
+
+impl QuestClaim {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // quest
+            32 + // rollup
+            8 + // amount
+            8 // claimed_at
+    }
+}
+
+/// Kind of address a [SubaccountInfo] describes.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubaccountType {
+    /// Derived wallet, signed for by [crate::smart_wallet::execute_ixs].
+    Derived,
+    /// Owner invoker, signed for by [crate::smart_wallet::owner_invoke_instruction].
+    OwnerInvoker,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for SubaccountType {
+    fn default() -> Self {
+        SubaccountType::Derived
+    }
+}
+
+/// Proposals made by a proposer in the current [ProposalRateLimit] window.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct ProposerActivity {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// The proposer.
+    pub proposer: Pubkey,
+    /// Start of the current window.
+    pub window_start: i64,
+    /// Proposals made since [ProposerActivity::window_start].
+    pub num_proposals: u32,
+}
+
+impl ProposerActivity {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            32 + // proposer
+            8 + // window_start
+            4 // num_proposals
+    }
+}
+
+/// An account allowed to propose [Transaction]s to a [SmartWallet] without being
+/// an owner, such as a program PDA proposing via CPI.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct AllowedProposer {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// The allowed proposer.
+    pub proposer: Pubkey,
+}
+
+impl AllowedProposer {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            32 // proposer
+    }
+}
+
+/// Where an owner of a [SmartWallet] wants to be alerted that their approval is
+/// needed, for off-chain notifier services. Opt-in.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct OwnerNotification {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// The owner.
+    pub owner: Pubkey,
+    /// Key notifier services deliver alerts to.
+    pub notification_key: Pubkey,
+    /// Hash of the webhook alerts are posted to. Zero if none.
+    pub webhook_hash: [u8; 32],
+}
+
+impl OwnerNotification {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            32 + // owner
+            32 + // notification_key
+            32 // webhook_hash
+    }
+}
+
+/// When an owner of a [SmartWallet] was last seen, for spotting unresponsive signers.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct OwnerHeartbeat {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// The owner.
+    pub owner: Pubkey,
+    /// Time of the owner's last heartbeat.
+    pub last_seen: i64,
+    /// Number of heartbeats the owner has sent.
+    pub num_heartbeats: u64,
+}
+
+impl OwnerHeartbeat {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            32 + // owner
+            8 + // last_seen
+            8 // num_heartbeats
+    }
+}
+
+/// An instruction governance pre-approved for a single execution by [OneShot::key],
+/// signed by a derived wallet, within a time window. For planned maintenance that
+/// should not need a live quorum.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct OneShot {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// The key allowed to execute the instruction.
+    pub key: Pubkey,
+    /// Nonce used to derive the [OneShot] address.
+    pub nonce: u64,
+    /// Index of the derived wallet the instruction is signed by.
+    pub wallet_index: u64,
+    /// The instruction.
+    pub instruction: TXInstruction,
+    /// Start of the window the instruction may be executed in.
+    pub valid_from: i64,
+    /// End of the window, exclusive.
+    pub valid_until: i64,
+    /// When the instruction was executed. -1 if not executed.
+    pub executed_at: i64,
+}
+
+impl OneShot {
+    pub fn space(instruction: &TXInstruction) -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            32 + // key
+            8 + // nonce
+            8 + // wallet_index
+            4 + 4 + instruction.space() + // instruction
+            8 + // valid_from
+            8 + // valid_until
+            8 // executed_at
+    }
+}
+
+/// Proof that a [Transaction] was executed, which outlives the [Transaction] account.
+/// Written when [SmartWallet::execution_receipts] is set.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct ExecutionReceipt {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// [Transaction::index] of the executed [Transaction].
+    pub index: u64,
+    /// Hash of the executed instructions, as [Transaction::hash_instructions].
+    pub digest: [u8; 32],
+    /// The account that executed the [Transaction].
+    pub executor: Pubkey,
+    /// Slot of the execution.
+    pub slot: u64,
+    /// When the [Transaction] was executed.
+    pub executed_at: i64,
+}
+
+impl ExecutionReceipt {
+    pub fn space() -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            8 + // index
+            32 + // digest
+            32 + // executor
+            8 + // slot
+            8 // executed_at
+    }
+}
+
+/// Maximum length of an [InvokerPermission::discriminator].
+pub const MAX_DISCRIMINATOR_LEN: usize = 8;
+
+/// Instructions an owner invoker may sign for. Once created, the owner invoker at
+/// [InvokerWhitelist::index] may only invoke instructions matching a permission.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct InvokerWhitelist {
+    pub bump: u8,
+    /// The [SmartWallet].
+    pub smart_wallet: Pubkey,
+    /// Index of the owner invoker.
+    pub index: u64,
+    /// Allowed instructions.
+    pub permissions: Vec<InvokerPermission>,
+}
+
+impl InvokerWhitelist {
+    pub fn space(num_permissions: usize) -> usize {
+        8 +
+            1 + // bump
+            32 + // smart_wallet
+            8 + // index
+            4 + num_permissions * InvokerPermission::space() // permissions
+    }
+}
+
+/// An instruction of a program, identified by the leading bytes of its data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct InvokerPermission {
+    /// Program the instruction is sent to.
+    pub program_id: Pubkey,
+    /// Prefix of the instruction data, e.g. an Anchor sighash.
+    pub discriminator: Vec<u8>,
+}
+
+impl InvokerPermission {
+    pub fn space() -> usize {
+        32 + // program_id
+            4 + MAX_DISCRIMINATOR_LEN // discriminator
+    }
+}
+
+/// Maps a subaccount address back to its [SmartWallet] and index.
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct SubaccountInfo {
+    /// The [SmartWallet] the subaccount belongs to.
+    pub smart_wallet: Pubkey,
+    /// Kind of subaccount.
+    pub subaccount_type: SubaccountType,
+    /// Index the subaccount is derived with.
+    pub index: u64,
+}
+
+impl SubaccountInfo {
+    pub fn space() -> usize {
+        8 +
+            32 + // smart_wallet
+            1 + // subaccount_type
+            8 // index
     }
 }
 
@@ -188,11 +1320,21 @@ impl TXInstruction {
     /// Space that a [TXInstruction] takes up.
     pub fn space(&self) -> usize {
         std::mem::size_of::<Pubkey>()
-            + (self.keys.len() as usize) * std::mem::size_of::<TXAccountMeta>()
-            + (self.data.len() as usize)
+            + self.keys.len() * std::mem::size_of::<TXAccountMeta>()
+            + self.data.len()
     }
 }
 
+/// An owner's ed25519 signature over their [Transaction::approval_payload], relayed with
+/// [crate::smart_wallet::approve_aggregate].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct OwnerSig {
+    /// The owner.
+    pub owner: Pubkey,
+    /// The owner's signature.
+    pub signature: [u8; 64],
+}
+
 /// Account metadata used to define [TXInstruction]s
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, PartialEq, Copy, Clone)]
 pub struct TXAccountMeta {
@@ -0,0 +1,123 @@
+//! Minimal bindings for the Metaplex Token Metadata program.
+
+use crate::*;
+use solana_program::instruction::{AccountMeta, Instruction};
+
+declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Maximum length of a metadata name.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Maximum length of a metadata symbol.
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+
+/// Maximum length of a metadata uri.
+pub const MAX_URI_LENGTH: usize = 200;
+
+/// Account key of a `MasterEditionV1`.
+const KEY_MASTER_EDITION_V1: u8 = 2;
+
+/// Account key of a `MasterEditionV2`.
+const KEY_MASTER_EDITION_V2: u8 = 6;
+
+/// Instruction index of `CreateMetadataAccountV2`.
+const CREATE_METADATA_ACCOUNT_V2: u8 = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum UseMethod {
+    Burn,
+    Multiple,
+    Single,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Uses {
+    pub use_method: UseMethod,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct DataV2 {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<Creator>>,
+    pub collection: Option<Collection>,
+    pub uses: Option<Uses>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct CreateMetadataAccountArgsV2 {
+    data: DataV2,
+    is_mutable: bool,
+}
+
+/// Address of the metadata account of a mint.
+pub fn metadata_address(mint: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata", &ID.to_bytes(), &mint.to_bytes()], &ID).0
+}
+
+/// Address of the master edition account of a mint.
+pub fn master_edition_address(mint: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", &ID.to_bytes(), &mint.to_bytes(), b"edition"],
+        &ID,
+    )
+    .0
+}
+
+/// Returns true if the account is the master edition of the mint.
+pub fn is_master_edition(info: &AccountInfo, mint: Pubkey) -> bool {
+    if info.key() != master_edition_address(mint) || *info.owner != ID {
+        return false;
+    }
+    let data = info.try_borrow_data();
+    matches!(
+        data.as_ref().map(|d| d.first().copied()),
+        Ok(Some(KEY_MASTER_EDITION_V1)) | Ok(Some(KEY_MASTER_EDITION_V2))
+    )
+}
+
+/// Builds a `CreateMetadataAccountV2` instruction.
+pub fn create_metadata_account_v2(
+    metadata: Pubkey,
+    mint: Pubkey,
+    mint_authority: Pubkey,
+    payer: Pubkey,
+    update_authority: Pubkey,
+    data: DataV2,
+    is_mutable: bool,
+) -> Result<Instruction> {
+    let mut ix_data = vec![CREATE_METADATA_ACCOUNT_V2];
+    CreateMetadataAccountArgsV2 { data, is_mutable }
+        .serialize(&mut ix_data)
+        .map_err(|_| StakeErrorCode::AccountMismatch)?;
+    Ok(Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(metadata, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(mint_authority, true),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(update_authority, true),
+            AccountMeta::new_readonly(System::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+        data: ix_data,
+    })
+}
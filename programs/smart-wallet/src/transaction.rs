@@ -1,8 +1,767 @@
 use crate::*;
+use std::ops::Range;
+
+impl LegacyTransaction {
+    /// Deserializes a [LegacyTransaction] from an account owned by the upstream program.
+    pub fn load(info: &AccountInfo) -> Result<LegacyTransaction> {
+        require!(*info.owner == upstream_goki::ID, InvalidLegacyAccount);
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == Transaction::discriminator(),
+            InvalidLegacyAccount
+        );
+        LegacyTransaction::deserialize(&mut &data[8..])
+            .map_err(|_| ErrorCode::InvalidLegacyAccount.into())
+    }
+}
+
+impl ExecutionReceipt {
+    /// Derives the address of the [ExecutionReceipt] of the [Transaction] of a
+    /// [SmartWallet] at `index`.
+    pub fn address(smart_wallet: Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                EXECUTION_RECEIPT_SEED,
+                &smart_wallet.to_bytes(),
+                &index.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Creates the [ExecutionReceipt] of the executed [Transaction], paid by the
+    /// executor. The receipt and the [System] program are looked up in `accounts`.
+    pub fn write<'info>(
+        tx: &Transaction,
+        instructions: &[TXInstruction],
+        executor: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+    ) -> ProgramResult {
+        let (address, bump) = ExecutionReceipt::address(tx.smart_wallet, tx.index);
+        let receipt = unwrap_or_err!(
+            accounts.iter().find(|a| a.key() == address),
+            MissingExecutionReceipt
+        );
+        let system_program = unwrap_or_err!(
+            accounts.iter().find(|a| a.key() == System::id()),
+            MissingExecutionReceipt
+        );
+        account_utils::create_pda(
+            receipt,
+            ExecutionReceipt::space(),
+            &[
+                EXECUTION_RECEIPT_SEED,
+                &tx.smart_wallet.to_bytes(),
+                &tx.index.to_le_bytes(),
+                &[bump],
+            ],
+            executor,
+            system_program,
+        )?;
+        ExecutionReceipt {
+            bump,
+            smart_wallet: tx.smart_wallet,
+            index: tx.index,
+            digest: Transaction::hash_instructions(instructions)?,
+            executor: tx.executor,
+            slot: Clock::get()?.slot,
+            executed_at: tx.executed_at,
+        }
+        .try_serialize(&mut &mut receipt.try_borrow_mut_data()?[..])
+    }
+}
+
+impl TransactionEstimate {
+    /// Estimates the size of executing `instructions`.
+    pub fn new(instructions: &[TXInstruction]) -> TransactionEstimate {
+        let mut accounts: Vec<Pubkey> = vec![];
+        let mut writable: Vec<Pubkey> = vec![];
+        let mut signers: Vec<Pubkey> = vec![];
+        for ix in instructions.iter() {
+            if !accounts.contains(&ix.program_id) {
+                accounts.push(ix.program_id);
+            }
+            for meta in ix.keys.iter() {
+                if !accounts.contains(&meta.pubkey) {
+                    accounts.push(meta.pubkey);
+                }
+                if meta.is_writable && !writable.contains(&meta.pubkey) {
+                    writable.push(meta.pubkey);
+                }
+                if meta.is_signer && !signers.contains(&meta.pubkey) {
+                    signers.push(meta.pubkey);
+                }
+            }
+        }
+        TransactionEstimate {
+            num_instructions: instructions.len() as u32,
+            num_accounts: accounts.len() as u32,
+            num_writable: writable.len() as u32,
+            num_signers: signers.len() as u32,
+            data_len: instructions.iter().map(|ix| ix.data.len() as u32).sum(),
+        }
+    }
+}
+
+impl TransactionState {
+    /// Whether a [Transaction] may move from this state to `to`. Executed, cancelled
+    /// and expired [Transaction]s never change state again.
+    pub fn can_transition(&self, to: TransactionState) -> bool {
+        use TransactionState::*;
+        matches!(
+            (self, to),
+            (Draft, Active)
+                | (Draft, Cancelled)
+                | (Active, Approved)
+                | (Approved, Active)
+                | (Active | Approved, Executed)
+                | (Active | Approved, Cancelled)
+                | (Active | Approved, Expired)
+        )
+    }
+}
 
 impl Transaction {
+    /// Derives the address of the [Transaction] of a [SmartWallet] at `index`.
+    pub fn address(smart_wallet: Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                TRANSACTION_SEED,
+                &smart_wallet.to_bytes(),
+                &index.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Grows the account of `tx` to fit its serialized contents, with `payer` topping
+    /// up its rent exemption, after a field of variable length grew.
+    pub fn realloc_to_fit<'info>(
+        tx: &Account<'info, Transaction>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> ProgramResult {
+        let info = tx.to_account_info();
+        let space = 8 + (**tx).try_to_vec().map_err(ProgramError::from)?.len();
+        if space > info.data_len() {
+            account_utils::realloc(&info, space, payer, system_program)?;
+        }
+        Ok(())
+    }
+
+    /// Derives the address of a [Transaction] from its proposer and a nonce chosen by them.
+    pub fn proposer_address(
+        smart_wallet: Pubkey,
+        proposer: Pubkey,
+        proposer_nonce: u64,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                TRANSACTION_SEED,
+                &smart_wallet.to_bytes(),
+                &proposer.to_bytes(),
+                &proposer_nonce.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// Initializes a proposed [Transaction] at `index`, approved by its proposer if
+    /// `owner_index` is set.
+    #[allow(clippy::too_many_arguments)]
+    pub fn init(
+        &mut self,
+        smart_wallet: &Account<SmartWallet>,
+        index: u64,
+        proposer: Pubkey,
+        owner_index: Option<usize>,
+        bump: u8,
+        instructions: Vec<TXInstruction>,
+        eta: i64,
+        now: i64,
+    ) -> ProgramResult {
+        self.init_draft(
+            smart_wallet,
+            index,
+            proposer,
+            owner_index,
+            bump,
+            instructions,
+        )?;
+        self.activate(smart_wallet, eta, now)
+    }
+
+    /// Initializes a [TransactionState::Draft] [Transaction] at `index`, to be
+    /// activated with [Transaction::activate] once its instructions are uploaded.
+    pub fn init_draft(
+        &mut self,
+        smart_wallet: &Account<SmartWallet>,
+        index: u64,
+        proposer: Pubkey,
+        owner_index: Option<usize>,
+        bump: u8,
+        instructions: Vec<TXInstruction>,
+    ) -> ProgramResult {
+        let mut signers = vec![false; smart_wallet.owners.len()];
+        if let Some(owner_index) = owner_index {
+            signers[owner_index] = true;
+        }
+
+        self.smart_wallet = smart_wallet.key();
+        self.index = index;
+        self.bump = bump;
+        self.proposer = proposer;
+        self.instructions = instructions;
+        self.signers = signers;
+        self.owner_set_seqno = smart_wallet.owner_set_seqno;
+        self.eta = NO_ETA;
+        self.executor = Pubkey::default();
+        self.executed_at = -1;
+        self.expired = false;
+        self.bond = 0;
+        self.instructions_hash = None;
+        self.writable_scope = None;
+        self.instructions_executed = 0;
+        self.proposer_executes = false;
+        self.instruction_signers = vec![];
+        self.cancelled = false;
+        self.approval_memos = vec![];
+        self.dependencies = vec![];
+        self.approval_nonces = vec![];
+        self.state = TransactionState::Draft;
+        Ok(())
+    }
+
+    /// Moves a [TransactionState::Draft] [Transaction] to [TransactionState::Active]
+    /// with `eta`, from when owners may approve it.
+    pub fn activate(&mut self, smart_wallet: &SmartWallet, eta: i64, now: i64) -> ProgramResult {
+        if smart_wallet.minimum_delay != 0 {
+            invariant!(
+                eta >= unwrap_int!(now.checked_add(smart_wallet.minimum_delay)),
+                InvalidETA
+            );
+        }
+        if eta != NO_ETA {
+            invariant!(eta >= 0, "ETA must be positive");
+            let delay = unwrap_int!(eta.checked_sub(now));
+            invariant!(delay >= 0, "ETA must be in the future");
+            invariant!(delay <= MAX_DELAY_SECONDS, DelayTooHigh);
+        }
+
+        self.eta = eta;
+        self.transition(TransactionState::Active)?;
+        self.refresh_approval_state(smart_wallet)
+    }
+
+    /// Initializes a [Transaction] at `index` from a pending [LegacyTransaction] of the
+    /// upstream wallet `legacy_wallet` was imported from. Approvals carry over if
+    /// neither owner set has changed since.
+    pub fn import(
+        &mut self,
+        smart_wallet: &Account<SmartWallet>,
+        index: u64,
+        bump: u8,
+        legacy: LegacyTransaction,
+        legacy_wallet: &LegacySmartWallet,
+    ) -> ProgramResult {
+        require!(legacy.executed_at == -1, AlreadyExecuted);
+        let signers = if legacy.owner_set_seqno == legacy_wallet.owner_set_seqno
+            && smart_wallet.owners == legacy_wallet.owners
+        {
+            legacy.signers
+        } else {
+            vec![false; smart_wallet.owners.len()]
+        };
+
+        self.smart_wallet = smart_wallet.key();
+        self.index = index;
+        self.bump = bump;
+        self.proposer = legacy.proposer;
+        self.instructions = legacy.instructions;
+        self.signers = signers;
+        self.owner_set_seqno = smart_wallet.owner_set_seqno;
+        self.eta = legacy.eta;
+        self.executor = Pubkey::default();
+        self.executed_at = -1;
+        self.expired = false;
+        self.bond = 0;
+        self.instructions_hash = None;
+        self.writable_scope = None;
+        self.instructions_executed = 0;
+        self.proposer_executes = false;
+        self.instruction_signers = vec![];
+        self.cancelled = false;
+        self.approval_memos = vec![];
+        self.dependencies = vec![];
+        self.approval_nonces = vec![];
+        self.state = TransactionState::Active;
+        self.refresh_approval_state(smart_wallet)
+    }
+
+    /// Checks that each of `instructions` is within [crate::MAX_INSTRUCTION_KEYS] and
+    /// [crate::MAX_INSTRUCTION_DATA_LEN], so it can be executed from Solana transactions.
+    pub fn check_instruction_limits(instructions: &[TXInstruction]) -> ProgramResult {
+        for ix in instructions {
+            require!(
+                ix.keys.len() <= crate::MAX_INSTRUCTION_KEYS,
+                TooManyInstructionKeys
+            );
+            require!(
+                ix.data.len() <= crate::MAX_INSTRUCTION_DATA_LEN,
+                InstructionDataTooLong
+            );
+        }
+        Ok(())
+    }
+
+    /// Hashes instructions for [Transaction::instructions_hash].
+    pub fn hash_instructions(instructions: &[TXInstruction]) -> Result<[u8; 32]> {
+        Ok(
+            solana_program::hash::hash(&instructions.try_to_vec().map_err(ProgramError::from)?)
+                .to_bytes(),
+        )
+    }
+
+    /// Hash identifying the instructions: [Transaction::instructions_hash] if committed,
+    /// otherwise the hash of the stored instructions.
+    pub fn digest(&self) -> Result<[u8; 32]> {
+        match self.instructions_hash {
+            Some(hash) => Ok(hash),
+            None => Transaction::hash_instructions(&self.instructions),
+        }
+    }
+
+    /// The Sign-In-With-Solana style message the owner at `owner_index`, `owner`, signs
+    /// to approve the [Transaction] at address `transaction` through `domain` until
+    /// `expiry`. It carries the owner's [Transaction::approval_nonce], so unapproving
+    /// invalidates the messages signed before.
+    pub fn approval_message(
+        &self,
+        domain: &str,
+        owner: Pubkey,
+        owner_index: usize,
+        transaction: Pubkey,
+        expiry: i64,
+    ) -> Result<String> {
+        let digest: String = self
+            .digest()?
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Ok(format!(
+            "{} wants you to approve a Goki transaction with your Solana account:\n{}\n\n\
+             Smart Wallet: {}\nTransaction: {}\nDigest: {}\nNonce: {}\nExpiration Time: {}",
+            domain,
+            owner,
+            self.smart_wallet,
+            transaction,
+            digest,
+            self.approval_nonce(owner_index),
+            expiry
+        ))
+    }
+
+    /// The bytes the owner at `owner_index` signs offline, e.g. on a hardware wallet, to
+    /// approve the [Transaction] with [crate::smart_wallet::approve_with_signature]: the
+    /// smart wallet, the index, the [Transaction::owner_set_seqno], the
+    /// [Transaction::digest] and the owner's [Transaction::approval_nonce], integers
+    /// little-endian.
+    pub fn approval_payload(&self, owner_index: usize) -> Result<Vec<u8>> {
+        let mut payload = Vec::with_capacity(32 + 8 + 4 + 32 + 8);
+        payload.extend_from_slice(self.smart_wallet.as_ref());
+        payload.extend_from_slice(&self.index.to_le_bytes());
+        payload.extend_from_slice(&self.owner_set_seqno.to_le_bytes());
+        payload.extend_from_slice(&self.digest()?);
+        payload.extend_from_slice(&self.approval_nonce(owner_index).to_le_bytes());
+        Ok(payload)
+    }
+
+    /// The nonce of the owner at `owner_index` in [Transaction::approval_nonces].
+    pub fn approval_nonce(&self, owner_index: usize) -> u64 {
+        self.approval_nonces.get(owner_index).copied().unwrap_or(0)
+    }
+
+    /// Advances the nonce of the owner at `owner_index` in [Transaction::approval_nonces],
+    /// invalidating their signed approvals.
+    pub fn bump_approval_nonce(&mut self, num_owners: usize, owner_index: usize) -> ProgramResult {
+        if self.approval_nonces.len() != num_owners {
+            self.approval_nonces = vec![0; num_owners];
+        }
+        let nonce = &mut self.approval_nonces[owner_index];
+        *nonce = unwrap_int!(nonce.checked_add(1));
+        Ok(())
+    }
+
+    /// Returns the instructions to execute: the stored ones, or `revealed` if they
+    /// match the [Transaction::instructions_hash].
+    pub fn reveal_instructions(&self, revealed: Vec<TXInstruction>) -> Result<Vec<TXInstruction>> {
+        match self.instructions_hash {
+            None => Ok(self.instructions.clone()),
+            Some(hash) => {
+                require!(
+                    Transaction::hash_instructions(&revealed)? == hash,
+                    InstructionsHashMismatch
+                );
+                Ok(revealed)
+            }
+        }
+    }
+
+    /// Returns the range of the `num_instructions` instructions to run in the next call
+    /// of a checkpointed execution: at most `max` from [Transaction::instructions_executed].
+    pub fn checkpoint_range(&self, num_instructions: usize, max: usize) -> Range<usize> {
+        let start = self.instructions_executed as usize;
+        start..num_instructions.min(start.saturating_add(max))
+    }
+
+    /// Records that the instructions before `end` ran. Returns true once all
+    /// `num_instructions` did, completing the execution.
+    pub fn record_checkpoint(&mut self, end: usize, num_instructions: usize) -> Result<bool> {
+        require!(
+            end >= self.instructions_executed as usize && end <= num_instructions,
+            InvalidCheckpoint
+        );
+        self.instructions_executed = unwrap_int!(u16::try_from(end).ok());
+        Ok(end == num_instructions)
+    }
+
+    /// Returns true if an owner other than the proposer has approved the [Transaction].
+    pub fn approved_by_others(&self, smart_wallet: &SmartWallet) -> bool {
+        self.signers
+            .iter()
+            .zip(smart_wallet.owners.iter())
+            .any(|(signed, owner)| *signed && *owner != self.proposer)
+    }
+
+    /// Checks that `executor` may execute the [Transaction]: anyone, unless
+    /// [Transaction::proposer_executes] reserves it for the proposer.
+    pub fn check_executor(&self, executor: Pubkey) -> ProgramResult {
+        require!(
+            !self.proposer_executes || executor == self.proposer,
+            ExecutorNotProposer
+        );
+        Ok(())
+    }
+
+    /// Checks that `instructions` only reference writable accounts within the
+    /// [Transaction::writable_scope], if one was declared.
+    pub fn check_writable_scope(&self, instructions: &[TXInstruction]) -> ProgramResult {
+        if let Some(scope) = &self.writable_scope {
+            for meta in instructions.iter().flat_map(|ix| ix.keys.iter()) {
+                require!(
+                    !meta.is_writable || scope.contains(&meta.pubkey),
+                    AccountOutsideWritableScope
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Transfers the [SmartWallet::proposal_bond] from the proposer into the [Transaction].
+    pub fn lock_bond<'info>(
+        &mut self,
+        smart_wallet: &SmartWallet,
+        transaction: AccountInfo<'info>,
+        proposer: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+    ) -> ProgramResult {
+        let bond = smart_wallet.proposal_bond;
+        if bond == 0 {
+            return Ok(());
+        }
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(
+                &proposer.key(),
+                &transaction.key(),
+                bond,
+            ),
+            &[proposer, transaction, system_program],
+        )?;
+        self.bond = bond;
+        Ok(())
+    }
+
+    /// Moves the locked bond out of the [Transaction] to `receiver`, returning the amount.
+    pub fn release_bond(
+        &mut self,
+        transaction: &AccountInfo,
+        receiver: &AccountInfo,
+    ) -> Result<u64> {
+        let bond = self.bond;
+        if bond == 0 {
+            return Ok(0);
+        }
+        **transaction.try_borrow_mut_lamports()? =
+            unwrap_int!(transaction.lamports().checked_sub(bond));
+        **receiver.try_borrow_mut_lamports()? = unwrap_int!(receiver.lamports().checked_add(bond));
+        self.bond = 0;
+        Ok(bond)
+    }
+
+    /// Derives the address of the [TransactionReservation] of a [SmartWallet] at `index`.
+    pub fn reservation_address(smart_wallet: Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                TRANSACTION_RESERVATION_SEED,
+                &smart_wallet.to_bytes(),
+                &index.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
     /// Number of signers.
     pub fn num_signers(&self) -> usize {
         self.signers.iter().filter(|&did_sign| *did_sign).count()
     }
+
+    /// Number of signers that are not suspended on the [SmartWallet].
+    pub fn num_active_signers(&self, smart_wallet: &SmartWallet) -> usize {
+        self.signers
+            .iter()
+            .zip(smart_wallet.owners.iter())
+            .filter(|(&did_sign, owner)| did_sign && !smart_wallet.is_suspended(**owner))
+            .count()
+    }
+
+    /// Records that the owner at `owner_index` approved the instructions marked in
+    /// `approved`, and only those, unless they approve the whole [Transaction].
+    pub fn approve_instructions(
+        &mut self,
+        num_owners: usize,
+        owner_index: usize,
+        approved: Vec<bool>,
+    ) -> ProgramResult {
+        require!(self.instructions_hash.is_none(), InstructionsNotStored);
+        require!(
+            approved.len() == self.instructions.len(),
+            InstructionApprovalsMismatch
+        );
+        if self.instruction_signers.len() != num_owners {
+            self.instruction_signers = vec![vec![]; num_owners];
+        }
+        self.instruction_signers[owner_index] = approved;
+        Ok(())
+    }
+
+    /// Stores the memo of the owner at `owner_index` in [Transaction::approval_memos].
+    pub fn set_approval_memo(
+        &mut self,
+        num_owners: usize,
+        owner_index: usize,
+        memo: Vec<u8>,
+    ) -> ProgramResult {
+        require!(memo.len() <= MAX_APPROVAL_MEMO_LEN, ApprovalMemoTooLong);
+        if self.approval_memos.len() != num_owners {
+            self.approval_memos = vec![vec![]; num_owners];
+        }
+        self.approval_memos[owner_index] = memo;
+        Ok(())
+    }
+
+    /// Returns whether instruction `index` passed: at least [SmartWallet::threshold]
+    /// non-suspended owners approved the whole [Transaction] or that instruction.
+    pub fn instruction_passed(&self, smart_wallet: &SmartWallet, index: usize) -> bool {
+        let num_approvals = smart_wallet
+            .owners
+            .iter()
+            .enumerate()
+            .filter(|(owner_index, owner)| {
+                !smart_wallet.is_suspended(**owner)
+                    && (self.signers.get(*owner_index).copied().unwrap_or(false)
+                        || self
+                            .instruction_signers
+                            .get(*owner_index)
+                            .and_then(|approved| approved.get(index))
+                            .copied()
+                            .unwrap_or(false))
+            })
+            .count();
+        num_approvals as u64 >= smart_wallet.threshold
+    }
+
+    /// Returns whether instruction `index` was rejected: so many non-suspended owners
+    /// approved a subset of the instructions without it that [SmartWallet::threshold]
+    /// can no longer be reached.
+    pub fn instruction_rejected(&self, smart_wallet: &SmartWallet, index: usize) -> bool {
+        let num_undecided = smart_wallet
+            .owners
+            .iter()
+            .enumerate()
+            .filter(|(owner_index, owner)| {
+                let excluded = !self.signers.get(*owner_index).copied().unwrap_or(false)
+                    && self
+                        .instruction_signers
+                        .get(*owner_index)
+                        .and_then(|approved| approved.get(index))
+                        == Some(&false);
+                !smart_wallet.is_suspended(**owner) && !excluded
+            })
+            .count();
+        (num_undecided as u64) < smart_wallet.threshold
+    }
+
+    /// Returns true if some instructions passed and every other one was rejected, so
+    /// the [Transaction] can run the passed ones without dropping any instruction
+    /// still collecting approvals.
+    pub fn instructions_settled(&self, smart_wallet: &SmartWallet) -> bool {
+        let passed = self.passed_instructions(smart_wallet, self.instructions.len());
+        passed.contains(&true)
+            && passed
+                .iter()
+                .enumerate()
+                .all(|(index, passed)| *passed || self.instruction_rejected(smart_wallet, index))
+    }
+
+    /// Returns, for each of the `num_instructions` instructions, whether it passed.
+    /// Execution runs only those that did.
+    pub fn passed_instructions(
+        &self,
+        smart_wallet: &SmartWallet,
+        num_instructions: usize,
+    ) -> Vec<bool> {
+        (0..num_instructions)
+            .map(|index| self.instruction_passed(smart_wallet, index))
+            .collect()
+    }
+
+    /// Time at which the [Transaction] becomes stale, or [NO_ETA] if it has no ETA
+    /// or the [SmartWallet] has [NO_GRACE_PERIOD].
+    pub fn expires_at(&self, smart_wallet: &SmartWallet) -> Option<i64> {
+        if self.eta == NO_ETA || smart_wallet.grace_period == NO_GRACE_PERIOD {
+            return Some(NO_ETA);
+        }
+        self.eta.checked_add(smart_wallet.grace_period)
+    }
+
+    /// Returns true if the [Transaction] is still pending at `now` but past its grace
+    /// period, whether or not it ever reached the threshold.
+    pub fn is_stale(&self, smart_wallet: &SmartWallet, now: i64) -> Result<bool> {
+        if self.executed_at != -1 || self.cancelled || self.expired {
+            return Ok(false);
+        }
+        let expires_at = unwrap_int!(self.expires_at(smart_wallet));
+        Ok(expires_at != NO_ETA && now >= expires_at)
+    }
+
+    /// Marks the [Transaction] as expired, slashing its bond to the treasury. It must
+    /// be past its grace period. Returns the bond slashed.
+    pub fn expire(
+        &mut self,
+        smart_wallet: &SmartWallet,
+        transaction: &AccountInfo,
+        treasury: &AccountInfo,
+        now: i64,
+    ) -> Result<u64> {
+        require!(self.is_stale(smart_wallet, now)?, TransactionNotExpired);
+        self.transition(TransactionState::Expired)?;
+        let bond_slashed = self.release_bond(transaction, treasury)?;
+        self.expired = true;
+        Ok(bond_slashed)
+    }
+
+    /// Moves the [Transaction] to `state`, failing unless
+    /// [TransactionState::can_transition] allows it.
+    pub fn transition(&mut self, state: TransactionState) -> ProgramResult {
+        if self.state != state {
+            require!(self.state.can_transition(state), InvalidStateTransition);
+            self.state = state;
+        }
+        Ok(())
+    }
+
+    /// Moves a pending [Transaction] to [TransactionState::Approved] or back to
+    /// [TransactionState::Active], depending on whether [SmartWallet::threshold]
+    /// active owners approved it.
+    pub fn refresh_approval_state(&mut self, smart_wallet: &SmartWallet) -> ProgramResult {
+        if (self.num_active_signers(smart_wallet) as u64) >= smart_wallet.threshold {
+            self.transition(TransactionState::Approved)
+        } else {
+            self.transition(TransactionState::Active)
+        }
+    }
+
+    /// Checks that owners may approve the [Transaction]: it is pending, and the owner
+    /// set has not changed since it was proposed.
+    pub fn check_approvable(&self, smart_wallet: &SmartWallet) -> ProgramResult {
+        require!(self.state != TransactionState::Draft, TransactionIsDraft);
+        require!(self.executed_at == -1, AlreadyExecuted);
+        require!(!self.expired, TransactionAlreadyExpired);
+        require!(!self.cancelled, TransactionCancelled);
+        require!(
+            self.owner_set_seqno == smart_wallet.owner_set_seqno,
+            OwnerSetChanged
+        );
+        Ok(())
+    }
+
+    /// Checks that all [Transaction::dependencies] executed, each passed in `accounts`
+    /// as its executed [Transaction] or, once that is closed, its [ExecutionReceipt].
+    pub fn check_dependencies<'info>(&self, accounts: &[AccountInfo<'info>]) -> ProgramResult {
+        if self.dependencies.is_empty() {
+            return Ok(());
+        }
+        let mut executed: Vec<u64> = vec![];
+        for info in accounts.iter().filter(|info| info.owner == &crate::ID) {
+            if let Ok(tx) = Account::<Transaction>::try_from(info) {
+                if tx.smart_wallet == self.smart_wallet && tx.executed_at != -1 {
+                    executed.push(tx.index);
+                }
+            } else if let Ok(receipt) = Account::<ExecutionReceipt>::try_from(info) {
+                if receipt.smart_wallet == self.smart_wallet {
+                    executed.push(receipt.index);
+                }
+            }
+        }
+        require!(
+            self.dependencies
+                .iter()
+                .all(|index| executed.contains(index)),
+            DependencyNotExecuted
+        );
+        Ok(())
+    }
+
+    /// Checks that the [Transaction] can be executed at `now`.
+    pub fn check_executable(&self, smart_wallet: &SmartWallet, now: i64) -> ProgramResult {
+        require!(self.state != TransactionState::Draft, TransactionIsDraft);
+        require!(!self.expired, TransactionAlreadyExpired);
+        match self.readiness(smart_wallet, now)? {
+            TransactionReadiness::Ready => Ok(()),
+            TransactionReadiness::AlreadyExecuted => Err(ErrorCode::AlreadyExecuted.into()),
+            TransactionReadiness::OwnerSetChanged => Err(ErrorCode::OwnerSetChanged.into()),
+            TransactionReadiness::BelowThreshold => Err(ErrorCode::NotEnoughSigners.into()),
+            TransactionReadiness::EtaNotReached => Err(ErrorCode::TransactionNotReady.into()),
+            TransactionReadiness::Stale => Err(ErrorCode::TransactionIsStale.into()),
+            TransactionReadiness::Cancelled => Err(ErrorCode::TransactionCancelled.into()),
+        }
+    }
+
+    /// Computes whether the [Transaction] can be executed at `now`.
+    pub fn readiness(&self, smart_wallet: &SmartWallet, now: i64) -> Result<TransactionReadiness> {
+        if self.executed_at != -1 {
+            return Ok(TransactionReadiness::AlreadyExecuted);
+        }
+        if self.cancelled {
+            return Ok(TransactionReadiness::Cancelled);
+        }
+        if self.owner_set_seqno != smart_wallet.owner_set_seqno {
+            return Ok(TransactionReadiness::OwnerSetChanged);
+        }
+        if (self.num_active_signers(smart_wallet) as u64) < smart_wallet.threshold
+            && !self.instructions_settled(smart_wallet)
+        {
+            return Ok(TransactionReadiness::BelowThreshold);
+        }
+        if self.eta != NO_ETA {
+            if now < self.eta {
+                return Ok(TransactionReadiness::EtaNotReached);
+            }
+            let expires_at = unwrap_int!(self.expires_at(smart_wallet));
+            if expires_at != NO_ETA && now >= expires_at {
+                return Ok(TransactionReadiness::Stale);
+            }
+        }
+        Ok(TransactionReadiness::Ready)
+    }
 }
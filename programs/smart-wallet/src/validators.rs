@@ -9,12 +9,27 @@ impl<'info> Validate<'info> for CreateSmartWallet<'info> {
     }
 }
 
+impl<'info> Validate<'info> for SuspendOwner<'info> {
+    fn validate(&self) -> ProgramResult {
+        self.smart_wallet.active_owner_index(self.owner.key())?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for OwnerInvokeInstruction<'info> {
+    fn validate(&self) -> ProgramResult {
+        self.smart_wallet.active_owner_index(self.owner.key())?;
+        Ok(())
+    }
+}
+
 impl<'info> Validate<'info> for ExecuteInstructions<'info> {
     fn validate(&self) -> ProgramResult {
        // ensure that the owner is a signer
         // this prevents common frontrunning/flash loan attacks
-        self.smart_wallet.owner_index(self.authority_a.key())?;
-        self.smart_wallet.owner_index(self.authority_b.key())?;
+        self.smart_wallet.active_owner_index(self.authority_a.key())?;
+        self.smart_wallet.active_owner_index(self.authority_b.key())?;
+        self.smart_wallet.check_executor(self.authority_a.key())?;
 
         Ok(())
     }
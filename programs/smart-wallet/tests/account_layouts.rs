@@ -0,0 +1,585 @@
+//! Golden-vector tests for account layouts.
+//!
+//! Each account type is serialized with fixed field values and compared against
+//! a fixture in `tests/fixtures`, so any change to a layout fails loudly. Run with
+//! `GOLDEN_UPDATE=1` to rewrite the fixtures after an intended layout change.
+
+mod common;
+
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use common::key;
+use smart_wallet::{
+    AllowedMint, AllowedParticipant, AllowedProposer, BonusBudget, ClaimWindow, CrankPermission,
+    EnrollmentFee, ExecutionReceipt, ExecutorReimbursement, FeeExemption, GidRange, GidRemap,
+    InvokerPermission, InvokerWhitelist, Milestone, MilestoneClaim, OneShot, OwnerHeartbeat,
+    OwnerNotification, OwnerRewards, ParticipantAllowlist, ProposalRateLimit, ProposerActivity,
+    ProposerGate, ProtocolConfig, Quest, QuestClaim, QuestRequirement, RentPool, RewardCurve,
+    RewardEmission, RewardRounding, Rollup, SealedParams, SmartWallet, Stake, StakeSponsor,
+    SubaccountInfo, SubaccountType, TXAccountMeta, TXInstruction, Ticket, Transaction,
+    TransactionReservation, TransactionState, YieldVenue,
+};
+use std::{env, fmt::Debug, fs, path::PathBuf};
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(format!("{}.hex", name))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .chunks(32)
+        .map(|line| {
+            line.iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+        .collect()
+}
+
+/// Checks that `account` serializes to its fixture, the fixture decodes back to
+/// `account`, and the account fits in `space` bytes.
+fn check<T>(name: &str, account: T, space: usize)
+where
+    T: AccountSerialize + AccountDeserialize + Debug + PartialEq,
+{
+    let mut bytes = vec![];
+    account.try_serialize(&mut bytes).unwrap();
+    assert!(
+        bytes.len() <= space,
+        "{} uses {} bytes but space() is {}",
+        name,
+        bytes.len(),
+        space
+    );
+
+    let path = fixture_path(name);
+    if env::var("GOLDEN_UPDATE").is_ok() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, to_hex(&bytes)).unwrap();
+    }
+    let fixture = from_hex(&fs::read_to_string(&path).unwrap());
+    assert_eq!(to_hex(&bytes), to_hex(&fixture), "{} layout changed", name);
+
+    let decoded = T::try_deserialize(&mut fixture.as_slice()).unwrap();
+    assert_eq!(decoded, account);
+}
+
+#[test]
+fn smart_wallet() {
+    let mut reserved = [0u64; 2];
+    reserved[0] = 7;
+    check(
+        "smart_wallet",
+        SmartWallet {
+            base: key(1),
+            bump: 254,
+            threshold: 2,
+            minimum_delay: 3600,
+            grace_period: 1_209_600,
+            owner_set_seqno: 3,
+            num_transactions: 5,
+            owners: vec![key(2), key(3), key(4)],
+            executor_reimbursement: Some(ExecutorReimbursement {
+                vault_index: 1,
+                vault_bump: 253,
+                fee_lamports: 5000,
+                tip_lamports: 1000,
+            }),
+            suspended_owners: vec![key(4)],
+            executor_allowlist: vec![key(2)],
+            event_seq: 42,
+            audited_programs_only: true,
+            num_executed: 8,
+            last_executed_at: 1_640_000_600,
+            last_approved_at: 1_640_000_700,
+            proposer_gate: Some(ProposerGate {
+                mint: key(9),
+                min_amount: 1_000,
+            }),
+            proposal_bond: 50_000_000,
+            proposal_rate_limit: Some(ProposalRateLimit {
+                max_proposals: 3,
+                window: 86_400,
+            }),
+            veto_authority: Some(key(10)),
+            execution_receipts: true,
+            expiry_cursor: 41,
+            reserved,
+        },
+        SmartWallet::space(3),
+    );
+}
+
+#[test]
+fn protocol_config() {
+    check(
+        "protocol_config",
+        ProtocolConfig {
+            bump: 240,
+            authority: key(20),
+            max_owners: 20,
+            max_instructions: 10,
+            default_grace_period: 1_209_600,
+            protocol_fee_bps: 250,
+            fee_recipient: key(21),
+            security_council: key(22),
+            paused: true,
+            audited_programs: vec![key(23), key(24)],
+            reserved: [0; 8],
+        },
+        ProtocolConfig::space(),
+    );
+}
+
+#[test]
+fn transaction() {
+    check(
+        "transaction",
+        Transaction {
+            smart_wallet: key(1),
+            index: 9,
+            bump: 252,
+            proposer: key(2),
+            instructions: vec![TXInstruction {
+                program_id: key(5),
+                keys: vec![TXAccountMeta {
+                    pubkey: key(6),
+                    is_signer: true,
+                    is_writable: false,
+                }],
+                data: vec![1, 2, 3],
+            }],
+            signers: vec![true, false, true],
+            owner_set_seqno: 3,
+            eta: 1_640_000_000,
+            executor: key(3),
+            executed_at: -1,
+            expired: true,
+            bond: 50_000_000,
+            instructions_hash: Some([9; 32]),
+            writable_scope: Some(vec![key(7)]),
+            instructions_executed: 1,
+            proposer_executes: true,
+            instruction_signers: vec![vec![true], vec![], vec![false]],
+            cancelled: true,
+            approval_memos: vec![b"lgtm".to_vec(), vec![], vec![]],
+            dependencies: vec![3, 5],
+            state: TransactionState::Approved,
+            approval_nonces: vec![0, 2, 0],
+        },
+        usize::MAX,
+    );
+}
+
+#[test]
+fn transaction_reservation() {
+    check(
+        "transaction_reservation",
+        TransactionReservation {
+            bump: 239,
+            smart_wallet: key(1),
+            proposer: key(2),
+            index: 10,
+        },
+        TransactionReservation::space(),
+    );
+}
+
+#[test]
+fn stake() {
+    check(
+        "stake",
+        Stake {
+            bump: 251,
+            duration: 86_400,
+            genesis_epoch: 1_640_000_000i64.to_le_bytes().to_vec(),
+            name: b"Genesis".to_vec(),
+            reward_pot: 1_000_000,
+            protected_gids: vec![1, 2],
+            uuid: b"0f8fad5b-d9cb-469f-a165-70867728950e".to_vec(),
+            reward_curve: RewardCurve::InverseSupply { max_rate: 10 },
+            total_mints: 12,
+            smart_wallet: key(1),
+            index: 4,
+            reward_mint: key(7),
+            reward_vault: key(8),
+            distribution_interval: 3600,
+            rewards_credited: 500,
+            max_rewards_per_owner: 10_000,
+            gid_remaps: vec![GidRemap { from: 3, to: 1 }],
+            mint_allowlist: true,
+            min_hold_seconds: 600,
+            enrollment_fee: Some(EnrollmentFee {
+                mint: Some(key(9)),
+                amount: 25,
+                destination: key(10),
+            }),
+            metadata_uri: "https://example.com/stake.json".to_string(),
+            require_master_edition: true,
+            burn_bps: 500,
+            protected_gid_ranges: vec![GidRange {
+                start: 100,
+                end: 199,
+            }],
+            reward_emission: Some(RewardEmission {
+                epoch_duration: 86_400,
+                max_per_epoch: 1_000_000,
+                epoch_start: 1_640_000_900,
+                minted_in_epoch: 4_000,
+            }),
+            claim_window: Some(ClaimWindow::MonthEnd { days: 7 }),
+            participant_allowlist: Some(ParticipantAllowlist::Merkle { root: [3; 32] }),
+            yield_venue: Some(YieldVenue {
+                program_id: key(20),
+                deposited: 400_000,
+            }),
+            owner_ata_claims: true,
+            sponsors: vec![StakeSponsor {
+                smart_wallet: key(21),
+                funded: 250_000,
+            }],
+            reward_rounding: RewardRounding::Round,
+            sealed_params: Some(SealedParams {
+                commitment: [22; 32],
+                ciphertext: b"sealed".to_vec(),
+            }),
+            viewers: vec![key(23)],
+            distribution_cursor: key(24),
+            bonus_budget: Some(BonusBudget {
+                vault: key(25),
+                funded: 5_000,
+                spent: 1_200,
+            }),
+        },
+        Stake::space(2, 1),
+    );
+}
+
+#[test]
+fn ticket() {
+    check(
+        "ticket",
+        Ticket {
+            enrollment_epoch: 1_640_000_100i64.to_le_bytes().to_vec(),
+            bump: 250,
+            gid: 3,
+            mint: key(11),
+            owner: key(12),
+            pending_until: 1_640_000_700,
+        },
+        Ticket::space(),
+    );
+}
+
+#[test]
+fn rollup() {
+    check(
+        "rollup",
+        Rollup {
+            bump: 249,
+            timestamp: 1_640_000_200i64.to_le_bytes().to_vec(),
+            gid: 3,
+            mints: 4,
+            owner: key(12),
+            accrued: 77,
+            stake: key(13),
+            accrued_dust: 1 << 63,
+            dust_owed: true,
+        },
+        Rollup::space(),
+    );
+}
+
+#[test]
+fn allowed_mint() {
+    check(
+        "allowed_mint",
+        AllowedMint {
+            bump: 248,
+            stake: key(13),
+            mint: key(11),
+        },
+        AllowedMint::space(),
+    );
+}
+
+#[test]
+fn allowed_participant() {
+    check(
+        "allowed_participant",
+        AllowedParticipant {
+            bump: 234,
+            stake: key(13),
+            owner: key(12),
+        },
+        AllowedParticipant::space(),
+    );
+}
+
+#[test]
+fn fee_exemption() {
+    check(
+        "fee_exemption",
+        FeeExemption {
+            bump: 233,
+            stake: key(13),
+            account: key(22),
+        },
+        FeeExemption::space(),
+    );
+}
+
+#[test]
+fn allowed_proposer() {
+    check(
+        "allowed_proposer",
+        AllowedProposer {
+            bump: 236,
+            smart_wallet: key(1),
+            proposer: key(2),
+        },
+        AllowedProposer::space(),
+    );
+}
+
+#[test]
+fn rent_pool() {
+    check(
+        "rent_pool",
+        RentPool {
+            bump: 241,
+            smart_wallet: key(1),
+            outstanding: 2_039_280,
+        },
+        RentPool::space(),
+    );
+}
+
+#[test]
+fn owner_notification() {
+    check(
+        "owner_notification",
+        OwnerNotification {
+            bump: 235,
+            smart_wallet: key(1),
+            owner: key(2),
+            notification_key: key(3),
+            webhook_hash: [4; 32],
+        },
+        OwnerNotification::space(),
+    );
+}
+
+#[test]
+fn one_shot() {
+    let instruction = TXInstruction {
+        program_id: key(5),
+        keys: vec![TXAccountMeta {
+            pubkey: key(6),
+            is_signer: true,
+            is_writable: true,
+        }],
+        data: vec![4, 5, 6],
+    };
+    check(
+        "one_shot",
+        OneShot {
+            bump: 232,
+            smart_wallet: key(1),
+            key: key(3),
+            nonce: 2,
+            wallet_index: 1,
+            instruction: instruction.clone(),
+            valid_from: 1_640_002_000,
+            valid_until: 1_640_005_600,
+            executed_at: -1,
+        },
+        OneShot::space(&instruction),
+    );
+}
+
+#[test]
+fn execution_receipt() {
+    check(
+        "execution_receipt",
+        ExecutionReceipt {
+            bump: 231,
+            smart_wallet: key(1),
+            index: 7,
+            digest: [4; 32],
+            executor: key(8),
+            slot: 120_000_000,
+            executed_at: 1_640_003_600,
+        },
+        ExecutionReceipt::space(),
+    );
+}
+
+#[test]
+fn owner_heartbeat() {
+    check(
+        "owner_heartbeat",
+        OwnerHeartbeat {
+            bump: 233,
+            smart_wallet: key(1),
+            owner: key(2),
+            last_seen: 1_640_001_000,
+            num_heartbeats: 14,
+        },
+        OwnerHeartbeat::space(),
+    );
+}
+
+#[test]
+fn owner_rewards() {
+    check(
+        "owner_rewards",
+        OwnerRewards {
+            bump: 247,
+            stake: key(13),
+            owner: key(12),
+            claimed: 321,
+        },
+        OwnerRewards::space(),
+    );
+}
+
+#[test]
+fn crank_permission() {
+    check(
+        "crank_permission",
+        CrankPermission {
+            bump: 246,
+            stake: key(13),
+            owner: key(12),
+            crank: key(14),
+            destination: key(15),
+            interval: 86_400,
+            last_claimed_at: 1_640_000_300,
+        },
+        CrankPermission::space(),
+    );
+}
+
+#[test]
+fn milestone() {
+    check(
+        "milestone",
+        Milestone {
+            bump: 245,
+            stake: key(13),
+            index: 1,
+            min_claimed: 5000,
+            name: "Staked 5k".to_string(),
+            symbol: "GOKI".to_string(),
+            uri: "https://example.com/badge.json".to_string(),
+            num_minted: 2,
+        },
+        Milestone::space(),
+    );
+}
+
+#[test]
+fn milestone_claim() {
+    check(
+        "milestone_claim",
+        MilestoneClaim {
+            bump: 244,
+            milestone: key(16),
+            owner: key(12),
+            mint: key(17),
+            claimed_at: 1_640_000_400,
+        },
+        MilestoneClaim::space(),
+    );
+}
+
+#[test]
+fn quest() {
+    check(
+        "quest",
+        Quest {
+            bump: 243,
+            stake: key(13),
+            index: 2,
+            requirement: QuestRequirement::StakedThrough {
+                start: 1_640_000_000,
+                end: 1_650_000_000,
+            },
+            bonus: 100,
+            num_claims: 6,
+        },
+        Quest::space(),
+    );
+}
+
+#[test]
+fn quest_claim() {
+    check(
+        "quest_claim",
+        QuestClaim {
+            bump: 242,
+            quest: key(18),
+            rollup: key(19),
+            amount: 100,
+            claimed_at: 1_640_000_500,
+        },
+        QuestClaim::space(),
+    );
+}
+
+#[test]
+fn proposer_activity() {
+    check(
+        "proposer_activity",
+        ProposerActivity {
+            bump: 238,
+            smart_wallet: key(1),
+            proposer: key(2),
+            window_start: 1_640_000_800,
+            num_proposals: 2,
+        },
+        ProposerActivity::space(),
+    );
+}
+
+#[test]
+fn subaccount_info() {
+    check(
+        "subaccount_info",
+        SubaccountInfo {
+            smart_wallet: key(1),
+            subaccount_type: SubaccountType::OwnerInvoker,
+            index: 3,
+        },
+        SubaccountInfo::space(),
+    );
+}
+
+#[test]
+fn invoker_whitelist() {
+    check(
+        "invoker_whitelist",
+        InvokerWhitelist {
+            bump: 237,
+            smart_wallet: key(1),
+            index: 3,
+            permissions: vec![InvokerPermission {
+                program_id: key(5),
+                discriminator: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            }],
+        },
+        InvokerWhitelist::space(1),
+    );
+}
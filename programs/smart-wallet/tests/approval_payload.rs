@@ -0,0 +1,68 @@
+//! Tests for the payload and message owners sign to approve a [Transaction] offline.
+//!
+//! A signed approval must not outlive what the owner saw when signing: both
+//! bind the instructions, and an owner's unapproval invalidates the approvals they
+//! signed before it.
+
+mod common;
+
+use common::{instruction, key, transaction, NUM_OWNERS};
+use smart_wallet::Transaction;
+
+#[test]
+fn payload_binds_instructions() {
+    let tx = transaction();
+    let other = Transaction {
+        instructions: vec![instruction(vec![2])],
+        ..transaction()
+    };
+    assert_ne!(
+        tx.approval_payload(0).unwrap(),
+        other.approval_payload(0).unwrap()
+    );
+
+    let committed = Transaction {
+        instructions: vec![],
+        instructions_hash: Some(Transaction::hash_instructions(&tx.instructions).unwrap()),
+        ..transaction()
+    };
+    assert_eq!(
+        tx.approval_payload(0).unwrap(),
+        committed.approval_payload(0).unwrap()
+    );
+}
+
+#[test]
+fn unapproving_invalidates_signed_approvals() {
+    let mut tx = transaction();
+    let signed: Vec<Vec<u8>> = (0..NUM_OWNERS)
+        .map(|i| tx.approval_payload(i).unwrap())
+        .collect();
+
+    tx.bump_approval_nonce(NUM_OWNERS, 1).unwrap();
+    assert_eq!(tx.approval_nonces, vec![0, 1, 0]);
+    assert_ne!(tx.approval_payload(1).unwrap(), signed[1]);
+    assert_eq!(tx.approval_payload(0).unwrap(), signed[0]);
+    assert_eq!(tx.approval_payload(2).unwrap(), signed[2]);
+
+    let resigned = tx.approval_payload(1).unwrap();
+    tx.bump_approval_nonce(NUM_OWNERS, 1).unwrap();
+    assert_ne!(tx.approval_payload(1).unwrap(), resigned);
+}
+
+#[test]
+fn unapproving_invalidates_signed_messages() {
+    let mut tx = transaction();
+    let message = |tx: &Transaction, i: usize| {
+        tx.approval_message("goki.so", key(10 + i as u8), i, key(9), 1_700_000_000)
+            .unwrap()
+    };
+    let signed: Vec<String> = (0..NUM_OWNERS).map(|i| message(&tx, i)).collect();
+    assert!(signed[1].contains("\nNonce: 0\n"));
+
+    tx.bump_approval_nonce(NUM_OWNERS, 1).unwrap();
+    assert_ne!(message(&tx, 1), signed[1]);
+    assert!(message(&tx, 1).contains("\nNonce: 1\n"));
+    assert_eq!(message(&tx, 0), signed[0]);
+    assert_eq!(message(&tx, 2), signed[2]);
+}
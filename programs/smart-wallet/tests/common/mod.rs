@@ -0,0 +1,48 @@
+//! Fixtures shared by the tests of the program and of the crates built on it.
+//!
+//! Each test binary uses only some of them.
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use smart_wallet::{SmartWallet, TXInstruction, Transaction, TransactionState};
+
+/// Number of owners of [smart_wallet].
+pub const NUM_OWNERS: usize = 3;
+
+/// A distinct key for every `n`.
+pub fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+/// An instruction of program `key(5)` without accounts.
+pub fn instruction(data: Vec<u8>) -> TXInstruction {
+    TXInstruction {
+        program_id: key(5),
+        keys: vec![],
+        data,
+    }
+}
+
+/// A 2-of-3 wallet owned by `key(1)`, `key(2)` and `key(3)`.
+pub fn smart_wallet() -> SmartWallet {
+    SmartWallet {
+        threshold: 2,
+        owners: vec![key(1), key(2), key(3)],
+        ..SmartWallet::default()
+    }
+}
+
+/// An active, unapproved [Transaction] #4 of the wallet at `key(1)`, proposed by
+/// `key(2)`, running one [instruction].
+pub fn transaction() -> Transaction {
+    Transaction {
+        smart_wallet: key(1),
+        index: 4,
+        proposer: key(2),
+        instructions: vec![instruction(vec![1])],
+        signers: vec![false; NUM_OWNERS],
+        executed_at: -1,
+        state: TransactionState::Active,
+        ..Transaction::default()
+    }
+}
@@ -0,0 +1,72 @@
+//! Tests for approving some of a [Transaction]'s instructions.
+//!
+//! Executing runs only the instructions that passed and settles the whole
+//! [Transaction], so it must wait until every other instruction was rejected
+//! rather than drop those still collecting approvals.
+
+mod common;
+
+use common::{instruction, smart_wallet, transaction, NUM_OWNERS};
+use smart_wallet::{Transaction, TransactionReadiness, NO_ETA};
+
+/// A [Transaction] of two instructions, without an ETA.
+fn bundle() -> Transaction {
+    Transaction {
+        instructions: vec![instruction(vec![1]), instruction(vec![2])],
+        eta: NO_ETA,
+        ..transaction()
+    }
+}
+
+fn approve(tx: &mut Transaction, owner_index: usize, approved: [bool; 2]) {
+    tx.approve_instructions(NUM_OWNERS, owner_index, approved.to_vec())
+        .unwrap();
+}
+
+fn readiness(tx: &Transaction) -> TransactionReadiness {
+    tx.readiness(&smart_wallet(), 0).unwrap()
+}
+
+#[test]
+fn pending_instructions_block_execution() {
+    let mut tx = bundle();
+    approve(&mut tx, 0, [true, false]);
+    approve(&mut tx, 1, [true, true]);
+    assert_eq!(
+        tx.passed_instructions(&smart_wallet(), 2),
+        vec![true, false]
+    );
+    assert!(!tx.instruction_rejected(&smart_wallet(), 1));
+    assert_eq!(readiness(&tx), TransactionReadiness::BelowThreshold);
+
+    approve(&mut tx, 2, [false, false]);
+    assert!(tx.instruction_rejected(&smart_wallet(), 1));
+    assert_eq!(readiness(&tx), TransactionReadiness::Ready);
+}
+
+#[test]
+fn rejected_instructions_are_left_out() {
+    let mut tx = bundle();
+    approve(&mut tx, 0, [true, false]);
+    assert_eq!(readiness(&tx), TransactionReadiness::BelowThreshold);
+
+    approve(&mut tx, 1, [true, false]);
+    assert!(tx.instruction_rejected(&smart_wallet(), 1));
+    assert_eq!(readiness(&tx), TransactionReadiness::Ready);
+}
+
+#[test]
+fn whole_approvals_count_for_every_instruction() {
+    let mut tx = bundle();
+    approve(&mut tx, 0, [false, true]);
+    tx.signers[1] = true;
+    assert!(!tx.instruction_rejected(&smart_wallet(), 0));
+    assert_eq!(
+        tx.passed_instructions(&smart_wallet(), 2),
+        vec![false, true]
+    );
+    assert_eq!(readiness(&tx), TransactionReadiness::BelowThreshold);
+
+    tx.signers[0] = true;
+    assert_eq!(readiness(&tx), TransactionReadiness::Ready);
+}
@@ -0,0 +1,144 @@
+//! Tests for governing the [ProtocolConfig] from a smart wallet.
+//!
+//! The wallet proposes `govern_protocol_config` as one of its transactions, and the
+//! program signs it as the wallet PDA when executing. The update only applies if the
+//! wallet governs it, and runs even while the protocol is paused.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use common::key;
+use smart_wallet::{
+    ProtocolConfig, ProtocolConfigUpdate, SmartWallet, TXAccountMeta, TXInstruction,
+};
+
+/// The wallet executing the updates, governing the config and holding the pause.
+fn wallet() -> Pubkey {
+    SmartWallet::address(key(1)).0
+}
+
+fn config() -> ProtocolConfig {
+    ProtocolConfig {
+        authority: wallet(),
+        security_council: wallet(),
+        ..ProtocolConfig::default()
+    }
+}
+
+/// The instruction a wallet transaction stores to apply `update`.
+fn govern(update: ProtocolConfigUpdate) -> TXInstruction {
+    let accounts = smart_wallet::accounts::GovernProtocolConfig {
+        protocol_config: ProtocolConfig::address().0,
+        smart_wallet: wallet(),
+    };
+    TXInstruction {
+        program_id: smart_wallet::ID,
+        keys: accounts
+            .to_account_metas(None)
+            .iter()
+            .map(|meta| TXAccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: smart_wallet::instruction::GovernProtocolConfig { update }.data(),
+    }
+}
+
+/// Decodes and applies a stored [govern] instruction as its executing wallet.
+fn execute(config: &mut ProtocolConfig, ix: &TXInstruction) -> ProgramResult {
+    let signer = ix.keys.iter().find(|meta| meta.is_signer).unwrap();
+    let update = ProtocolConfigUpdate::try_from_slice(&ix.data[8..])?;
+    config.apply_update(signer.pubkey, update)
+}
+
+#[test]
+fn wallet_transaction_updates_the_config() {
+    let ix = govern(ProtocolConfigUpdate::SetProtocolFee {
+        protocol_fee_bps: 250,
+        fee_recipient: key(2),
+    });
+    assert!(ProtocolConfig::is_governance(&ix));
+    assert_eq!(
+        ix.keys
+            .iter()
+            .filter(|meta| meta.is_signer)
+            .map(|meta| meta.pubkey)
+            .collect::<Vec<_>>(),
+        vec![wallet()]
+    );
+
+    let mut config = config();
+    execute(&mut config, &ix).unwrap();
+    assert_eq!(config.protocol_fee_bps, 250);
+    assert_eq!(config.fee_recipient, key(2));
+
+    let program = key(3);
+    execute(
+        &mut config,
+        &govern(ProtocolConfigUpdate::AddAuditedProgram { program }),
+    )
+    .unwrap();
+    assert_eq!(config.audited_programs, vec![program]);
+    execute(
+        &mut config,
+        &govern(ProtocolConfigUpdate::RemoveAuditedProgram { program }),
+    )
+    .unwrap();
+    assert!(config.audited_programs.is_empty());
+}
+
+#[test]
+fn only_the_governing_wallet_updates_the_config() {
+    let mut config = ProtocolConfig {
+        authority: key(4),
+        ..config()
+    };
+    let fee = ProtocolConfigUpdate::SetProtocolFee {
+        protocol_fee_bps: 250,
+        fee_recipient: key(2),
+    };
+    assert!(execute(&mut config, &govern(fee)).is_err());
+    assert_eq!(config.protocol_fee_bps, 0);
+
+    let pause = govern(ProtocolConfigUpdate::SetPaused { paused: true });
+    execute(&mut config, &pause).unwrap();
+    assert!(config.paused);
+
+    let council = govern(ProtocolConfigUpdate::SetSecurityCouncil {
+        security_council: key(5),
+    });
+    assert!(execute(&mut config, &council).is_err());
+    config.authority = wallet();
+    execute(&mut config, &council).unwrap();
+    assert_eq!(config.security_council, key(5));
+    let unpause = govern(ProtocolConfigUpdate::SetPaused { paused: false });
+    assert!(execute(&mut config, &unpause).is_err());
+    assert!(config.paused);
+}
+
+#[test]
+fn paused_wallet_can_lift_the_pause() {
+    let mut config = ProtocolConfig {
+        paused: true,
+        ..config()
+    };
+    let smart_wallet = common::smart_wallet();
+    let unpause = govern(ProtocolConfigUpdate::SetPaused { paused: false });
+    let transfer = TXInstruction {
+        program_id: key(6),
+        keys: vec![],
+        data: vec![],
+    };
+    assert!(config
+        .check_invoke(&smart_wallet, &[unpause.clone(), transfer])
+        .is_err());
+    config
+        .check_invoke(&smart_wallet, std::slice::from_ref(&unpause))
+        .unwrap();
+
+    execute(&mut config, &unpause).unwrap();
+    assert!(!config.paused);
+}
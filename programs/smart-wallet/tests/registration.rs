@@ -0,0 +1,71 @@
+//! Tests for checking a mint before registering it or sponsoring its rent.
+//!
+//! `sponsor_rent` pays out of the wallet's [smart_wallet::RentPool], so it must only
+//! fund the accounts of mints `register_entity` would accept: throwaway tokens
+//! anyone can mint are rejected by both.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use common::key;
+use smart_wallet::Stake;
+use spl_token::solana_program::program_option::COption;
+use spl_token::solana_program::program_pack::Pack;
+
+/// The packed data of a mint of `supply` with `decimals`.
+fn mint_data(supply: u64, decimals: u8) -> Vec<u8> {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+/// Checks whether `owner` may register a mint of `supply` with `decimals` in `stake`,
+/// without any remaining accounts.
+fn check(stake: &Stake, supply: u64, decimals: u8) -> ProgramResult {
+    let (mint_key, owner) = (key(7), key(8));
+    let mut lamports = 0;
+    let mut data = mint_data(supply, decimals);
+    let info = AccountInfo::new(
+        &mint_key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &spl_token::ID,
+        false,
+        0,
+    );
+    let mint = Account::<Mint>::try_from(&info)?;
+    stake.check_registrable(key(6), &mint, owner, &[])
+}
+
+#[test]
+fn throwaway_mints_are_rejected() {
+    let stake = Stake::default();
+    check(&stake, 1, 0).unwrap();
+    assert!(check(&stake, 1_000, 0).is_err());
+    assert!(check(&stake, 1, 6).is_err());
+}
+
+#[test]
+fn mints_outside_the_allowlist_are_rejected() {
+    let stake = Stake {
+        mint_allowlist: true,
+        ..Stake::default()
+    };
+    assert!(check(&stake, 1, 0).is_err());
+
+    let stake = Stake {
+        require_master_edition: true,
+        ..Stake::default()
+    };
+    assert!(check(&stake, 1, 0).is_err());
+}
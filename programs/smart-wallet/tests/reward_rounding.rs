@@ -0,0 +1,90 @@
+//! Conservation tests for reward rounding.
+//!
+//! Rollups are credited at irregular intervals under each [RewardRounding], and
+//! the whole rewards credited plus the dust carried in each rollup must always add
+//! up to exactly what the reward rate accrued, without exceeding the reward pot.
+
+use smart_wallet::{RewardCurve, RewardRounding, Rollup, Stake};
+
+const GENESIS: i64 = 1_600_000_000;
+const ROUNDINGS: [RewardRounding; 3] = [
+    RewardRounding::Floor,
+    RewardRounding::Round,
+    RewardRounding::Ceil,
+];
+
+fn stake(reward_rounding: RewardRounding) -> Stake {
+    Stake {
+        duration: 7 * 86_400,
+        reward_pot: 1_000_000_007,
+        total_mints: 3,
+        reward_curve: RewardCurve::InverseSupply { max_rate: 0 },
+        reward_rounding,
+        ..Stake::default()
+    }
+}
+
+fn rollup() -> Rollup {
+    Rollup {
+        mints: 1,
+        timestamp: GENESIS.to_le_bytes().to_vec(),
+        ..Rollup::default()
+    }
+}
+
+/// Rewards credited to the rollup plus its dust, in units of 2^-64.
+fn balance(rollup: &Rollup) -> i128 {
+    let dust = i128::from(rollup.accrued_dust);
+    let whole = i128::from(rollup.accrued) << 64;
+    if rollup.dust_owed {
+        whole - dust
+    } else {
+        whole + dust
+    }
+}
+
+#[test]
+fn carried_dust_conserves_accrued_rewards() {
+    for &rounding in ROUNDINGS.iter() {
+        let mut stake = stake(rounding);
+        let rate = stake.reward_rate().unwrap() as i128;
+        let mut rollups = [rollup(), rollup(), rollup()];
+        let mut now = GENESIS;
+        for step in 0..500i64 {
+            now += 1 + step % 13;
+            let rollup = &mut rollups[(step % 3) as usize];
+            stake.credit_rollup(rollup, now).unwrap();
+
+            let elapsed = i128::from(now - GENESIS);
+            assert_eq!(balance(rollup), rate * elapsed, "{:?}", rounding);
+        }
+
+        let credited: u64 = rollups.iter().map(|r| r.accrued).sum();
+        assert_eq!(credited, stake.rewards_credited, "{:?}", rounding);
+        assert!(stake.rewards_credited <= stake.reward_pot as u64);
+    }
+}
+
+#[test]
+fn credits_stop_at_reward_pot() {
+    for &rounding in ROUNDINGS.iter() {
+        let mut stake = stake(rounding);
+        let mut rollups = [rollup(), rollup(), rollup()];
+        let mut now = GENESIS;
+        for step in 0..60i64 {
+            now += 20_000 + step % 7;
+            stake
+                .credit_rollup(&mut rollups[(step % 3) as usize], now)
+                .unwrap();
+            assert!(stake.rewards_credited <= stake.reward_pot as u64);
+        }
+
+        let credited: u64 = rollups.iter().map(|r| r.accrued).sum();
+        assert_eq!(credited, stake.rewards_credited, "{:?}", rounding);
+        assert_eq!(
+            stake.rewards_credited, stake.reward_pot as u64,
+            "{:?}",
+            rounding
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Tests for what the sponsors of a [Stake] may do.
+//!
+//! Sponsors fund a [Stake], but only its own [SmartWallet] may change its
+//! parameters: otherwise a sponsor could raise the reward pot without backing it,
+//! or rewrite emission and claim settings under its own threshold.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::AccountSerialize;
+use common::key;
+use smart_wallet::{
+    ExtendStake, RevealStakeParams, SetRewardEmission, SetStakeMetadata, Stake, StakeSponsor,
+};
+
+/// The [Stake] of the wallet at `key(1)`, sponsored by the wallet at `key(2)`.
+fn stake() -> Stake {
+    Stake {
+        smart_wallet: key(1),
+        sponsors: vec![StakeSponsor {
+            smart_wallet: key(2),
+            funded: 0,
+        }],
+        ..Stake::default()
+    }
+}
+
+fn serialize<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+/// Validates the accounts of a stake setter with `try_accounts`, passing the wallet
+/// at `wallet` and the [stake].
+fn validate(wallet: Pubkey, try_accounts: impl Fn(&mut &[AccountInfo]) -> ProgramResult) -> bool {
+    let stake_key = key(3);
+    let (mut wallet_lamports, mut stake_lamports) = (0, 0);
+    let mut wallet_data = serialize(&common::smart_wallet());
+    let mut stake_data = serialize(&stake());
+    let accounts = [
+        AccountInfo::new(
+            &wallet,
+            true,
+            true,
+            &mut wallet_lamports,
+            &mut wallet_data,
+            &smart_wallet::ID,
+            false,
+            0,
+        ),
+        AccountInfo::new(
+            &stake_key,
+            false,
+            true,
+            &mut stake_lamports,
+            &mut stake_data,
+            &smart_wallet::ID,
+            false,
+            0,
+        ),
+    ];
+    try_accounts(&mut &accounts[..]).is_ok()
+}
+
+/// Validates the accounts of the stake setter `$accounts` for `$wallet`.
+macro_rules! setter {
+    ($accounts:ident, $wallet:expr) => {
+        validate($wallet, |accounts| {
+            $accounts::try_accounts(&smart_wallet::ID, accounts, &[]).map(|_| ())
+        })
+    };
+}
+
+#[test]
+fn sponsors_are_refused_on_stake_setters() {
+    let (own, sponsor) = (key(1), key(2));
+    assert!(setter!(ExtendStake, own));
+    assert!(!setter!(ExtendStake, sponsor));
+    assert!(setter!(SetRewardEmission, own));
+    assert!(!setter!(SetRewardEmission, sponsor));
+    assert!(setter!(SetStakeMetadata, own));
+    assert!(!setter!(SetStakeMetadata, sponsor));
+    assert!(setter!(RevealStakeParams, own));
+    assert!(!setter!(RevealStakeParams, sponsor));
+}
+
+#[test]
+fn sponsors_may_fund_the_stake() {
+    let stake = stake();
+    assert!(stake.is_admin(key(1)));
+    assert!(stake.is_admin(key(2)));
+    assert!(!stake.is_admin(key(4)));
+}
@@ -0,0 +1,112 @@
+//! Tests for counting tickets in and out of their rollups, and moving their epochs.
+//!
+//! Every way of withdrawing a ticket must settle its rollup first and take it out
+//! of both [Rollup::mints] and [Stake::total_mints], so the reward rate of the
+//! tickets left is computed over how many are actually staked.
+
+use smart_wallet::{RewardCurve, Rollup, Stake, Ticket, FROZEN_TICKET};
+
+const GENESIS: i64 = 1_600_000_000;
+
+fn stake() -> Stake {
+    Stake {
+        duration: 7 * 86_400,
+        reward_pot: 1_000_000,
+        total_mints: 2,
+        reward_curve: RewardCurve::InverseSupply { max_rate: 0 },
+        ..Stake::default()
+    }
+}
+
+fn rollup() -> Rollup {
+    Rollup {
+        mints: 2,
+        timestamp: GENESIS.to_le_bytes().to_vec(),
+        ..Rollup::default()
+    }
+}
+
+fn ticket(enrollment: i64, pending_until: i64) -> Ticket {
+    Ticket {
+        enrollment_epoch: enrollment.to_le_bytes().to_vec(),
+        pending_until,
+        ..Ticket::default()
+    }
+}
+
+#[test]
+fn withdrawing_credits_and_uncounts_the_ticket() {
+    let mut stake = stake();
+    let mut rollup = rollup();
+    let now = GENESIS + 3600;
+    let rate = stake.reward_rate().unwrap();
+
+    stake
+        .uncount_ticket(&ticket(GENESIS, 0), &mut rollup, now)
+        .unwrap();
+    assert_eq!(rollup.mints, 1);
+    assert_eq!(stake.total_mints, 1);
+    assert_eq!(u128::from(rollup.accrued), (rate * 2 * 3600) >> 64);
+    assert_eq!(rollup.timestamp, now.to_le_bytes().to_vec());
+}
+
+#[test]
+fn uncounted_tickets_are_left_alone() {
+    let withdrawn_by_owner = ticket(0, 0);
+    let withdrawn_by_program = ticket(-1, 0);
+    let pending = ticket(GENESIS, GENESIS + 600);
+    let frozen = ticket(GENESIS, FROZEN_TICKET);
+    for ticket in [withdrawn_by_owner, withdrawn_by_program, pending, frozen].iter() {
+        let mut stake = stake();
+        let mut rollup = rollup();
+        stake
+            .uncount_ticket(ticket, &mut rollup, GENESIS + 3600)
+            .unwrap();
+        assert_eq!(rollup.mints, 2);
+        assert_eq!(stake.total_mints, 2);
+        assert_eq!(rollup.accrued, 0);
+    }
+}
+
+#[test]
+fn withdrawing_all_rejects_a_duplicated_rollup() {
+    let first = Rollup { gid: 1, ..rollup() };
+    let second = Rollup { gid: 2, ..rollup() };
+    assert!(Rollup::check_distinct([&first, &second]).is_ok());
+    assert!(Rollup::check_distinct([&first, &second, &first]).is_err());
+}
+
+#[test]
+fn updating_the_epoch_credits_the_rollup_first() {
+    let mut stake = stake();
+    let mut rollup = rollup();
+    let mut ticket = ticket(GENESIS, 0);
+    let (timestamp, now) = (GENESIS + 3600, GENESIS + 7200);
+    let rate = stake.reward_rate().unwrap();
+
+    stake
+        .set_entity_epoch(&mut ticket, &mut rollup, timestamp, now)
+        .unwrap();
+    assert_eq!(u128::from(rollup.accrued), (rate * 2 * 3600) >> 64);
+    assert_eq!(stake.rewards_credited, rollup.accrued);
+    assert_eq!(rollup.timestamp, timestamp.to_le_bytes().to_vec());
+    assert_eq!(ticket.enrollment_epoch, timestamp.to_le_bytes().to_vec());
+    assert_eq!(rollup.mints, 2);
+    assert_eq!(stake.total_mints, 2);
+}
+
+#[test]
+fn updating_the_epoch_rejects_timestamps_out_of_bounds() {
+    let now = GENESIS + 7200;
+    for timestamp in [GENESIS - 1, now + 1, 0, -1].iter() {
+        let mut stake = stake();
+        let mut rollup = rollup();
+        let mut ticket = ticket(GENESIS, 0);
+        assert!(stake
+            .set_entity_epoch(&mut ticket, &mut rollup, *timestamp, now)
+            .is_err());
+        assert_eq!(rollup.accrued, 0);
+        assert_eq!(rollup.timestamp, GENESIS.to_le_bytes().to_vec());
+        assert_eq!(ticket.enrollment().unwrap(), GENESIS);
+    }
+}
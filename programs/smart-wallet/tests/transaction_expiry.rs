@@ -0,0 +1,96 @@
+//! Tests for expiring stale transactions.
+//!
+//! A [Transaction] past its grace period can be marked expired, slashing its
+//! proposal bond, whether or not its owners ever approved it, since proposals
+//! nobody approves are the usual ones to go stale.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use common::key;
+use smart_wallet::{SmartWallet, Transaction, TransactionState, NO_ETA};
+
+const ETA: i64 = 1_640_000_000;
+const GRACE_PERIOD: i64 = 86_400;
+
+fn smart_wallet() -> SmartWallet {
+    SmartWallet {
+        grace_period: GRACE_PERIOD,
+        ..common::smart_wallet()
+    }
+}
+
+fn unapproved() -> Transaction {
+    Transaction {
+        eta: ETA,
+        ..common::transaction()
+    }
+}
+
+/// Expires `tx` at `now`, with stand-ins for the transaction account, holding the
+/// bond, and the treasury. Returns the bond slashed and the treasury's lamports.
+fn expire(tx: &mut Transaction, now: i64) -> smart_wallet::Result<(u64, u64)> {
+    let (tx_key, treasury_key) = (key(8), key(9));
+    let (mut tx_lamports, mut treasury_lamports) = (tx.bond, 0);
+    let (mut tx_data, mut treasury_data) = (vec![], vec![]);
+    let transaction = AccountInfo::new(
+        &tx_key,
+        false,
+        true,
+        &mut tx_lamports,
+        &mut tx_data,
+        &smart_wallet::ID,
+        false,
+        0,
+    );
+    let treasury = AccountInfo::new(
+        &treasury_key,
+        false,
+        true,
+        &mut treasury_lamports,
+        &mut treasury_data,
+        &smart_wallet::ID,
+        false,
+        0,
+    );
+    let bond_slashed = tx.expire(&smart_wallet(), &transaction, &treasury, now)?;
+    Ok((bond_slashed, treasury.lamports()))
+}
+
+#[test]
+fn unapproved_transaction_expires_after_grace_period() {
+    let mut tx = unapproved();
+    assert!(expire(&mut tx, ETA + GRACE_PERIOD - 1).is_err());
+    assert!(!tx.expired);
+
+    assert_eq!(expire(&mut tx, ETA + GRACE_PERIOD).unwrap(), (0, 0));
+    assert!(tx.expired);
+    assert_eq!(tx.state, TransactionState::Expired);
+    assert!(expire(&mut tx, ETA + GRACE_PERIOD).is_err());
+}
+
+#[test]
+fn unapproved_stale_proposal_forfeits_bond() {
+    let mut tx = Transaction {
+        bond: 50_000_000,
+        ..unapproved()
+    };
+    assert!(expire(&mut tx, ETA).is_err());
+    assert_eq!(tx.bond, 50_000_000);
+
+    assert_eq!(
+        expire(&mut tx, ETA + GRACE_PERIOD).unwrap(),
+        (50_000_000, 50_000_000)
+    );
+    assert_eq!(tx.bond, 0);
+}
+
+#[test]
+fn transaction_without_eta_never_expires() {
+    let mut tx = Transaction {
+        eta: NO_ETA,
+        ..unapproved()
+    };
+    assert!(expire(&mut tx, i64::MAX).is_err());
+    assert!(!tx.expired);
+}
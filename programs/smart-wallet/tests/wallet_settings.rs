@@ -0,0 +1,74 @@
+//! Tests for changing the settings of a [SmartWallet].
+//!
+//! Settings guarding proposals and execution must be signed by the wallet PDA, which
+//! the program only does when executing one of its transactions, so every change
+//! waits out the wallet's minimum delay. Audited-programs mode cannot lock the wallet
+//! out of its own settings.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::AccountSerialize;
+use common::{instruction, key};
+use smart_wallet::{
+    ProtocolConfig, SetExecutorAllowlist, SetGracePeriod, SetVetoAuthority, SmartWallet,
+    TXInstruction,
+};
+
+/// Validates the accounts of a setting with `try_accounts`, passing the wallet
+/// signing or not as `is_signer`.
+fn validate(is_signer: bool, try_accounts: impl Fn(&mut &[AccountInfo]) -> ProgramResult) -> bool {
+    let wallet = key(1);
+    let mut lamports = 0;
+    let mut data = vec![];
+    common::smart_wallet().try_serialize(&mut data).unwrap();
+    let accounts = [AccountInfo::new(
+        &wallet,
+        is_signer,
+        true,
+        &mut lamports,
+        &mut data,
+        &smart_wallet::ID,
+        false,
+        0,
+    )];
+    try_accounts(&mut &accounts[..]).is_ok()
+}
+
+/// Validates the accounts of the setting `$accounts`, signed or not by the wallet.
+macro_rules! setting {
+    ($accounts:ident, $is_signer:expr) => {
+        validate($is_signer, |accounts| {
+            $accounts::try_accounts(&smart_wallet::ID, accounts, &[]).map(|_| ())
+        })
+    };
+}
+
+#[test]
+fn settings_must_be_signed_by_the_wallet() {
+    assert!(setting!(SetGracePeriod, true));
+    assert!(!setting!(SetGracePeriod, false));
+    assert!(setting!(SetVetoAuthority, true));
+    assert!(!setting!(SetVetoAuthority, false));
+    assert!(setting!(SetExecutorAllowlist, true));
+    assert!(!setting!(SetExecutorAllowlist, false));
+}
+
+#[test]
+fn audited_wallets_can_change_their_settings() {
+    let smart_wallet = SmartWallet {
+        audited_programs_only: true,
+        ..common::smart_wallet()
+    };
+    let config = ProtocolConfig::default();
+    let setting = TXInstruction {
+        program_id: smart_wallet::ID,
+        ..instruction(vec![])
+    };
+    config
+        .check_invoke(&smart_wallet, std::slice::from_ref(&setting))
+        .unwrap();
+    assert!(config
+        .check_invoke(&smart_wallet, &[instruction(vec![])])
+        .is_err());
+}
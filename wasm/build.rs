@@ -0,0 +1,246 @@
+//! Collects the seeds declared by the `seeds` constraints of the program's
+//! `#[derive(Accounts)]` structs, in the `pda` format of the Anchor IDL.
+//!
+//! The Anchor 0.19 IDL does not include seeds, so they are read from the same
+//! source the IDL is generated from and written to `$OUT_DIR/pda_seeds.json`.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::{env, fs};
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, BareFnArg, Expr, Fields, GenericArgument, Item, ItemStruct, Lit, PathArguments,
+    Token, Type,
+};
+
+const PROGRAM_SRC: &str = "../programs/smart-wallet/src";
+
+/// The fields of the program's structs, by struct name.
+type Structs = HashMap<String, HashMap<String, Type>>;
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", PROGRAM_SRC);
+    let mut files = vec![];
+    for entry in fs::read_dir(PROGRAM_SRC).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "rs") {
+            println!("cargo:rerun-if-changed={}", path.display());
+            files.push(syn::parse_file(&fs::read_to_string(&path).unwrap()).unwrap());
+        }
+    }
+
+    let mut constants = HashMap::new();
+    let mut structs = Structs::new();
+    let mut accounts = vec![];
+    for item in files.iter().flat_map(|file| &file.items) {
+        match item {
+            Item::Const(item) => {
+                if let Expr::Lit(expr) = &*item.expr {
+                    if let Lit::ByteStr(value) = &expr.lit {
+                        constants.insert(item.ident.to_string(), value.value());
+                    }
+                }
+            }
+            Item::Struct(item) => {
+                structs.insert(item.ident.to_string(), fields(item));
+                if derives_accounts(item) {
+                    accounts.push(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let pdas: Vec<Value> = accounts
+        .into_iter()
+        .flat_map(|item| pdas(item, &constants, &structs))
+        .collect();
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("pda_seeds.json");
+    fs::write(out, Value::Array(pdas).to_string()).unwrap();
+}
+
+fn fields(item: &ItemStruct) -> HashMap<String, Type> {
+    match &item.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| (field.ident.as_ref().unwrap().to_string(), field.ty.clone()))
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+fn derives_accounts(item: &ItemStruct) -> bool {
+    item.attrs
+        .iter()
+        .any(|attr| attr.path.is_ident("derive") && attr.tokens.to_string().contains("Accounts"))
+}
+
+/// The program derived accounts of the accounts struct `item`, and their seeds.
+fn pdas(item: &ItemStruct, constants: &HashMap<String, Vec<u8>>, structs: &Structs) -> Vec<Value> {
+    let args: HashMap<String, Type> = item
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("instruction"))
+        .flat_map(|attr| {
+            attr.parse_args_with(Punctuated::<BareFnArg, Token![,]>::parse_terminated)
+                .unwrap()
+        })
+        .map(|arg| (arg.name.unwrap().0.to_string(), arg.ty))
+        .collect();
+    let accounts = fields(item);
+    let seeds_of = |expr: &Expr| seed(expr, constants, structs, &args, &accounts);
+
+    let mut pdas = vec![];
+    if let Fields::Named(fields) = &item.fields {
+        for field in &fields.named {
+            let seeds = match field.attrs.iter().find_map(declared_seeds) {
+                Some(seeds) => seeds,
+                None => continue,
+            };
+            // Seeds read from other programs' accounts cannot be described.
+            let seeds: Option<Vec<Value>> = seeds.iter().map(seeds_of).collect();
+            if let Some(seeds) = seeds {
+                pdas.push(json!({
+                    "accounts": item.ident.to_string(),
+                    "name": camel_case(&field.ident.as_ref().unwrap().to_string()),
+                    "account": account_type(&field.ty),
+                    "pda": { "seeds": seeds },
+                }));
+            }
+        }
+    }
+    pdas
+}
+
+/// The expressions of the `seeds` constraint in the `#[account]` attribute `attr`.
+fn declared_seeds(attr: &Attribute) -> Option<Vec<Expr>> {
+    if !attr.path.is_ident("account") {
+        return None;
+    }
+    let tokens: Vec<_> = match attr.tokens.clone().into_iter().next()? {
+        proc_macro2::TokenTree::Group(group) => group.stream().into_iter().collect(),
+        _ => return None,
+    };
+    tokens.windows(3).find_map(|window| match window {
+        [proc_macro2::TokenTree::Ident(ident), proc_macro2::TokenTree::Punct(eq), proc_macro2::TokenTree::Group(seeds)]
+            if ident == "seeds" && eq.as_char() == '=' =>
+        {
+            let parser = Punctuated::<Expr, Token![,]>::parse_terminated;
+            Some(syn::parse::Parser::parse2(parser, seeds.stream()).unwrap().into_iter().collect())
+        }
+        _ => None,
+    })
+}
+
+/// Describes the seed `expr` as a constant, an instruction argument or an account.
+fn seed(
+    expr: &Expr,
+    constants: &HashMap<String, Vec<u8>>,
+    structs: &Structs,
+    args: &HashMap<String, Type>,
+    accounts: &HashMap<String, Type>,
+) -> Option<Value> {
+    let mut expr = expr;
+    let mut le_bytes = false;
+    while let Expr::MethodCall(call) = expr {
+        le_bytes |= call.method == "to_le_bytes";
+        expr = &call.receiver;
+    }
+    match expr {
+        Expr::Path(path) => {
+            let name = path.path.get_ident()?.to_string();
+            if let Some(value) = constants.get(&name) {
+                let value = String::from_utf8(value.clone()).unwrap();
+                return Some(json!({ "kind": "const", "type": "string", "value": value }));
+            }
+            if let Some(ty) = args.get(&name) {
+                return Some(
+                    json!({ "kind": "arg", "type": idl_type(ty)?, "path": camel_case(&name) }),
+                );
+            }
+            accounts.get(&name)?;
+            Some(json!({ "kind": "account", "type": "publicKey", "path": camel_case(&name) }))
+        }
+        Expr::Field(field) => {
+            let account = match &*field.base {
+                Expr::Path(path) => path.path.get_ident()?.to_string(),
+                _ => return None,
+            };
+            let member = match &field.member {
+                syn::Member::Named(member) => member.to_string(),
+                _ => return None,
+            };
+            let account_ty = account_type(accounts.get(&account)?)?;
+            let ty = structs.get(&account_ty)?.get(&member)?;
+            Some(json!({
+                "kind": "account",
+                "type": idl_type(ty)?,
+                "account": account_ty,
+                "path": format!("{}.{}", camel_case(&account), camel_case(&member)),
+            }))
+        }
+        Expr::Lit(lit) if le_bytes => match &lit.lit {
+            Lit::Int(int) => Some(json!({
+                "kind": "const",
+                "type": int.suffix(),
+                "value": int.base10_parse::<u64>().ok()?,
+            })),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The IDL name of the seed type `ty`.
+fn idl_type(ty: &Type) -> Option<String> {
+    let name = type_name(ty)?;
+    match name.as_str() {
+        "Pubkey" => Some("publicKey".to_string()),
+        "u8" | "u16" | "u32" | "u64" | "i64" => Some(name),
+        _ => None,
+    }
+}
+
+/// The program account type of the field type `ty`, e.g. `Stake` for
+/// `Box<Account<'info, Stake>>`.
+fn account_type(ty: &Type) -> Option<String> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => &args.args,
+        _ => return None,
+    };
+    let inner = args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })?;
+    match segment.ident.to_string().as_str() {
+        "Box" => account_type(inner),
+        "Account" | "Loader" => type_name(inner),
+        _ => None,
+    }
+}
+
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => Some(path.path.segments.last()?.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut words = name.split('_');
+    let mut camel = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
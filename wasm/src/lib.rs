@@ -0,0 +1,636 @@
+//! WebAssembly bindings for Goki smart wallet front-ends.
+//!
+//! Addresses are derived and accounts decoded with the same code the program uses.
+//! Pubkeys are passed and returned as base58 strings. Decoded accounts are plain
+//! JS objects, with 64-bit integers as decimal strings so they keep their precision.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use serde_json::{json, Value};
+use smart_wallet::{
+    AllowedParticipant, AllowedProposer, ExecutionReceipt, FeeExemption, InvokerWhitelist, OneShot,
+    OwnerHeartbeat, OwnerNotification, OwnerRewards, ProposerActivity, ProtocolConfig, RentPool,
+    Rollup, SmartWallet, Stake, SubaccountInfo, SubaccountType, TXInstruction, Ticket, Transaction,
+};
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// A program derived address and its bump seed.
+#[wasm_bindgen]
+pub struct ProgramAddress {
+    address: Pubkey,
+    bump: u8,
+}
+
+#[wasm_bindgen]
+impl ProgramAddress {
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.address.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+}
+
+impl From<(Pubkey, u8)> for ProgramAddress {
+    fn from((address, bump): (Pubkey, u8)) -> Self {
+        ProgramAddress { address, bump }
+    }
+}
+
+/// Derives the address of the smart wallet created with `base`.
+#[wasm_bindgen(js_name = findSmartWalletAddress)]
+pub fn find_smart_wallet_address(base: &str) -> Result<ProgramAddress, JsValue> {
+    Ok(SmartWallet::address(parse_pubkey(base)?).into())
+}
+
+/// Derives the address of a subaccount. `subaccount_type` is `"derived"` or `"ownerInvoker"`.
+#[wasm_bindgen(js_name = findSubaccountAddress)]
+pub fn find_subaccount_address(
+    smart_wallet: &str,
+    subaccount_type: &str,
+    index: u64,
+) -> Result<ProgramAddress, JsValue> {
+    let subaccount_type = match subaccount_type {
+        "derived" => SubaccountType::Derived,
+        "ownerInvoker" => SubaccountType::OwnerInvoker,
+        _ => return Err(JsValue::from_str("unknown subaccount type")),
+    };
+    Ok(SmartWallet::subaccount_address(parse_pubkey(smart_wallet)?, subaccount_type, index).into())
+}
+
+/// Derives the address of the subaccount info of a subaccount.
+#[wasm_bindgen(js_name = findSubaccountInfoAddress)]
+pub fn find_subaccount_info_address(subaccount: &str) -> Result<ProgramAddress, JsValue> {
+    Ok(SubaccountInfo::address(parse_pubkey(subaccount)?).into())
+}
+
+/// Derives the address of the transaction of a smart wallet at `index`.
+#[wasm_bindgen(js_name = findTransactionAddress)]
+pub fn find_transaction_address(smart_wallet: &str, index: u64) -> Result<ProgramAddress, JsValue> {
+    Ok(Transaction::address(parse_pubkey(smart_wallet)?, index).into())
+}
+
+/// Derives the address of a transaction proposed with `create_proposer_transaction`.
+#[wasm_bindgen(js_name = findProposerTransactionAddress)]
+pub fn find_proposer_transaction_address(
+    smart_wallet: &str,
+    proposer: &str,
+    proposer_nonce: u64,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(Transaction::proposer_address(
+        parse_pubkey(smart_wallet)?,
+        parse_pubkey(proposer)?,
+        proposer_nonce,
+    )
+    .into())
+}
+
+/// Derives the address of the reservation of a transaction index.
+#[wasm_bindgen(js_name = findTransactionReservationAddress)]
+pub fn find_transaction_reservation_address(
+    smart_wallet: &str,
+    index: u64,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(Transaction::reservation_address(parse_pubkey(smart_wallet)?, index).into())
+}
+
+/// Derives the address of the allowed proposer record of a proposer, which proposers
+/// that are not owners, such as program PDAs, pass when proposing.
+#[wasm_bindgen(js_name = findAllowedProposerAddress)]
+pub fn find_allowed_proposer_address(
+    smart_wallet: &str,
+    proposer: &str,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(AllowedProposer::address(parse_pubkey(smart_wallet)?, parse_pubkey(proposer)?).into())
+}
+
+/// Derives the address of the notification registration of an owner. Passing it when
+/// proposing references it in the creation event.
+#[wasm_bindgen(js_name = findOwnerNotificationAddress)]
+pub fn find_owner_notification_address(
+    smart_wallet: &str,
+    owner: &str,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(OwnerNotification::address(parse_pubkey(smart_wallet)?, parse_pubkey(owner)?).into())
+}
+
+/// Derives the address of the heartbeat account of an owner.
+#[wasm_bindgen(js_name = findOwnerHeartbeatAddress)]
+pub fn find_owner_heartbeat_address(
+    smart_wallet: &str,
+    owner: &str,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(OwnerHeartbeat::address(parse_pubkey(smart_wallet)?, parse_pubkey(owner)?).into())
+}
+
+/// Derives the address of the execution receipt of the transaction at `index`.
+#[wasm_bindgen(js_name = findExecutionReceiptAddress)]
+pub fn find_execution_receipt_address(
+    smart_wallet: &str,
+    index: u64,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(ExecutionReceipt::address(parse_pubkey(smart_wallet)?, index).into())
+}
+
+/// Derives the address of the one-shot authorization of `key` at `nonce`.
+#[wasm_bindgen(js_name = findOneShotAddress)]
+pub fn find_one_shot_address(
+    smart_wallet: &str,
+    key: &str,
+    nonce: u64,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(OneShot::address(parse_pubkey(smart_wallet)?, parse_pubkey(key)?, nonce).into())
+}
+
+/// Derives the address of the proposer activity of a proposer, which must be passed
+/// when proposing to a rate-limited wallet.
+#[wasm_bindgen(js_name = findProposerActivityAddress)]
+pub fn find_proposer_activity_address(
+    smart_wallet: &str,
+    proposer: &str,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(ProposerActivity::address(parse_pubkey(smart_wallet)?, parse_pubkey(proposer)?).into())
+}
+
+/// Derives the address of the invoker whitelist of the owner invoker at `index`,
+/// which must be passed to owner invocations.
+#[wasm_bindgen(js_name = findInvokerWhitelistAddress)]
+pub fn find_invoker_whitelist_address(
+    smart_wallet: &str,
+    index: u64,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(InvokerWhitelist::address(parse_pubkey(smart_wallet)?, index).into())
+}
+
+/// Derives the address of the stake of a smart wallet at `index`.
+#[wasm_bindgen(js_name = findStakeAddress)]
+pub fn find_stake_address(smart_wallet: &str, index: u64) -> Result<ProgramAddress, JsValue> {
+    Ok(Stake::address(parse_pubkey(smart_wallet)?, index).into())
+}
+
+/// Derives the address of the ticket of a mint.
+#[wasm_bindgen(js_name = findTicketAddress)]
+pub fn find_ticket_address(smart_wallet: &str, mint: &str) -> Result<ProgramAddress, JsValue> {
+    Ok(Ticket::address(parse_pubkey(smart_wallet)?, parse_pubkey(mint)?).into())
+}
+
+/// Derives the address of the rollup of an owner's gid in a stake.
+#[wasm_bindgen(js_name = findRollupAddress)]
+pub fn find_rollup_address(stake: &str, owner: &str, gid: u16) -> Result<ProgramAddress, JsValue> {
+    Ok(Rollup::address(parse_pubkey(stake)?, parse_pubkey(owner)?, gid).into())
+}
+
+/// Derives the address of the owner rewards of an owner in a stake.
+#[wasm_bindgen(js_name = findOwnerRewardsAddress)]
+pub fn find_owner_rewards_address(stake: &str, owner: &str) -> Result<ProgramAddress, JsValue> {
+    Ok(OwnerRewards::address(parse_pubkey(stake)?, parse_pubkey(owner)?).into())
+}
+
+/// Derives the address of the protocol config, which must be passed to the
+/// instructions consulting it.
+/// Derives the address of the allowed participant record of an owner in a stake,
+/// which owners pass when registering in a stake with a participant allowlist.
+#[wasm_bindgen(js_name = findAllowedParticipantAddress)]
+pub fn find_allowed_participant_address(
+    stake: &str,
+    owner: &str,
+) -> Result<ProgramAddress, JsValue> {
+    Ok(AllowedParticipant::address(parse_pubkey(stake)?, parse_pubkey(owner)?).into())
+}
+
+/// Derives the address of the fee exemption of an account in a stake, which exempt
+/// owners pass when registering and claiming.
+#[wasm_bindgen(js_name = findFeeExemptionAddress)]
+pub fn find_fee_exemption_address(stake: &str, account: &str) -> Result<ProgramAddress, JsValue> {
+    Ok(FeeExemption::address(parse_pubkey(stake)?, parse_pubkey(account)?).into())
+}
+
+#[wasm_bindgen(js_name = findProtocolConfigAddress)]
+pub fn find_protocol_config_address() -> ProgramAddress {
+    ProtocolConfig::address().into()
+}
+
+/// Derives the address of the rent pool of a smart wallet.
+#[wasm_bindgen(js_name = findRentPoolAddress)]
+pub fn find_rent_pool_address(smart_wallet: &str) -> Result<ProgramAddress, JsValue> {
+    Ok(RentPool::address(parse_pubkey(smart_wallet)?).into())
+}
+
+/// Describes the program derived accounts of every instruction, with the seeds
+/// declared by the `seeds` constraints of the program's accounts structs, in the
+/// `pda` format of the Anchor IDL, e.g. `{ accounts: "CreateStake", name: "stake",
+/// account: "Stake", pda: { seeds: [...] } }`. Seeds are constants, with a `value`,
+/// instruction arguments or accounts, with a `path`. `publicKey` seeds are the key's
+/// bytes and integer seeds are little-endian.
+#[wasm_bindgen(js_name = pdaSeeds)]
+pub fn pda_seeds() -> Result<JsValue, JsValue> {
+    to_js(pda_seed_descriptions())
+}
+
+/// The descriptions returned by `pdaSeeds`, collected from the program's accounts
+/// structs by the build script.
+pub fn pda_seed_descriptions() -> Value {
+    serde_json::from_str(include_str!(concat!(env!("OUT_DIR"), "/pda_seeds.json"))).unwrap()
+}
+
+#[wasm_bindgen(js_name = decodeSmartWallet)]
+pub fn decode_smart_wallet(data: &[u8]) -> Result<JsValue, JsValue> {
+    let wallet: SmartWallet = decode(data)?;
+    to_js(json!({
+        "base": wallet.base.to_string(),
+        "bump": wallet.bump,
+        "threshold": wallet.threshold.to_string(),
+        "minimumDelay": wallet.minimum_delay.to_string(),
+        "gracePeriod": wallet.grace_period.to_string(),
+        "ownerSetSeqno": wallet.owner_set_seqno,
+        "numTransactions": wallet.num_transactions.to_string(),
+        "owners": keys(&wallet.owners),
+        "executorReimbursement": wallet.executor_reimbursement.map(|r| json!({
+            "vaultIndex": r.vault_index.to_string(),
+            "vaultBump": r.vault_bump,
+            "feeLamports": r.fee_lamports.to_string(),
+            "tipLamports": r.tip_lamports.to_string(),
+        })),
+        "suspendedOwners": keys(&wallet.suspended_owners),
+        "executorAllowlist": keys(&wallet.executor_allowlist),
+        "eventSeq": wallet.event_seq.to_string(),
+        "auditedProgramsOnly": wallet.audited_programs_only,
+        "numExecuted": wallet.num_executed.to_string(),
+        "lastExecutedAt": wallet.last_executed_at.to_string(),
+        "lastApprovedAt": wallet.last_approved_at.to_string(),
+        "proposerGate": wallet.proposer_gate.map(|g| json!({
+            "mint": g.mint.to_string(),
+            "minAmount": g.min_amount.to_string(),
+        })),
+        "proposalBond": wallet.proposal_bond.to_string(),
+        "proposalRateLimit": wallet.proposal_rate_limit.map(|l| json!({
+            "maxProposals": l.max_proposals,
+            "window": l.window.to_string(),
+        })),
+        "vetoAuthority": wallet.veto_authority.map(|k| k.to_string()),
+        "executionReceipts": wallet.execution_receipts,
+        "expiryCursor": wallet.expiry_cursor.to_string(),
+    }))
+}
+
+/// Computes the configuration digest a smart wallet, whose account data is `data`,
+/// would emit from `attest_configuration`.
+#[wasm_bindgen(js_name = walletConfigDigest)]
+pub fn wallet_config_digest(data: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let smart_wallet: SmartWallet = decode(data)?;
+    Ok(smart_wallet
+        .config_digest()
+        .map_err(program_error)?
+        .to_vec())
+}
+
+/// Decodes a transaction, with each instruction decoded as far as its program is known.
+#[wasm_bindgen(js_name = decodeTransaction)]
+pub fn decode_transaction(data: &[u8]) -> Result<JsValue, JsValue> {
+    let tx: Transaction = decode(data)?;
+    let instructions: Vec<Value> = tx.instructions.iter().map(instruction).collect();
+    to_js(json!({
+        "smartWallet": tx.smart_wallet.to_string(),
+        "index": tx.index.to_string(),
+        "bump": tx.bump,
+        "proposer": tx.proposer.to_string(),
+        "instructions": instructions,
+        "signers": tx.signers,
+        "ownerSetSeqno": tx.owner_set_seqno,
+        "eta": tx.eta.to_string(),
+        "executor": tx.executor.to_string(),
+        "executedAt": tx.executed_at.to_string(),
+        "expired": tx.expired,
+        "bond": tx.bond.to_string(),
+        "instructionsHash": tx.instructions_hash.map(|hash| hash.to_vec()),
+        "writableScope": tx.writable_scope.as_ref().map(|scope| keys(scope)),
+        "instructionsExecuted": tx.instructions_executed,
+        "proposerExecutes": tx.proposer_executes,
+        "instructionSigners": tx.instruction_signers,
+        "cancelled": tx.cancelled,
+        "approvalMemos": tx.approval_memos,
+        "state": match tx.state {
+            smart_wallet::TransactionState::Draft => "draft",
+            smart_wallet::TransactionState::Active => "active",
+            smart_wallet::TransactionState::Approved => "approved",
+            smart_wallet::TransactionState::Executed => "executed",
+            smart_wallet::TransactionState::Cancelled => "cancelled",
+            smart_wallet::TransactionState::Expired => "expired",
+        },
+        "dependencies": tx.dependencies.iter().map(|i| i.to_string()).collect::<Vec<_>>(),
+        "approvalNonces": tx.approval_nonces.iter().map(|n| n.to_string()).collect::<Vec<_>>(),
+    }))
+}
+
+/// Builds the message `owner` signs to approve the transaction at `transaction`,
+/// whose account data is `data`, with `approve_with_message`. `smart_wallet` is the
+/// account data of its smart wallet.
+#[wasm_bindgen(js_name = approvalMessage)]
+pub fn approval_message(
+    data: &[u8],
+    smart_wallet: &[u8],
+    domain: &str,
+    owner: &str,
+    transaction: &str,
+    expiry: i64,
+) -> Result<String, JsValue> {
+    let tx: Transaction = decode(data)?;
+    let smart_wallet: SmartWallet = decode(smart_wallet)?;
+    let owner = parse_pubkey(owner)?;
+    let owner_index = smart_wallet.owner_index(owner).map_err(program_error)?;
+    tx.approval_message(
+        domain,
+        owner,
+        owner_index,
+        parse_pubkey(transaction)?,
+        expiry,
+    )
+    .map_err(program_error)
+}
+
+/// Builds the bytes `owner` signs offline to approve the transaction whose account
+/// data is `data` with `approve_with_signature`. `smart_wallet` is the account data of
+/// its smart wallet.
+#[wasm_bindgen(js_name = approvalPayload)]
+pub fn approval_payload(data: &[u8], smart_wallet: &[u8], owner: &str) -> Result<Vec<u8>, JsValue> {
+    let tx: Transaction = decode(data)?;
+    let smart_wallet: SmartWallet = decode(smart_wallet)?;
+    let owner_index = smart_wallet
+        .owner_index(parse_pubkey(owner)?)
+        .map_err(program_error)?;
+    tx.approval_payload(owner_index).map_err(program_error)
+}
+
+#[wasm_bindgen(js_name = decodeStake)]
+pub fn decode_stake(data: &[u8]) -> Result<JsValue, JsValue> {
+    let stake: Stake = decode(data)?;
+    let reward_curve = match stake.reward_curve {
+        smart_wallet::RewardCurve::Fixed { rate } => {
+            json!({ "fixed": { "rate": rate.to_string() } })
+        }
+        smart_wallet::RewardCurve::InverseSupply { max_rate } => {
+            json!({ "inverseSupply": { "maxRate": max_rate.to_string() } })
+        }
+    };
+    to_js(json!({
+        "bump": stake.bump,
+        "duration": stake.duration,
+        "genesisEpoch": stake.genesis().map_err(program_error)?.to_string(),
+        "name": String::from_utf8_lossy(&stake.name),
+        "rewardPot": stake.reward_pot.to_string(),
+        "protectedGids": stake.protected_gids,
+        "protectedGidRanges": stake
+            .protected_gid_ranges
+            .iter()
+            .map(|r| json!({ "start": r.start, "end": r.end }))
+            .collect::<Vec<_>>(),
+        "uuid": String::from_utf8_lossy(&stake.uuid),
+        "rewardCurve": reward_curve,
+        "totalMints": stake.total_mints.to_string(),
+        "smartWallet": stake.smart_wallet.to_string(),
+        "index": stake.index.to_string(),
+        "rewardMint": stake.reward_mint.to_string(),
+        "rewardVault": stake.reward_vault.to_string(),
+        "distributionInterval": stake.distribution_interval.to_string(),
+        "rewardsCredited": stake.rewards_credited.to_string(),
+        "maxRewardsPerOwner": stake.max_rewards_per_owner.to_string(),
+        "gidRemaps": stake.gid_remaps.iter().map(|remap| json!({
+            "from": remap.from,
+            "to": remap.to,
+        })).collect::<Vec<_>>(),
+        "mintAllowlist": stake.mint_allowlist,
+        "minHoldSeconds": stake.min_hold_seconds.to_string(),
+        "enrollmentFee": stake.enrollment_fee.map(|fee| json!({
+            "mint": fee.mint.map(|mint| mint.to_string()),
+            "amount": fee.amount.to_string(),
+            "destination": fee.destination.to_string(),
+        })),
+        "metadataUri": stake.metadata_uri,
+        "requireMasterEdition": stake.require_master_edition,
+        "burnBps": stake.burn_bps,
+        "rewardEmission": stake.reward_emission.map(|e| json!({
+            "epochDuration": e.epoch_duration.to_string(),
+            "maxPerEpoch": e.max_per_epoch.to_string(),
+            "epochStart": e.epoch_start.to_string(),
+            "mintedInEpoch": e.minted_in_epoch.to_string(),
+        })),
+        "claimWindow": stake.claim_window.map(|w| match w {
+            smart_wallet::ClaimWindow::Periodic { start, period, length } => json!({
+                "periodic": {
+                    "start": start.to_string(),
+                    "period": period.to_string(),
+                    "length": length.to_string(),
+                }
+            }),
+            smart_wallet::ClaimWindow::MonthEnd { days } => json!({ "monthEnd": { "days": days } }),
+        }),
+        "participantAllowlist": stake.participant_allowlist.map(|a| match a {
+            smart_wallet::ParticipantAllowlist::Explicit => json!({ "explicit": {} }),
+            smart_wallet::ParticipantAllowlist::Merkle { root } => {
+                json!({ "merkle": { "root": root.to_vec() } })
+            }
+        }),
+        "yieldVenue": stake.yield_venue.map(|v| json!({
+            "programId": v.program_id.to_string(),
+            "deposited": v.deposited.to_string(),
+        })),
+        "ownerAtaClaims": stake.owner_ata_claims,
+        "sponsors": stake.sponsors.iter().map(|s| json!({
+            "smartWallet": s.smart_wallet.to_string(),
+            "funded": s.funded.to_string(),
+        })).collect::<Vec<_>>(),
+        "sealedParams": stake.sealed_params.as_ref().map(|sealed| json!({
+            "commitment": sealed.commitment.to_vec(),
+            "ciphertext": sealed.ciphertext,
+        })),
+        "viewers": keys(&stake.viewers),
+        "distributionCursor": stake.distribution_cursor.to_string(),
+        "bonusBudget": stake.bonus_budget.map(|budget| json!({
+            "vault": budget.vault.to_string(),
+            "funded": budget.funded.to_string(),
+            "spent": budget.spent.to_string(),
+        })),
+        "rewardRounding": match stake.reward_rounding {
+            smart_wallet::RewardRounding::Floor => "floor",
+            smart_wallet::RewardRounding::Round => "round",
+            smart_wallet::RewardRounding::Ceil => "ceil",
+        },
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeTicket)]
+pub fn decode_ticket(data: &[u8]) -> Result<JsValue, JsValue> {
+    let ticket: Ticket = decode(data)?;
+    to_js(json!({
+        "enrollmentEpoch": ticket.enrollment().map_err(program_error)?.to_string(),
+        "bump": ticket.bump,
+        "gid": ticket.gid,
+        "mint": ticket.mint.to_string(),
+        "owner": ticket.owner.to_string(),
+        "pendingUntil": ticket.pending_until.to_string(),
+        "frozen": ticket.is_frozen(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeRollup)]
+pub fn decode_rollup(data: &[u8]) -> Result<JsValue, JsValue> {
+    let rollup: Rollup = decode(data)?;
+    to_js(json!({
+        "bump": rollup.bump,
+        "timestamp": rollup.last_epoch().map_err(program_error)?.to_string(),
+        "gid": rollup.gid,
+        "mints": rollup.mints,
+        "owner": rollup.owner.to_string(),
+        "accrued": rollup.accrued.to_string(),
+        "stake": rollup.stake.to_string(),
+        "accruedDust": rollup.accrued_dust.to_string(),
+        "dustOwed": rollup.dust_owed,
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeOwnerRewards)]
+pub fn decode_owner_rewards(data: &[u8]) -> Result<JsValue, JsValue> {
+    let owner_rewards: OwnerRewards = decode(data)?;
+    to_js(json!({
+        "bump": owner_rewards.bump,
+        "stake": owner_rewards.stake.to_string(),
+        "owner": owner_rewards.owner.to_string(),
+        "claimed": owner_rewards.claimed.to_string(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeRentPool)]
+pub fn decode_rent_pool(data: &[u8]) -> Result<JsValue, JsValue> {
+    let rent_pool: RentPool = decode(data)?;
+    to_js(json!({
+        "bump": rent_pool.bump,
+        "smartWallet": rent_pool.smart_wallet.to_string(),
+        "outstanding": rent_pool.outstanding.to_string(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeSubaccountInfo)]
+pub fn decode_subaccount_info(data: &[u8]) -> Result<JsValue, JsValue> {
+    let info: SubaccountInfo = decode(data)?;
+    to_js(json!({
+        "smartWallet": info.smart_wallet.to_string(),
+        "subaccountType": match info.subaccount_type {
+            SubaccountType::Derived => "derived",
+            SubaccountType::OwnerInvoker => "ownerInvoker",
+        },
+        "index": info.index.to_string(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeOwnerNotification)]
+pub fn decode_owner_notification(data: &[u8]) -> Result<JsValue, JsValue> {
+    let notification: OwnerNotification = decode(data)?;
+    to_js(json!({
+        "bump": notification.bump,
+        "smartWallet": notification.smart_wallet.to_string(),
+        "owner": notification.owner.to_string(),
+        "notificationKey": notification.notification_key.to_string(),
+        "webhookHash": notification.webhook_hash.to_vec(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeOneShot)]
+pub fn decode_one_shot(data: &[u8]) -> Result<JsValue, JsValue> {
+    let one_shot: OneShot = decode(data)?;
+    to_js(json!({
+        "bump": one_shot.bump,
+        "smartWallet": one_shot.smart_wallet.to_string(),
+        "key": one_shot.key.to_string(),
+        "nonce": one_shot.nonce.to_string(),
+        "walletIndex": one_shot.wallet_index.to_string(),
+        "instruction": instruction(&one_shot.instruction),
+        "validFrom": one_shot.valid_from.to_string(),
+        "validUntil": one_shot.valid_until.to_string(),
+        "executedAt": one_shot.executed_at.to_string(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeExecutionReceipt)]
+pub fn decode_execution_receipt(data: &[u8]) -> Result<JsValue, JsValue> {
+    let receipt: ExecutionReceipt = decode(data)?;
+    to_js(json!({
+        "bump": receipt.bump,
+        "smartWallet": receipt.smart_wallet.to_string(),
+        "index": receipt.index.to_string(),
+        "digest": receipt.digest.to_vec(),
+        "executor": receipt.executor.to_string(),
+        "slot": receipt.slot.to_string(),
+        "executedAt": receipt.executed_at.to_string(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeOwnerHeartbeat)]
+pub fn decode_owner_heartbeat(data: &[u8]) -> Result<JsValue, JsValue> {
+    let heartbeat: OwnerHeartbeat = decode(data)?;
+    to_js(json!({
+        "bump": heartbeat.bump,
+        "smartWallet": heartbeat.smart_wallet.to_string(),
+        "owner": heartbeat.owner.to_string(),
+        "lastSeen": heartbeat.last_seen.to_string(),
+        "numHeartbeats": heartbeat.num_heartbeats.to_string(),
+    }))
+}
+
+#[wasm_bindgen(js_name = decodeInvokerWhitelist)]
+pub fn decode_invoker_whitelist(data: &[u8]) -> Result<JsValue, JsValue> {
+    let whitelist: InvokerWhitelist = decode(data)?;
+    to_js(json!({
+        "bump": whitelist.bump,
+        "smartWallet": whitelist.smart_wallet.to_string(),
+        "index": whitelist.index.to_string(),
+        "permissions": whitelist
+            .permissions
+            .iter()
+            .map(|permission| json!({
+                "programId": permission.program_id.to_string(),
+                "discriminator": permission.discriminator,
+            }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+fn parse_pubkey(s: &str) -> Result<Pubkey, JsValue> {
+    Pubkey::from_str(s).map_err(|_| JsValue::from_str(&format!("invalid pubkey: {}", s)))
+}
+
+fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T, JsValue> {
+    let mut data = data;
+    T::try_deserialize(&mut data).map_err(program_error)
+}
+
+fn program_error<E: std::fmt::Display>(err: E) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
+
+/// Renders an instruction, decoded as far as its program is known.
+fn instruction(ix: &TXInstruction) -> Value {
+    let decoded = goki_decoder::decode_instruction(ix);
+    json!({
+        "programId": decoded.program_id.to_string(),
+        "program": decoded.program,
+        "instruction": decoded.instruction,
+        "keys": decoded.accounts.iter().map(|account| json!({
+            "pubkey": account.pubkey.to_string(),
+            "isSigner": account.is_signer,
+            "isWritable": account.is_writable,
+        })).collect::<Vec<_>>(),
+        "data": decoded.data,
+    })
+}
+
+fn keys(keys: &[Pubkey]) -> Vec<String> {
+    keys.iter().map(|key| key.to_string()).collect()
+}
+
+fn to_js(value: Value) -> Result<JsValue, JsValue> {
+    js_sys::JSON::parse(&value.to_string())
+}
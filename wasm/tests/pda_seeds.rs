@@ -0,0 +1,146 @@
+//! Tests that the seeds described by `pdaSeeds` derive the program's addresses.
+//!
+//! Each description is re-derived from sample inputs and compared to the program's
+//! `address` helper for that account, called with the same inputs, so a client
+//! following the descriptions finds the same accounts the program creates.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_program;
+use serde_json::Value;
+use smart_wallet::{
+    AllowedMint, AllowedParticipant, AllowedProposer, CrankPermission, FeeExemption,
+    InvokerWhitelist, Milestone, MilestoneClaim, OneShot, OwnerHeartbeat, OwnerNotification,
+    OwnerRewards, ProposerActivity, ProtocolConfig, Quest, QuestClaim, RentPool, Rollup,
+    SmartWallet, Stake, SubaccountInfo, SubaccountType, Ticket, Transaction,
+};
+
+/// The sample input seeds of a description.
+#[derive(Default)]
+struct Inputs {
+    keys: Vec<Pubkey>,
+    ints: Vec<u64>,
+}
+
+/// The sample key passed as the input seed at `path`. The `systemProgram`
+/// account can only be the System program.
+fn key(path: &str) -> Pubkey {
+    if path == "systemProgram" {
+        return system_program::ID;
+    }
+    Pubkey::new_from_array(anchor_lang::solana_program::hash::hash(path.as_bytes()).to_bytes())
+}
+
+/// The sample integer passed as the input seed at `path`.
+fn int(path: &str) -> u64 {
+    path.len() as u64
+}
+
+/// The bytes of `seed`, a constant or a sample input, recording the inputs.
+fn seed_bytes(seed: &Value, inputs: &mut Inputs) -> Vec<u8> {
+    let ty = seed["type"].as_str().unwrap();
+    if seed["kind"] == "const" {
+        return match ty {
+            "string" => seed["value"].as_str().unwrap().as_bytes().to_vec(),
+            "u64" => seed["value"].as_u64().unwrap().to_le_bytes().to_vec(),
+            _ => panic!("unknown constant seed type {}", ty),
+        };
+    }
+    let path = seed["path"].as_str().unwrap();
+    if path != "systemProgram" {
+        match ty {
+            "publicKey" => inputs.keys.push(key(path)),
+            _ => inputs.ints.push(int(path)),
+        }
+    }
+    match ty {
+        "publicKey" => key(path).to_bytes().to_vec(),
+        "u64" => int(path).to_le_bytes().to_vec(),
+        "u16" => (int(path) as u16).to_le_bytes().to_vec(),
+        _ => panic!("unknown input seed type {}", ty),
+    }
+}
+
+/// The address the program's helper derives for the account `name` from `inputs`,
+/// or `None` if it has no helper.
+fn expected_address(name: &str, inputs: &Inputs) -> Option<Pubkey> {
+    let Inputs { keys, ints } = inputs;
+    let (address, _) = match name {
+        "protocolConfig" => ProtocolConfig::address(),
+        "smartWallet" => SmartWallet::address(keys[0]),
+        // Imported transactions are derived from the upstream transaction.
+        "transaction" if ints.is_empty() => return None,
+        "transaction" if keys.len() == 2 => {
+            Transaction::proposer_address(keys[0], keys[1], ints[0])
+        }
+        "transaction" => Transaction::address(keys[0], ints[0]),
+        "reservation" => Transaction::reservation_address(keys[0], ints[0]),
+        // The treasury is the derived wallet at index 0.
+        "treasury" => SmartWallet::subaccount_address(keys[0], SubaccountType::Derived, 0),
+        "subaccountInfo" => SubaccountInfo::address(keys[0]),
+        "allowedProposer" => AllowedProposer::address(keys[0], keys[1]),
+        "proposerActivity" => ProposerActivity::address(keys[0], keys[1]),
+        "notification" => OwnerNotification::address(keys[0], keys[1]),
+        "heartbeat" => OwnerHeartbeat::address(keys[0], keys[1]),
+        "oneShot" => OneShot::address(keys[0], keys[1], ints[0]),
+        "invokerWhitelist" => InvokerWhitelist::address(keys[0], ints[0]),
+        "rentPool" => RentPool::address(keys[0]),
+        "stake" => Stake::address(keys[0], ints[0]),
+        "ticket" => Ticket::address(keys[0], keys[1]),
+        "rollup" => Rollup::address(keys[0], keys[1], ints[0] as u16),
+        "ownerRewards" => OwnerRewards::address(keys[0], keys[1]),
+        "allowedParticipant" => AllowedParticipant::address(keys[0], keys[1]),
+        "feeExemption" => FeeExemption::address(keys[0], keys[1]),
+        "allowedMint" => AllowedMint::address(keys[0], keys[1]),
+        "crankPermission" => CrankPermission::address(keys[0], keys[1]),
+        "quest" => Quest::address(keys[0], ints[0]),
+        "questClaim" => QuestClaim::address(keys[0], keys[1]),
+        "milestone" => Milestone::address(keys[0], ints[0]),
+        "milestoneClaim" => MilestoneClaim::address(keys[0], keys[1]),
+        _ => panic!("no address helper for {}", name),
+    };
+    Some(address)
+}
+
+#[test]
+fn described_seeds_derive_program_addresses() {
+    let descriptions = goki_wasm::pda_seed_descriptions();
+    let descriptions = descriptions.as_array().unwrap();
+    let mut checked = 0;
+    for pda in descriptions {
+        let name = pda["name"].as_str().unwrap();
+        let mut inputs = Inputs::default();
+        let seeds: Vec<Vec<u8>> = pda["pda"]["seeds"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|seed| seed_bytes(seed, &mut inputs))
+            .collect();
+        let seeds: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+        let (address, _) = Pubkey::find_program_address(&seeds, &smart_wallet::ID);
+        if let Some(expected) = expected_address(name, &inputs) {
+            assert_eq!(address, expected, "{}.{}", pda["accounts"], name);
+            checked += 1;
+        }
+    }
+    assert_eq!(checked, descriptions.len() - 1);
+}
+
+#[test]
+fn created_accounts_are_described() {
+    let descriptions = goki_wasm::pda_seed_descriptions();
+    let described = |accounts: &str, name: &str| {
+        descriptions
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|pda| pda["accounts"] == accounts && pda["name"] == name)
+    };
+    assert!(described("CreateSmartWallet", "smartWallet"));
+    assert!(described("CreateTransaction", "transaction"));
+    assert!(described("CreateStake", "stake"));
+    assert!(described("RollupEntityInit", "rollup"));
+    assert!(described("RegisterEntity", "ticket"));
+    assert!(described("CreateSubaccountInfo", "subaccountInfo"));
+    // Imported wallets are derived from the upstream wallet's account data.
+    assert!(!described("ImportSmartWallet", "smartWallet"));
+}